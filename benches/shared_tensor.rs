@@ -1,3 +1,4 @@
+// Run with `cargo bench --features nightly` on a nightly toolchain.
 #![feature(test)]
 
 extern crate parenchyma;
@@ -7,7 +8,7 @@ use parenchyma::{Backend, Native, OpenCL, SharedTensor};
 use test::Bencher;
 
 fn native_backend() -> Backend {
-    Backend::new::<Native>().unwrap()
+    Backend::new::<Native<()>>().unwrap()
 }
 
 fn opencl_backend() -> Backend {
@@ -0,0 +1,63 @@
+// Run with `cargo bench --features nightly` on a nightly toolchain.
+#![feature(test)]
+
+#[macro_use(array)]
+extern crate parenchyma;
+extern crate parenchyma_blas;
+extern crate test;
+
+use parenchyma::frameworks::Native;
+use parenchyma::prelude::*;
+use parenchyma_blas::*;
+use test::Bencher;
+
+/// Creates a native backend - a single thread reproduces the serial implementations.
+fn backend(threads: Option<usize>) -> Backend<Package> {
+    let mut backend: Backend<Package> = Backend::new::<Native<_>>().unwrap();
+
+    if let Some(n) = threads {
+        backend.set_num_threads(n).unwrap();
+    }
+
+    backend
+}
+
+fn bench_dot(b: &mut Bencher, threads: Option<usize>, n: usize) {
+    let backend = backend(threads);
+    let ref x = SharedTensor::with([n], vec![1.0; n]).unwrap();
+    let ref y = SharedTensor::with([n], vec![2.0; n]).unwrap();
+    let ref mut result = SharedTensor::from([1]);
+    b.bytes = (n * 8) as u64;
+    b.iter(|| backend.dot(x, y, result).unwrap());
+}
+
+fn bench_gemm(b: &mut Bencher, threads: Option<usize>, n: usize) {
+    let backend = backend(threads);
+    let ref alpha = array![1.0].into();
+    let ref beta = array![0.0].into();
+    let ref amat = SharedTensor::with([n, n], vec![1.0; n * n]).unwrap();
+    let ref bmat = SharedTensor::with([n, n], vec![2.0; n * n]).unwrap();
+    let ref mut cmat = SharedTensor::from([n, n]);
+    let t = Transposition::NoTranspose;
+    b.iter(|| backend.gemm(alpha, t, amat, t, bmat, beta, cmat).unwrap());
+}
+
+#[bench]
+fn dot_1m_serial(b: &mut Bencher) {
+    bench_dot(b, Some(1), 1 << 20);
+}
+
+#[bench]
+fn dot_1m_parallel(b: &mut Bencher) {
+    bench_dot(b, None, 1 << 20);
+}
+
+#[bench]
+fn gemm_256_serial(b: &mut Bencher) {
+    bench_gemm(b, Some(1), 256);
+}
+
+#[bench]
+fn gemm_256_parallel(b: &mut Bencher) {
+    bench_gemm(b, None, 256);
+}
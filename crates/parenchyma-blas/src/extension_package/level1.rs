@@ -2,48 +2,48 @@ use parenchyma::error::Result;
 use parenchyma::tensor::SharedTensor;
 
 /// `Vector` consists of level 1 BLAS routines - vector operations on strided arrays.
-pub trait Vector {
+pub trait Vector: super::Fallback {
     /// Provides the asum operation.
     ///
     /// Computes the sum of the absolute values of the elements of `x`, and the saves the `result`.
     fn asum(&self, x: &SharedTensor, result: &mut SharedTensor) -> Result {
-        unimplemented!("asum")
+        super::fall_back(self, "asum", |native| native.asum(x, result))
     }
     /// Provides the axpy operation.
     ///
     /// Computes a vector `x` times a constant `a` plus a vector `y` (i.e., `a * x + y`), and then
     /// saves the result to `y`.
     fn axpy(&self, a: &SharedTensor, x: &SharedTensor, y: &mut SharedTensor) -> Result {
-        unimplemented!("axpy")
+        super::fall_back(self, "axpy", |native| native.axpy(a, x, y))
     }
     /// Provides the copy operation.
     ///
     /// Copies `from.len()` elements of vector `from` into vector `to`.
     fn copy(&self, from: &SharedTensor, to: &mut SharedTensor) -> Result {
-        unimplemented!("copy")
+        super::fall_back(self, "copy", |native| native.copy(from, to))
     }
     /// Provides the dot operation.
     ///
     /// Computes the [dot product] over `x` and `y`, and then saves the `result`.
     fn dot(&self, x: &SharedTensor, y: &SharedTensor, result: &mut SharedTensor) -> Result {
-        unimplemented!("dot")
+        super::fall_back(self, "dot", |native| native.dot(x, y, result))
     }
     /// Provides the nrm2 operation.
     ///
     /// Computes the L2 norm (i.e., the euclidean length of vector `x`), and then saves the `result`.
     fn nrm2(&self, x: &SharedTensor, result: &mut SharedTensor) -> Result {
-        unimplemented!("nrm2")
+        super::fall_back(self, "nrm2", |native| native.nrm2(x, result))
     }
     /// Provides the scal operation.
     ///
     /// Scales a vector `x` by a constant `a` (i.e., `a * x`).
     fn scal(&self, a: &SharedTensor, x: &mut SharedTensor) -> Result {
-        unimplemented!("scal")
+        super::fall_back(self, "scal", |native| native.scal(a, x))
     }
     /// Provides the swap operation.
     ///
     /// Swaps the elements of vector `x` and vector `y`.
     fn swap(&self, x: &mut SharedTensor, y: &mut SharedTensor) -> Result {
-        unimplemented!("swap")
+        super::fall_back(self, "swap", |native| native.swap(x, y))
     }
 }
\ No newline at end of file
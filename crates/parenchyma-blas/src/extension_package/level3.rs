@@ -31,7 +31,7 @@ pub struct GenericMatrix<'a> {
 
 /// The trait `Matrix` consists of level 3 BLAS routines - matrix-matrix operations, including a 
 /// general matrix multiplication.
-pub trait Matrix {
+pub trait Matrix: super::Fallback {
     /// Computes a matrix-matrix product with general matrices.
     ///
     /// # Arguments
@@ -48,6 +48,14 @@ pub trait Matrix {
         bmatrix: &SharedTensor,
         beta: &SharedTensor,
         cmatrix: &mut SharedTensor) -> Result {
-        unimplemented!()
+        super::fall_back(self, "gemm", |native|
+            native.gemm(
+                alpha,
+                amatrix_transposition,
+                amatrix,
+                bmatrix_transposition,
+                bmatrix,
+                beta,
+                cmatrix))
     }
 }
\ No newline at end of file
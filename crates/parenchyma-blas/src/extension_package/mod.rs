@@ -10,7 +10,12 @@ mod level2;
 mod level3;
 mod transpose;
 
-use parenchyma::extension_package::ExtensionPackage;
+use parenchyma::error::Result;
+use parenchyma::extension_package::{self, Capabilities, ExtensionPackage, Upcast};
+use parenchyma::frameworks::{Native, OpenCL};
+
+/// The name of the package.
+pub(crate) const NAME: &'static str = "parenchyma/blas";
 
 /// The BLAS package.
 pub enum Package {
@@ -35,10 +40,49 @@ impl Package {
 /// [RFC#1733]: https://github.com/rust-lang/rfcs/pull/1733
 pub trait Extension: Axpby + Vector + MatrixVector + Matrix { }
 
+impl<C> Upcast<C> for Extension where C: Extension + 'static {
+    fn upcast(context: &C) -> &(Extension + 'static) {
+        context
+    }
+}
+
+/// Provides the extension running the operations the framework of a context doesn't implement.
+///
+/// Implemented for every context implementing `parenchyma::extension_package::Fallback` for the 
+/// package's extension.
+pub trait Fallback {
+    /// Returns the extension running the `operation` instead, or `None` if it's unsupported.
+    fn fallback(&self, operation: &'static str) -> Option<&(Extension + 'static)>;
+}
+
+impl<C> Fallback for C where C: extension_package::Fallback<Extension> {
+    fn fallback(&self, operation: &'static str) -> Option<&(Extension + 'static)> {
+        extension_package::Fallback::fallback(self, NAME, operation)
+    }
+}
+
+/// Runs the `operation` on the extension the `context` falls back to, or returns an 
+/// `ErrorKind::Unsupported` error.
+fn fall_back<C, F>(context: &C, operation: &'static str, f: F) -> Result
+    where C: ?Sized + Fallback, F: FnOnce(&(Extension + 'static)) -> Result {
+
+    extension_package::fall_back(context.fallback(operation), NAME, operation, f)
+}
+
 impl ExtensionPackage for Package {
     type Extension = Extension;
 
     fn package_name(&self) -> &'static str {
-        return "parenchyma/blas";
+        return NAME;
+    }
+
+    fn capabilities(framework: &str) -> Capabilities {
+        let operations: &[&'static str] = match framework {
+            f if f == Native::<()>::ID => ::frameworks::native::OPERATIONS,
+            f if f == OpenCL::<()>::ID => ::frameworks::open_cl::OPERATIONS,
+            _ => &[],
+        };
+
+        Capabilities::new(NAME, operations)
     }
 }
\ No newline at end of file
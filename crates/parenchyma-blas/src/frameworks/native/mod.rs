@@ -3,6 +3,7 @@ use parenchyma::extension_package::Dependency;
 use parenchyma::frameworks::NativeContext as Context;
 use parenchyma::tensor::SharedTensor;
 
+use rayon::prelude::*;
 use rblas;
 use rblas::math::mat::Mat;
 use rblas::matrix::Matrix as IMatrix;
@@ -10,11 +11,22 @@ use rblas::matrix::Matrix as IMatrix;
 use super::super::{Extension, Package, Transposition};
 use super::super::extension_package::{Matrix, MatrixVector, Vector};
 
+/// The number of elements reduced by a task - smaller vectors are reduced serially.
+const CHUNK_LEN: usize = 16384;
+
+/// The operations implemented below (see `Package::capabilities`) - `axpby` is implemented by 
+/// every context implementing `Vector`.
+pub(crate) const OPERATIONS: &'static [&'static str] = 
+    &["asum", "axpby", "axpy", "copy", "dot", "gemm", "nrm2", "scal", "swap"];
+
 impl<P> Extension for Context<P> where P: Dependency<Package> { }
 
 impl<P> Vector for Context<P> where P: Dependency<Package> {
     fn asum(&self, x: &SharedTensor, result: &mut SharedTensor) -> Result {
-        result.as_mut_slice_unsynched()?[0] = rblas::Asum::asum(x.as_slice()?);
+        let x = x.as_slice()?;
+        result.as_mut_slice_unsynched()?[0] = self.pool().install(|| {
+            x.par_chunks(CHUNK_LEN).map(|chunk| rblas::Asum::asum(chunk)).sum()
+        });
         Ok(())
     }
 
@@ -33,12 +45,29 @@ impl<P> Vector for Context<P> where P: Dependency<Package> {
     }
 
     fn dot(&self, x: &SharedTensor, y: &SharedTensor, result: &mut SharedTensor) -> Result {
-        result.as_mut_slice_unsynched()?[0] =  rblas::Dot::dot(x.as_slice()?, y.as_slice()?);
+        let (x, y) = (x.as_slice()?, y.as_slice()?);
+        result.as_mut_slice_unsynched()?[0] = self.pool().install(|| {
+            x.par_chunks(CHUNK_LEN).zip(y.par_chunks(CHUNK_LEN))
+                .map(|(x, y)| rblas::Dot::dot(x, y))
+                .sum()
+        });
         Ok(())
     }
 
     fn nrm2(&self, x: &SharedTensor, result: &mut SharedTensor) -> Result {
-        result.as_mut_slice_unsynched()?[0] =  rblas::Nrm2::nrm2(x.as_slice()?);
+        let x = x.as_slice()?;
+        result.as_mut_slice_unsynched()?[0] = if x.len() <= CHUNK_LEN {
+            rblas::Nrm2::nrm2(x)
+        } else {
+            // the norms of the chunks are combined as a scaled sum of squares (the norm is 
+            // `scale * sqrt(ssq)`), so that the squares of large norms don't overflow
+            let (scale, ssq) = self.pool().install(|| {
+                x.par_chunks(CHUNK_LEN)
+                    .map(|chunk| (rblas::Nrm2::nrm2(chunk), 1.0))
+                    .reduce(|| (0.0, 0.0), scaled_sum_of_squares)
+            });
+            scale * ssq.sqrt()
+        };
         Ok(())
     }
 
@@ -55,6 +84,17 @@ impl<P> Vector for Context<P> where P: Dependency<Package> {
     }
 }
 
+/// Adds two scaled sums of squares `(scale, ssq)`, each standing for `scale^2 * ssq`.
+fn scaled_sum_of_squares(a: (f32, f32), b: (f32, f32)) -> (f32, f32) {
+    let ((large, large_ssq), (small, small_ssq)) = if a.0 >= b.0 { (a, b) } else { (b, a) };
+
+    if large == 0.0 {
+        (0.0, 0.0)
+    } else {
+        (large, large_ssq + small_ssq * (small / large).powi(2))
+    }
+}
+
 impl<P> Matrix for Context<P> where P: Dependency<Package> {
     fn gemm(
         self: &Self,
@@ -75,6 +115,40 @@ impl<P> Matrix for Context<P> where P: Dependency<Package> {
         let c_0 = cmatrix.shape().dimensions()[0] as i32;
         let c_1 = cmatrix.shape().dimensions().iter().skip(1).fold(1, |prod, i| prod * i) as i32;
 
+        let threads = self.num_threads();
+
+        if amatrix_transposition == Transposition::NoTranspose && threads > 1 && a_0 > 1 {
+            // the rows of A (and C) are split into one slab per thread
+            let rows = (a_0 as usize + threads - 1) / threads;
+            let weights = as_matrix(bmatrix.as_slice()?, b_0 as usize, b_1 as usize);
+            let (alpha, beta) = (alpha.as_slice()?[0], beta.as_slice()?[0]);
+            let a = amatrix.as_slice()?;
+            let c = cmatrix.as_mut_slice()?;
+
+            self.pool().install(|| {
+                c.par_chunks_mut(rows * c_1 as usize).zip(a.par_chunks(rows * a_1 as usize))
+                    .for_each(|(c, a)| {
+                        let n = a.len() / a_1 as usize;
+                        let input = as_matrix(a, n, a_1 as usize);
+                        let mut output = as_matrix(c, n, c_1 as usize);
+
+                        rblas::Gemm::gemm(
+                            &alpha, 
+                            amatrix_transposition.into(), 
+                            &input, 
+                            bmatrix_transposition.into(), 
+                            &weights, 
+                            &beta, 
+                            &mut output
+                        );
+
+                        read_from_matrix(&output, c);
+                    })
+            });
+
+            return Ok(());
+        }
+
         let input = as_matrix(amatrix.as_slice()?, a_0 as usize, a_1 as usize);
         let weights = as_matrix(bmatrix.as_slice()?, b_0 as usize, b_1 as usize);
         let mut output = as_matrix(cmatrix.as_slice()?, c_0 as usize, c_1 as usize);
@@ -2,73 +2,36 @@
 //!
 //! If possible, use 'fast-versions'. note: 'fast-versions' have specific requirements.
 //!
-//! [Cloning kernels before arguments are specified. #97][1]
-//!
 //! limits: https://stackoverflow.com/a/40272984/8034246
-//!
-//! [1](https://github.com/cogciprocate/ocl/issues/97#issuecomment-367178247)
 
-pub use self::package::OpenCLPackage;
+pub use self::package::{Gemm, GemmParameters, Level1, Level1Parameters, OpenCLPackage};
+pub use self::package::{ShapeClass, TUNING_DATABASE};
+pub use self::tuner::{DeviceTuning, Tuner, TuningDatabase};
 
 mod package;
+mod tuner;
 
 use parenchyma::error::{Error, ErrorKind, Result};
 use parenchyma::extension_package::{Dependency, ExtensionPackageCtor};
-use parenchyma::frameworks::{OpenCLContext as Context, OpenCLMemory as Memory};
+use parenchyma::frameworks::{OpenCLAccess as Access, OpenCLContext as Context, OpenCLDevice};
+use parenchyma::frameworks::OpenCLMemory as Memory;
 use parenchyma::tensor::{self, SharedTensor};
-use ocl;
 
 use super::super::{Extension, Package, Transposition};
 use super::super::extension_package::{Matrix, MatrixVector, Vector};
 
+/// The operations implemented below (see `Package::capabilities`) - `axpby` is implemented by 
+/// every context implementing `Vector`.
+pub(crate) const OPERATIONS: &'static [&'static str] = &["axpby", "axpy", "copy", "gemm", "scal"];
+
 impl<P> Extension for Context<P> where P: Dependency<Package> {
     // ..
 }
 
 impl<P> Vector for Context<P> where P: Dependency<Package> {
-    fn asum(&self, x: &SharedTensor<f32>, result: &mut SharedTensor<f32>) -> Result {
-        let length = x.shape().capacity();
-        let offset = 0i32;
-        let inc = 1i32;
-
-        unimplemented!()
-    }
-
     fn axpy(&self, a: &SharedTensor, x: &SharedTensor, y: &mut SharedTensor) -> Result {
-
-        let n = x.shape().capacity;
-        let offset = 0;
-        let inc = 1;
-
-        let alpha: &Memory<_> = tensor::reference(a, /*on:*/ self.device())?;
-        let x: &Memory<_> = tensor::reference(x, /*on:*/ self.device())?;
-        let y: &mut Memory<_> = tensor::mut_reference(y, /*on:*/ self.device())?;
-
-        unsafe {
-            // take into account the CL_DEVICE_MAX_WORK_GROUP_SIZE (given by clGetDeviceInfo()) 
-            // parameter. For bigger matrices, you will have to use more than 2 dimensions.
-            //
-            // The global size (GSZ) is the total number of work-items (WI)
-            // The local size (LSZ) is the number of work-items per work-group (WI/WG)
-            // The number of work-groups is the global size / local size, or GSZ/LSZ, or WG
-
-            ocl::Kernel::new("Xaxpy", &self.extension_package().dependency().open_cl().program)?
-                .arg_scl(n)
-                .arg_buf(alpha)
-                .arg_buf(x).arg_scl(offset).arg_scl(inc)
-                .arg_buf(y).arg_scl(offset).arg_scl(inc)
-                // .gwo(..)
-                // .gws([64 * 4,   1, 1])
-                .gws([64,   1, 1])
-                .lws([64,       1, 1])
-
-                // todo The queue must be associated with a device associated with 
-                // the kernel's program.
-                .queue(self.device().queue().clone())
-                .enq()?;
-        }
-
-        Ok(())
+        let package = self.extension_package().dependency().open_cl();
+        axpy(package.level1(self.device()), self.device(), a, x, y)
     }
 
     fn copy(&self, from: &SharedTensor, to: &mut SharedTensor) -> Result {
@@ -80,20 +43,22 @@ impl<P> Vector for Context<P> where P: Dependency<Package> {
         let to: &mut Memory<_> = tensor::mut_reference(to, /*on:*/ self.device())?;
 
         unsafe {
-            ocl::Kernel::new("Xcopy", &self.extension_package().dependency().open_cl().program)?
+            let level1 = self.extension_package().dependency().open_cl().level1(self.device());
+            let wgs = level1.parameters.wgs;
+
+            let kernel = level1.kernels.get(self.device(), "Xcopy")?
                 .arg_scl(length as i32)
                 .arg_buf(from)
                 .arg_scl(offset)
                 .arg_scl(inc)
-                .arg_buf(to)
+                .arg_buf(&*to)
                 .arg_scl(offset)
                 .arg_scl(inc)
 
-                .gws([64, 1, 1])
-                .lws([64, 1, 1])
+                .gws([ceil(length, wgs), 1, 1])
+                .lws([wgs, 1, 1]);
 
-                .queue(self.device().queue().clone())
-                .enq()?;
+            self.device().enqueue("Xcopy", &kernel, &[Access::read(from), Access::write(to)])?;
         }
 
         Ok(())
@@ -108,18 +73,20 @@ impl<P> Vector for Context<P> where P: Dependency<Package> {
         let x: &mut Memory<_> = tensor::mut_reference(x, /*on:*/ self.device())?;
 
         unsafe {
-            ocl::Kernel::new("Xscal", &self.extension_package().dependency().open_cl().program)?
+            let level1 = self.extension_package().dependency().open_cl().level1(self.device());
+            let wgs = level1.parameters.wgs;
+
+            let kernel = level1.kernels.get(self.device(), "Xscal")?
                 .arg_scl(length as i32)
                 .arg_buf(a)
-                .arg_buf(x)
+                .arg_buf(&*x)
                 .arg_scl(offset)
                 .arg_scl(inc)
 
-                .gws([64, 1, 1])
-                .lws([64, 1, 1])
+                .gws([ceil(length, wgs), 1, 1])
+                .lws([wgs, 1, 1]);
 
-                .queue(self.device().queue().clone())
-                .enq()?;
+            self.device().enqueue("Xscal", &kernel, &[Access::read(a), Access::write(x)])?;
         }
 
         Ok(())
@@ -130,6 +97,9 @@ impl<P> Matrix for Context<P> where P: Dependency<Package> {
     /// Provides the gemm operation.
     ///
     /// Computes a matrix-matrix product with general matrices.
+    ///
+    /// If data-parallel execution is enabled and `amatrix` isn't transposed, the rows of `amatrix`
+    /// and `cmatrix` (the batch) are split across the selected devices.
     fn gemm(
         self: &Self,
         alpha: &SharedTensor,
@@ -140,157 +110,237 @@ impl<P> Matrix for Context<P> where P: Dependency<Package> {
         beta: &SharedTensor,
         cmatrix: &mut SharedTensor) -> Result {
 
-        const WGD: usize = 8; // Tile-size in dimension M, N, and K (e.g. 8, 16, 32, 64)
-        const MDIMCD: usize = 8; // Threads per workgroup in M-dimension (e.g. 8, 16, 32)
-        const NDIMCD: usize = 8; // Threads per workgroup in N-dimension (e.g. 8, 16, 32)
-
-        // TODO
-        // 1) check that `c` has the correct `shape`
-
-        // rounding functions performing ceiling and division operations
-        fn ceil_div(x: usize, y: usize) -> usize { 1 + ((x - 1) / y) }
-        fn ceil(x: usize, y: usize) -> usize { ceil_div(x, y) * y }
-
-        let column_major = false;
-        let row_major = true;
-        let offset = 0;
-
-        let a_nrows = amatrix.shape().dimensions()[0];
-        //let a_ncols = amatrix.shape().dimensions()[1];
-        let a_ncols = amatrix.shape().dimensions().iter().skip(1).fold(1, |prod, d| prod * d); // ..?
-
-        let b_nrows = bmatrix.shape().dimensions()[0];
-        //let b_ncols = bmatrix.shape().dimensions()[1];
-        let b_ncols = bmatrix.shape().dimensions().iter().skip(1).fold(1, |prod, d| prod * d); // ..?
+        let (m, n, k) = 
+            dimensions(amatrix_transposition, amatrix, bmatrix_transposition, bmatrix);
+
+        // the kernels are picked for the whole problem, even if it's split across the devices
+        let class = ShapeClass::of(m, n, k);
+        let package = self.extension_package().dependency().open_cl();
+
+        match amatrix_transposition {
+            Transposition::NoTranspose => {
+                self.batched(&[amatrix], cmatrix, |device, amatrix, cmatrix| gemm_direct(
+                    package.gemm(device, class), device, 
+                    alpha, amatrix_transposition, amatrix[0], bmatrix_transposition, bmatrix, 
+                    beta, cmatrix))
+            },
+
+            _ => gemm_direct(
+                package.gemm(self.device(), class), self.device(), 
+                alpha, amatrix_transposition, amatrix, bmatrix_transposition, bmatrix, 
+                beta, cmatrix),
+        }
+    }
+}
 
-        let c_nrows = cmatrix.shape().dimensions()[0];
-        //let c_ncols = cmatrix.shape().dimensions()[1];
-        let c_ncols = cmatrix.shape().dimensions().iter().skip(1).fold(1, |prod, d| prod * d); // ..?
+impl<P> MatrixVector for Context<P> where P: Dependency<Package> {
+    // ..
+}
 
-        let n = match bmatrix_transposition {
-            Transposition::NoTranspose => b_ncols,
-            _ => b_nrows
-        };
+impl ExtensionPackageCtor<Context<()>> for super::super::Package {
+    fn package(target: &mut Context<()>) -> Result<Self> {
+        OpenCLPackage::compile(target).map(Package::OpenCL)
+    }
+}
 
-        let (m, k) = match amatrix_transposition {
-            Transposition::NoTranspose => (a_nrows, a_ncols),
-            _ => (a_ncols, a_nrows)
+/// Enqueues a direct gemm (`XgemmDirect*`) on the `device`.
+pub(in frameworks::open_cl) fn gemm_direct(
+    gemm: &Gemm,
+    device: &OpenCLDevice,
+    alpha: &SharedTensor,
+    amatrix_transposition: Transposition,
+    amatrix: &SharedTensor,
+    bmatrix_transposition: Transposition,
+    bmatrix: &SharedTensor,
+    beta: &SharedTensor,
+    cmatrix: &mut SharedTensor) -> Result {
+
+    let GemmParameters { wgd, mdimcd, ndimcd, .. } = gemm.parameters;
+
+    // TODO
+    // 1) check that `c` has the correct `shape`
+
+    let column_major = false;
+    let row_major = true;
+    let offset = 0;
+
+    //let a_ncols = amatrix.shape().dimensions()[1];
+    let a_ncols = amatrix.shape().dimensions().iter().skip(1).fold(1, |prod, d| prod * d); // ..?
+
+    //let b_ncols = bmatrix.shape().dimensions()[1];
+    let b_ncols = bmatrix.shape().dimensions().iter().skip(1).fold(1, |prod, d| prod * d); // ..?
+
+    let c_nrows = cmatrix.shape().dimensions()[0];
+    //let c_ncols = cmatrix.shape().dimensions()[1];
+    let c_ncols = cmatrix.shape().dimensions().iter().skip(1).fold(1, |prod, d| prod * d); // ..?
+
+    let (m, n, k) = dimensions(amatrix_transposition, amatrix, bmatrix_transposition, bmatrix);
+
+    // row-major: distance between two consecutive rows
+    // col-major: distance between two consecutive columns
+    let a_leading = a_ncols;
+    let b_leading = b_ncols;
+    let c_leading = c_ncols;
+
+    // =============================
+
+    // **important**:
+    //
+    // > Computes whether or not the matrices are transposed in memory. This is based on 
+    // > their layout (row or column-major) and whether or not they are requested to 
+    // > be pre-transposed. Note that the Xgemm kernel expects either matrices A 
+    // > and C (in case of row-major) or B (in case of col-major) to be transformed, so 
+    // > transposing requirements are not the same as whether or not the matrix is actually 
+    // > transposed in memory.
+
+    let a_rotated = 
+        (column_major && amatrix_transposition != Transposition::NoTranspose) || 
+        (row_major && amatrix_transposition == Transposition::NoTranspose);
+
+    let b_rotated = 
+        (column_major && bmatrix_transposition != Transposition::NoTranspose) || 
+        (row_major && bmatrix_transposition == Transposition::NoTranspose);
+
+    let c_rotated = row_major == true;
+    let a_want_rotated = false;
+    let b_want_rotated = true;
+    let c_want_rotated = false;
+    let a_do_transpose = a_rotated != a_want_rotated;
+    let b_do_transpose = b_rotated != b_want_rotated;
+    let c_do_transpose = c_rotated != c_want_rotated;
+
+    // In case of complex data-types, the transpose can also become a conjugate transpose
+    let a_conjugate = amatrix_transposition == Transposition::ConjugateTranspose;
+    let b_conjugate = bmatrix_transposition == Transposition::ConjugateTranspose;
+
+    unsafe {
+        // Retrieves the proper XgemmDirect kernel from the compiled binary
+        let name = {
+            if a_do_transpose {
+                if b_do_transpose { "XgemmDirectTT" } else { "XgemmDirectTN" }
+            } else {
+                if b_do_transpose { "XgemmDirectNT" } else { "XgemmDirectNN" }
+            }
         };
 
-        // row-major: distance between two consecutive rows
-        // col-major: distance between two consecutive columns
-        let a_leading = a_ncols;
-        let b_leading = b_ncols;
-        let c_leading = c_ncols;
+        // compute the global and local thread sizes
+        let m_ceiled = ceil(m, wgd);
+        let n_ceiled = ceil(n, wgd);
+        let global = &[(m_ceiled * mdimcd) / wgd, (n_ceiled * ndimcd) / wgd];
+        let local = &[mdimcd, ndimcd];
+
+        let alpha: &Memory<_> = tensor::reference(alpha, /*on:*/ device)?;
+        let beta: &Memory<_> = tensor::reference(beta, /*on:*/ device)?;
+        let amatrix: &Memory<_> = tensor::reference(amatrix, /*on:*/ device)?;
+        let bmatrix: &Memory<_> = tensor::reference(bmatrix, /*on:*/ device)?;
+        let cmatrix: &mut Memory<_> = tensor::mut_reference(cmatrix, /*on:*/ device)?;
+
+        // set the kernel arguments
+        let kernel = gemm.kernels.get(device, name)?
+            .arg_scl(m as i32)
+            .arg_scl(n as i32)
+            .arg_scl(k as i32)
+            .arg_buf(alpha)
+            .arg_buf(beta)
+            .arg_buf(amatrix)
+            .arg_scl(offset as i32)
+            .arg_scl(a_leading as i32)
+            .arg_buf(bmatrix)
+            .arg_scl(offset as i32)
+            .arg_scl(b_leading as i32)
+            .arg_buf(&*cmatrix)
+            .arg_scl(offset as i32)
+            .arg_scl(c_leading as i32)
+            .arg_scl(c_do_transpose as i32)
+            .arg_scl(a_conjugate as i32)
+            .arg_scl(b_conjugate as i32)
+
+            .gws(global)
+            .lws(local);
+
+        let accesses = [
+            Access::read(alpha),
+            Access::read(beta),
+            Access::read(amatrix),
+            Access::read(bmatrix),
+            Access::write(cmatrix),
+        ];
+
+        device.enqueue(name, &kernel, &accesses)?;
+    }
 
-        // =============================
+    Ok(())
+}
 
-        // **important**:
+/// Enqueues an axpy (`Xaxpy`) on the `device`.
+pub(in frameworks::open_cl) fn axpy(
+    level1: &Level1,
+    device: &OpenCLDevice,
+    a: &SharedTensor,
+    x: &SharedTensor,
+    y: &mut SharedTensor) -> Result {
+
+    let n = x.shape().capacity;
+    let offset = 0;
+    let inc = 1;
+
+    let alpha: &Memory<_> = tensor::reference(a, /*on:*/ device)?;
+    let x: &Memory<_> = tensor::reference(x, /*on:*/ device)?;
+    let y: &mut Memory<_> = tensor::mut_reference(y, /*on:*/ device)?;
+
+    unsafe {
+        // The global size (GSZ) is the total number of work-items (WI)
+        // The local size (LSZ) is the number of work-items per work-group (WI/WG)
+        // The number of work-groups is the global size / local size, or GSZ/LSZ, or WG
         //
-        // > Computes whether or not the matrices are transposed in memory. This is based on 
-        // > their layout (row or column-major) and whether or not they are requested to 
-        // > be pre-transposed. Note that the Xgemm kernel expects either matrices A 
-        // > and C (in case of row-major) or B (in case of col-major) to be transformed, so 
-        // > transposing requirements are not the same as whether or not the matrix is actually 
-        // > transposed in memory.
-
-        let a_rotated = 
-            (column_major && amatrix_transposition != Transposition::NoTranspose) || 
-            (row_major && amatrix_transposition == Transposition::NoTranspose);
-
-        let b_rotated = 
-            (column_major && bmatrix_transposition != Transposition::NoTranspose) || 
-            (row_major && bmatrix_transposition == Transposition::NoTranspose);
-
-        let c_rotated = row_major == true;
-        let a_want_rotated = false;
-        let b_want_rotated = true;
-        let c_want_rotated = false;
-        let a_do_transpose = a_rotated != a_want_rotated;
-        let b_do_transpose = b_rotated != b_want_rotated;
-        let c_do_transpose = c_rotated != c_want_rotated;
-
-        // In case of complex data-types, the transpose can also become a conjugate transpose
-        let a_conjugate = amatrix_transposition == Transposition::ConjugateTranspose;
-        let b_conjugate = bmatrix_transposition == Transposition::ConjugateTranspose;
-
-        unsafe {
-            // Retrieves the proper XgemmDirect kernel from the compiled binary
-            let kernel = {
-                if a_do_transpose {
-                    if b_do_transpose {
-                        ocl::Kernel::new(
-                            "XgemmDirectTT", 
-                            &self.extension_package().dependency().open_cl().program
-                        )?
-                    } else {
-                        ocl::Kernel::new(
-                            "XgemmDirectTN", 
-                            &self.extension_package().dependency().open_cl().program
-                        )?
-                    }
-                } else {
-                    if b_do_transpose {
-                        ocl::Kernel::new(
-                            "XgemmDirectNT", 
-                            &self.extension_package().dependency().open_cl().program
-                        )?
-                    } else {
-                        ocl::Kernel::new(
-                            "XgemmDirectNN", 
-                            &self.extension_package().dependency().open_cl().program
-                        )?
-                    }
-                }
-            };
-
-            // compute the global and local thread sizes
-            let m_ceiled = ceil(m, WGD);
-            let n_ceiled = ceil(n, WGD);
-            let global = &[(m_ceiled * MDIMCD) / WGD, (n_ceiled * NDIMCD) / WGD];
-            // let global = [
-            //     ((1 + ((m - 1) / WGD)) * WGD * m_ceiled) / WGD,
-            //     ((1 + ((n - 1) / WGD)) * WGD * n_ceiled) / WGD,
-            //     1
-            // ];
-            let local = &[MDIMCD, NDIMCD];
-
-            // set the kernel arguments
-            kernel
-                .arg_scl(m as i32)
-                .arg_scl(n as i32)
-                .arg_scl(k as i32)
-                .arg_buf(tensor::reference(alpha, /*on:*/ self.device())?: &Memory<_>)
-                .arg_buf(tensor::reference(beta, /*on:*/ self.device())?: &Memory<_>)
-                .arg_buf(tensor::reference(amatrix, /*on:*/ self.device())?: &Memory<_>)
-                .arg_scl(offset as i32)
-                .arg_scl(a_leading as i32)
-                .arg_buf(tensor::reference(bmatrix, /*on:*/ self.device())?: &Memory<_>)
-                .arg_scl(offset as i32)
-                .arg_scl(b_leading as i32)
-                .arg_buf(tensor::mut_reference(cmatrix, /*on:*/ self.device())?: &mut Memory<_>)
-                .arg_scl(offset as i32)
-                .arg_scl(c_leading as i32)
-                .arg_scl(c_do_transpose as i32)
-                .arg_scl(a_conjugate as i32)
-                .arg_scl(b_conjugate as i32)
-
-                .gws(global)
-                .lws(local)
-                .queue(self.device().queue().clone())
-                .enq()?;
-        }
-
-        Ok(())
+        // The global size is rounded up to a multiple of the local size - the kernel skips the
+        // work-items past the end of the vectors.
+        let wgs = level1.parameters.wgs;
+
+        let kernel = level1.kernels.get(device, "Xaxpy")?
+            .arg_scl(n)
+            .arg_buf(alpha)
+            .arg_buf(x).arg_scl(offset).arg_scl(inc)
+            .arg_buf(&*y).arg_scl(offset).arg_scl(inc)
+            .gws([ceil(n, wgs), 1, 1])
+            .lws([wgs, 1, 1]);
+
+        device.enqueue(
+            "Xaxpy", &kernel, &[Access::read(alpha), Access::read(x), Access::write(y)])?;
     }
+
+    Ok(())
 }
 
-impl<P> MatrixVector for Context<P> where P: Dependency<Package> {
-    // ..
+/// Returns the dimensions `(m, n, k)` of the gemm computing the product of the (transposed)
+/// matrices: `m`x`k` times `k`x`n`.
+fn dimensions(
+    amatrix_transposition: Transposition,
+    amatrix: &SharedTensor,
+    bmatrix_transposition: Transposition,
+    bmatrix: &SharedTensor) -> (usize, usize, usize) {
+
+    let rows_and_columns = |matrix: &SharedTensor| {
+        let dimensions = matrix.shape().dimensions();
+        (dimensions[0], dimensions.iter().skip(1).fold(1, |prod, d| prod * d))
+    };
+
+    let (a_nrows, a_ncols) = rows_and_columns(amatrix);
+    let (b_nrows, b_ncols) = rows_and_columns(bmatrix);
+
+    let n = match bmatrix_transposition {
+        Transposition::NoTranspose => b_ncols,
+        _ => b_nrows
+    };
+
+    let (m, k) = match amatrix_transposition {
+        Transposition::NoTranspose => (a_nrows, a_ncols),
+        _ => (a_ncols, a_nrows)
+    };
+
+    (m, n, k)
 }
 
-impl ExtensionPackageCtor<Context<()>> for super::super::Package {
-    fn package(target: &mut Context<()>) -> Result<Self> {
-        OpenCLPackage::compile(target).map(Package::OpenCL)
-    }
-}
\ No newline at end of file
+// rounding functions performing ceiling and division operations
+fn ceil_div(x: usize, y: usize) -> usize { 1 + ((x.max(1) - 1) / y) }
+fn ceil(x: usize, y: usize) -> usize { ceil_div(x, y) * y }
@@ -1,80 +1,349 @@
-use ocl;
-use std::ffi::CString;
-use parenchyma::error::Result;
-use parenchyma::frameworks::OpenCLContext;
-
-// const WGS: usize = 64;
-// const WGS1: usize = 64;
-// const WGS2: usize = 64;
-
-// /// Caches instances of `Kernel`
-// #[derive(Debug)]
-// pub struct OpenCLPackage {
-//     pub(in super) program: ocl::Program,
-//     asum: [ocl::Kernel; 2],
-//     pub(in super) axpy: ocl::Kernel,
-//     copy: ocl::Kernel,
-//     dot: [ocl::Kernel; 2],
-//     nrm2: [ocl::Kernel; 2],
-//     scal: ocl::Kernel,
-//     swap: ocl::Kernel,
-
-//     gemm_direct: Gemm,
-// }
-
-// #[derive(Debug)]
-// pub struct Gemm {
-//     tt: ocl::Kernel,
-//     tn: ocl::Kernel,
-//     nt: ocl::Kernel,
-//     nn: ocl::Kernel,
-// }
-
-/// Caches instances of `Kernel`
+use parenchyma::error::{Error, ErrorKind, Result};
+use parenchyma::frameworks::{OpenCLContext, OpenCLDevice, OpenCLKernels};
+use parenchyma::hardware::Hardware;
+use std::collections::HashMap;
+use std::env;
+use std::rc::Rc;
+
+use super::tuner::TuningDatabase;
+
+/// The environment variable naming the tuning database loaded by `OpenCLPackage::compile`.
+pub const TUNING_DATABASE: &'static str = "PARENCHYMA_BLAS_TUNING";
+
+/// The parameters the level 1 kernels are compiled with.
+///
+/// The values are passed to the compiler as preprocessor defines, and read by the host code to
+/// size the work-groups, so that both always agree.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Level1Parameters {
+    /// The local work-group size of the level 1 kernels (`WGS`).
+    pub wgs: usize,
+    /// The local work-group size of the main reduction kernels (`WGS1`).
+    pub wgs1: usize,
+    /// The local work-group size of the epilogue reduction kernels (`WGS2`).
+    pub wgs2: usize,
+}
+
+impl Level1Parameters {
+    /// Checks that the work-groups fit on the `hardware`.
+    pub fn check(&self, hardware: &Hardware) -> Result {
+        let fits = |n: usize| n > 0 && n <= hardware.max_work_group_size;
+
+        if !fits(self.wgs) || !fits(self.wgs1) || !fits(self.wgs2) {
+            let message = format!(
+                "invalid BLAS level 1 parameters for {}: {:?}", hardware.name, self);
+            return Err(Error::new(ErrorKind::InvalidConfiguration, message));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Level1Parameters {
+    fn default() -> Level1Parameters {
+        Level1Parameters { wgs: 64, wgs1: 64, wgs2: 64 }
+    }
+}
+
+/// The parameters the direct gemm kernels are compiled with.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct GemmParameters {
+    /// The tile-size in the dimensions M, N, and K (`WGD`).
+    pub wgd: usize,
+    /// The threads per work-group in the M-dimension (`MDIMCD`).
+    pub mdimcd: usize,
+    /// The threads per work-group in the N-dimension (`NDIMCD`).
+    pub ndimcd: usize,
+    /// The vector width of the loads and stores of the matrices A and C (`VWMD`).
+    pub vwmd: usize,
+    /// The vector width of the loads of the matrix B (`VWND`).
+    pub vwnd: usize,
+}
+
+impl GemmParameters {
+    /// The local memory used by a work-group, in bytes - a padded tile of both A and B.
+    pub fn local_memory(&self) -> u64 {
+        (2 * 4 * self.wgd * (self.wgd + 1)) as u64
+    }
+
+    /// Checks that the tile can be split across the threads of a work-group, and that the
+    /// work-group fits on the `hardware`.
+    pub fn check(&self, hardware: &Hardware) -> Result {
+        let divides = |threads: usize, width: usize|
+            threads > 0 && width > 0 && self.wgd % (threads * width) == 0;
+
+        let fits =
+            self.mdimcd * self.ndimcd <= hardware.max_work_group_size &&
+            (hardware.local_memory == 0 || self.local_memory() <= hardware.local_memory);
+
+        if !divides(self.mdimcd, self.vwmd) || !divides(self.ndimcd, self.vwnd) || !fits {
+            let message = format!(
+                "invalid BLAS gemm parameters for {}: {:?}", hardware.name, self);
+            return Err(Error::new(ErrorKind::InvalidConfiguration, message));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for GemmParameters {
+    fn default() -> GemmParameters {
+        GemmParameters { wgd: 8, mdimcd: 8, ndimcd: 8, vwmd: 1, vwnd: 1 }
+    }
+}
+
+/// A class of gemm problem sizes, tuned separately.
+///
+/// Small problems favour small tiles that keep every compute unit busy, whereas large problems
+/// favour large tiles that reuse more of the data loaded into local memory.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum ShapeClass {
+    /// The largest dimension is at most 256.
+    Small,
+    /// The largest dimension is at most 1024.
+    Medium,
+    /// Anything larger.
+    Large,
+}
+
+impl ShapeClass {
+    /// The shape classes, from the smallest to the largest.
+    pub const ALL: [ShapeClass; 3] = [ShapeClass::Small, ShapeClass::Medium, ShapeClass::Large];
+
+    /// Returns the class of the gemm computing an `m`x`n` matrix with an inner dimension `k`.
+    pub fn of(m: usize, n: usize, k: usize) -> ShapeClass {
+        match m.max(n).max(k) {
+            0..=256 => ShapeClass::Small,
+            257..=1024 => ShapeClass::Medium,
+            _ => ShapeClass::Large,
+        }
+    }
+
+    /// Returns the dimension of the square matrices the class is tuned with.
+    pub fn representative(&self) -> usize {
+        match *self {
+            ShapeClass::Small => 128,
+            ShapeClass::Medium => 512,
+            ShapeClass::Large => 2048,
+        }
+    }
+
+    /// Returns the name of the class in a tuning database.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            ShapeClass::Small => "small",
+            ShapeClass::Medium => "medium",
+            ShapeClass::Large => "large",
+        }
+    }
+
+    /// Parses the name of a class in a tuning database.
+    pub fn parse(name: &str) -> Option<ShapeClass> {
+        ShapeClass::ALL.iter().cloned().find(|class| class.name() == name)
+    }
+}
+
+/// The level 1 kernels (`Xaxpy`, `Xcopy`, and `Xscal`) and the parameters they're compiled with.
+#[derive(Debug)]
+pub struct Level1 {
+    pub(in frameworks::open_cl) parameters: Level1Parameters,
+    pub(in frameworks::open_cl) kernels: OpenCLKernels,
+}
+
+impl Level1 {
+    /// Compiles the level 1 kernels with the `parameters` for the `devices`.
+    pub fn compile(
+        cx: &OpenCLContext<()>,
+        devices: &[&OpenCLDevice],
+        parameters: Level1Parameters) -> Result<Level1> {
+
+        for device in devices {
+            parameters.check(&cx.hardware()[device.index()])?;
+        }
+
+        let program = cx.program_builder()
+            .devices(devices.iter().cloned())
+            .define("WGS", parameters.wgs)
+            .define("WGS1", parameters.wgs1)
+            .define("WGS2", parameters.wgs2)
+
+            .src(include_str!("source/common.cl"))
+
+            .src(include_str!("source/level1/level1.cl"))
+            .src(include_str!("source/level1/xasum.cl"))
+            .src(include_str!("source/level1/xaxpy.cl"))
+            .src(include_str!("source/level1/xcopy.cl"))
+            .src(include_str!("source/level1/xdot.cl"))
+            .src(include_str!("source/level1/xnrm2.cl"))
+            .src(include_str!("source/level1/xscal.cl"))
+            .src(include_str!("source/level1/xswap.cl"))
+
+            .build()?;
+
+        let kernels = OpenCLKernels::new(&program, devices.iter().cloned(), &[
+            "Xaxpy", "Xcopy", "Xscal",
+        ])?;
+
+        Ok(Level1 { parameters, kernels })
+    }
+}
+
+/// The direct gemm kernels (`XgemmDirect*`) and the parameters they're compiled with.
+#[derive(Debug)]
+pub struct Gemm {
+    pub(in frameworks::open_cl) parameters: GemmParameters,
+    pub(in frameworks::open_cl) kernels: OpenCLKernels,
+}
+
+impl Gemm {
+    /// Compiles the direct gemm kernels with the `parameters` for the `devices`.
+    pub fn compile(
+        cx: &OpenCLContext<()>,
+        devices: &[&OpenCLDevice],
+        parameters: GemmParameters) -> Result<Gemm> {
+
+        for device in devices {
+            parameters.check(&cx.hardware()[device.index()])?;
+        }
+
+        // the tiles of A and B are loaded in the same shape as the tile of C, so that the loads
+        // are evenly split across the threads of a work-group
+        let program = cx.program_builder()
+            .devices(devices.iter().cloned())
+            .define("WGD", parameters.wgd)
+            .define("MDIMCD", parameters.mdimcd)
+            .define("NDIMCD", parameters.ndimcd)
+            .define("MDIMAD", parameters.mdimcd)
+            .define("NDIMBD", parameters.ndimcd)
+            .define("VWMD", parameters.vwmd)
+            .define("VWND", parameters.vwnd)
+
+            .src(include_str!("source/common.cl"))
+
+            .src(include_str!("source/level3/level3.cl"))
+            .src(include_str!("source/level3/xgemm_direct_part1.cl"))
+            .src(include_str!("source/level3/xgemm_direct_part2.cl"))
+            .src(include_str!("source/level3/xgemm_direct_part3.cl"))
+
+            .build()?;
+
+        let kernels = OpenCLKernels::new(&program, devices.iter().cloned(), &[
+            "XgemmDirectTT", "XgemmDirectTN", "XgemmDirectNT", "XgemmDirectNN",
+        ])?;
+
+        Ok(Gemm { parameters, kernels })
+    }
+}
+
+/// The BLAS kernels of each of the selected devices, compiled with the parameters tuned for it.
 #[derive(Debug)]
 pub struct OpenCLPackage {
-    pub(in frameworks::open_cl) program: ocl::Program,
+    /// The level 1 kernels of each device.
+    pub(in frameworks::open_cl) level1: Vec<Rc<Level1>>,
+    /// The gemm kernels of each device and shape class.
+    pub(in frameworks::open_cl) gemm: Vec<HashMap<ShapeClass, Rc<Gemm>>>,
 }
 
 impl OpenCLPackage {
+    /// Compiles the package with the parameters of the tuning database named by the
+    /// `PARENCHYMA_BLAS_TUNING` environment variable, or with the default parameters if it
+    /// isn't set.
     pub fn compile(cx: &mut OpenCLContext<()>) -> Result<OpenCLPackage> {
-        let program = cx.program(vec![
-            CString::new(include_str!("source/common.cl")).unwrap(),
-
-            CString::new(include_str!("source/level1/level1.cl")).unwrap(),
-            CString::new(include_str!("source/level1/xasum.cl")).unwrap(),
-            CString::new(include_str!("source/level1/xaxpy.cl")).unwrap(),
-            CString::new(include_str!("source/level1/xcopy.cl")).unwrap(),
-            CString::new(include_str!("source/level1/xdot.cl")).unwrap(),
-            CString::new(include_str!("source/level1/xnrm2.cl")).unwrap(),
-            CString::new(include_str!("source/level1/xscal.cl")).unwrap(),
-            CString::new(include_str!("source/level1/xswap.cl")).unwrap(),
-
-            CString::new(include_str!("source/level3/level3.cl")).unwrap(),
-            CString::new(include_str!("source/level3/xgemm_direct_part1.cl")).unwrap(),
-            CString::new(include_str!("source/level3/xgemm_direct_part2.cl")).unwrap(),
-            CString::new(include_str!("source/level3/xgemm_direct_part3.cl")).unwrap(),
-        ])?;
+        let database = match env::var_os(TUNING_DATABASE) {
+            Some(path) => TuningDatabase::open(path)?,
+            _ => TuningDatabase::new(),
+        };
 
-        // Ok(OpenCLPackage {
-        //     asum: [ocl::Kernel::new("Xasum", &program)?, ocl::Kernel::new("XasumEpilogue", &program)?],
-        //     axpy: ocl::Kernel::new("Xaxpy", &program)?, 
-        //     copy: ocl::Kernel::new("Xcopy", &program)?, 
-        //     dot: [ocl::Kernel::new("Xdot", &program)?, ocl::Kernel::new("XdotEpilogue", &program)?],
-        //     nrm2: [ocl::Kernel::new("Xnrm2", &program)?, ocl::Kernel::new("Xnrm2Epilogue", &program)?],
-        //     scal: ocl::Kernel::new("Xscal", &program)?,
-        //     swap: ocl::Kernel::new("Xswap", &program)?,
+        OpenCLPackage::compile_with(cx, &database)
+    }
+
+    /// Compiles the package with the parameters tuned for each of the context's devices.
+    ///
+    /// A program is built once for all of the devices (and shape classes) tuned to the same 
+    /// parameters. Kernels that weren't tuned use the default parameters.
+    pub fn compile_with(
+        cx: &mut OpenCLContext<()>,
+        database: &TuningDatabase) -> Result<OpenCLPackage> {
+
+        let tunings: Vec<_> = cx.devices().iter()
+            .map(|device| database.device(&device.identifier()))
+            .collect();
+
+        let level1_parameters: Vec<_> = tunings.iter()
+            .map(|tuning| vec![tuning.and_then(|tuning| tuning.level1).unwrap_or_default()])
+            .collect();
+
+        let gemm_parameters: Vec<Vec<_>> = tunings.iter()
+            .map(|tuning| ShapeClass::ALL.iter().map(|class| {
+                tuning.and_then(|tuning| tuning.gemm.get(class).cloned()).unwrap_or_default()
+            }).collect())
+            .collect();
+
+        let level1 = compile_grouped(cx, &level1_parameters, Level1::compile)?;
+        let gemm = compile_grouped(cx, &gemm_parameters, Gemm::compile)?;
+
+        Ok(OpenCLPackage {
+            level1: level1.into_iter().map(|mut kernels| kernels.remove(0)).collect(),
+            gemm: gemm.into_iter()
+                .map(|kernels| ShapeClass::ALL.iter().cloned().zip(kernels).collect())
+                .collect(),
+        })
+    }
+
+    /// Returns the level 1 kernels of the `device`.
+    pub(in frameworks::open_cl) fn level1(&self, device: &OpenCLDevice) -> &Level1 {
+        &self.level1[device.index()]
+    }
+
+    /// Returns the gemm kernels of the `device` used for the shape `class`.
+    pub(in frameworks::open_cl) fn gemm(&self, device: &OpenCLDevice, class: ShapeClass) -> &Gemm {
+        &self.gemm[device.index()][&class]
+    }
+
+    /// Returns the parameters of the level 1 kernels of the `device`.
+    pub fn level1_parameters(&self, device: &OpenCLDevice) -> Level1Parameters {
+        self.level1(device).parameters
+    }
+
+    /// Returns the parameters of the gemm kernels of the `device` used for the shape `class`.
+    pub fn gemm_parameters(&self, device: &OpenCLDevice, class: ShapeClass) -> GemmParameters {
+        self.gemm(device, class).parameters
+    }
+}
 
-        //     gemm_direct: Gemm {
-        //         tt: ocl::Kernel::new("XgemmDirectTT", &program)?,
-        //         tn: ocl::Kernel::new("XgemmDirectTN", &program)?,
-        //         nt: ocl::Kernel::new("XgemmDirectNT", &program)?,
-        //         nn: ocl::Kernel::new("XgemmDirectNN", &program)?,
-        //     },
+/// Compiles the kernels once for each of the parameters the devices are tuned to, given the 
+/// `parameters` of each device, and returns the kernels of each device for each of its parameters.
+fn compile_grouped<T, K, F>(
+    cx: &OpenCLContext<()>,
+    parameters: &[Vec<T>],
+    compile: F) -> Result<Vec<Vec<Rc<K>>>>
+    where T: Copy + PartialEq,
+          F: Fn(&OpenCLContext<()>, &[&OpenCLDevice], T) -> Result<K> {
 
-        //     program,
-        // })
+    let mut groups: Vec<(T, Vec<&OpenCLDevice>)> = vec![];
 
-        Ok(OpenCLPackage { program })
+    for (device, parameters) in cx.devices().iter().zip(parameters) {
+        for &p in parameters {
+            match groups.iter_mut().find(|&&mut (q, _)| q == p) {
+                Some(&mut (_, ref mut devices)) => {
+                    if !devices.iter().any(|d| d.index() == device.index()) {
+                        devices.push(device);
+                    }
+                },
+                _ => groups.push((p, vec![device])),
+            }
+        }
     }
-}
\ No newline at end of file
+
+    let mut compiled = vec![];
+
+    for (p, devices) in groups {
+        compiled.push((p, Rc::new(compile(cx, &devices, p)?)));
+    }
+
+    Ok(parameters.iter().map(|parameters| parameters.iter().map(|&p| {
+        let &(_, ref kernels) = compiled.iter().find(|&&(q, _)| q == p)
+            .expect("the kernels of a device weren't compiled");
+        kernels.clone()
+    }).collect()).collect())
+}
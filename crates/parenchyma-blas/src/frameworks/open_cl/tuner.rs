@@ -0,0 +1,322 @@
+use parenchyma::error::{Error, ErrorKind, Result};
+use parenchyma::frameworks::{OpenCLContext, OpenCLDevice};
+use parenchyma::tensor::SharedTensor;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use toml;
+
+use super::super::super::Transposition;
+use super::package::{Gemm, GemmParameters, Level1, Level1Parameters, ShapeClass};
+
+/// The parameters tuned for a device.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DeviceTuning {
+    /// The parameters of the level 1 kernels, if tuned.
+    pub level1: Option<Level1Parameters>,
+    /// The parameters of the gemm kernels of each tuned shape class.
+    pub gemm: BTreeMap<ShapeClass, GemmParameters>,
+}
+
+/// The kernel parameters tuned for each device, keyed by the identifier of the device (see
+/// `OpenCLDevice::identifier`).
+///
+/// The database is stored as a TOML file:
+///
+/// ```toml
+/// ["GeForce GTX 1080 (NVIDIA Corporation, driver 390.48)".level1]
+/// wgs = 128
+/// wgs1 = 64
+/// wgs2 = 64
+///
+/// ["GeForce GTX 1080 (NVIDIA Corporation, driver 390.48)".gemm.large]
+/// wgd = 32
+/// mdimcd = 16
+/// ndimcd = 16
+/// vwmd = 2
+/// vwnd = 2
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TuningDatabase {
+    devices: BTreeMap<String, DeviceTuning>,
+}
+
+impl TuningDatabase {
+    /// Creates an empty database.
+    pub fn new() -> TuningDatabase {
+        TuningDatabase::default()
+    }
+
+    /// Reads the database from a TOML file.
+    ///
+    /// A missing file is read as an empty database, so that a database can be opened before it's
+    /// first saved.
+    pub fn open<Q>(path: Q) -> Result<TuningDatabase> where Q: AsRef<Path> {
+        let mut contents = String::new();
+
+        match File::open(path.as_ref()) {
+            Ok(mut file) => file.read_to_string(&mut contents)
+                .map_err(|e| Error::new(ErrorKind::InvalidConfiguration, e))?,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(TuningDatabase::new()),
+            Err(e) => return Err(Error::new(ErrorKind::InvalidConfiguration, e)),
+        };
+
+        TuningDatabase::from_toml(&contents)
+    }
+
+    /// Parses a database from a TOML string.
+    pub fn from_toml(contents: &str) -> Result<TuningDatabase> {
+        let value: toml::Value = contents.parse()
+            .map_err(|e: toml::de::Error| Error::new(ErrorKind::InvalidConfiguration, e))?;
+
+        let mut database = TuningDatabase::new();
+
+        for (identifier, value) in table(&value, "the tuning database")? {
+            let mut tuning = DeviceTuning::default();
+
+            for (key, value) in table(value, identifier)? {
+                match &key[..] {
+                    "level1" => tuning.level1 = Some(Level1Parameters {
+                        wgs: parameter(value, "wgs")?,
+                        wgs1: parameter(value, "wgs1")?,
+                        wgs2: parameter(value, "wgs2")?,
+                    }),
+                    "gemm" => for (class, value) in table(value, "gemm")? {
+                        let class = ShapeClass::parse(class).ok_or_else(|| {
+                            let message = format!("unknown shape class `{}`", class);
+                            Error::new(ErrorKind::InvalidConfiguration, message)
+                        })?;
+
+                        tuning.gemm.insert(class, GemmParameters {
+                            wgd: parameter(value, "wgd")?,
+                            mdimcd: parameter(value, "mdimcd")?,
+                            ndimcd: parameter(value, "ndimcd")?,
+                            vwmd: parameter(value, "vwmd")?,
+                            vwnd: parameter(value, "vwnd")?,
+                        });
+                    },
+                    _ => {
+                        let message = format!("unknown tuning key `{}`", key);
+                        return Err(Error::new(ErrorKind::InvalidConfiguration, message));
+                    }
+                }
+            }
+
+            database.devices.insert(identifier.clone(), tuning);
+        }
+
+        Ok(database)
+    }
+
+    /// Returns the database as a TOML string.
+    pub fn to_toml(&self) -> String {
+        let integers = |values: &[(&str, usize)]| toml::Value::Table(values.iter()
+            .map(|&(key, value)| (key.to_string(), toml::Value::Integer(value as i64)))
+            .collect());
+
+        let devices = self.devices.iter().map(|(identifier, tuning)| {
+            let mut table = toml::value::Table::new();
+
+            if let Some(p) = tuning.level1 {
+                let level1 = integers(&[("wgs", p.wgs), ("wgs1", p.wgs1), ("wgs2", p.wgs2)]);
+                table.insert("level1".to_string(), level1);
+            }
+
+            if !tuning.gemm.is_empty() {
+                let gemm = tuning.gemm.iter().map(|(class, p)| {
+                    let parameters = integers(&[
+                        ("wgd", p.wgd), ("mdimcd", p.mdimcd), ("ndimcd", p.ndimcd),
+                        ("vwmd", p.vwmd), ("vwnd", p.vwnd),
+                    ]);
+
+                    (class.name().to_string(), parameters)
+                });
+
+                table.insert("gemm".to_string(), toml::Value::Table(gemm.collect()));
+            }
+
+            (identifier.clone(), toml::Value::Table(table))
+        });
+
+        toml::Value::Table(devices.collect()).to_string()
+    }
+
+    /// Writes the database to a TOML file.
+    pub fn save<Q>(&self, path: Q) -> Result where Q: AsRef<Path> {
+        File::create(path.as_ref())
+            .and_then(|mut file| file.write_all(self.to_toml().as_bytes()))
+            .map_err(|e| Error::new(ErrorKind::Other, e))
+    }
+
+    /// Returns the parameters tuned for the device with the `identifier`.
+    pub fn device(&self, identifier: &str) -> Option<&DeviceTuning> {
+        self.devices.get(identifier)
+    }
+
+    /// Sets the parameters of the level 1 kernels of the device with the `identifier`.
+    pub fn insert_level1(&mut self, identifier: &str, parameters: Level1Parameters) {
+        self.entry(identifier).level1 = Some(parameters);
+    }
+
+    /// Sets the parameters of the gemm kernels of a shape `class` of the device with the
+    /// `identifier`.
+    pub fn insert_gemm(&mut self, identifier: &str, class: ShapeClass, parameters: GemmParameters) {
+        self.entry(identifier).gemm.insert(class, parameters);
+    }
+
+    fn entry(&mut self, identifier: &str) -> &mut DeviceTuning {
+        self.devices.entry(identifier.to_string()).or_insert_with(DeviceTuning::default)
+    }
+}
+
+/// Benchmarks candidate kernel parameters on the devices of a context.
+///
+/// Each candidate is compiled, launched once to warm up, and then timed over a number of
+/// repetitions. Candidates that don't fit on a device, or that fail to compile or launch, are
+/// skipped. Tuning takes a while - it's meant to be run once per device (and driver update), with
+/// the results saved to a `TuningDatabase`.
+pub struct Tuner<'a> {
+    context: &'a OpenCLContext<()>,
+    repetitions: usize,
+}
+
+impl<'a> Tuner<'a> {
+    /// Creates a tuner timing 10 repetitions of each candidate.
+    pub fn new(context: &'a OpenCLContext<()>) -> Tuner<'a> {
+        Tuner { context, repetitions: 10 }
+    }
+
+    /// Sets the number of timed repetitions of each candidate.
+    pub fn repetitions(mut self, n: usize) -> Self {
+        self.repetitions = n.max(1);
+        self
+    }
+
+    /// Tunes the kernels on each of the context's devices, and records the results in the
+    /// `database`.
+    pub fn tune(&self, database: &mut TuningDatabase) -> Result {
+        for device in self.context.devices() {
+            let identifier = device.identifier();
+            database.insert_level1(&identifier, self.tune_level1(device)?);
+
+            for &class in ShapeClass::ALL.iter() {
+                database.insert_gemm(&identifier, class, self.tune_gemm(device, class)?);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the fastest level 1 parameters on the `device`, timing an axpy.
+    pub fn tune_level1(&self, device: &OpenCLDevice) -> Result<Level1Parameters> {
+        let n = 1 << 20;
+        let ref a = SharedTensor::scalar(1.);
+        let ref x = SharedTensor::from([n]);
+        let ref mut y = SharedTensor::from([n]);
+
+        let hardware = &self.context.hardware()[device.index()];
+
+        let candidates = [32, 64, 128, 256].iter()
+            .map(|&wgs| Level1Parameters { wgs, ..Level1Parameters::default() })
+            .filter(|candidate| candidate.check(hardware).is_ok());
+
+        self.fastest(device, "level 1", candidates, |parameters| {
+            let level1 = Level1::compile(self.context, &[device], parameters)?;
+            self.time(device, || super::axpy(&level1, device, a, x, y))
+        })
+    }
+
+    /// Returns the fastest gemm parameters on the `device` for the shape `class`, timing the
+    /// product of square matrices of the class' representative size.
+    pub fn tune_gemm(&self, device: &OpenCLDevice, class: ShapeClass) -> Result<GemmParameters> {
+        let size = class.representative();
+        let ref alpha = SharedTensor::scalar(1.);
+        let ref beta = SharedTensor::scalar(0.);
+        let ref a = SharedTensor::from([size, size]);
+        let ref b = SharedTensor::from([size, size]);
+        let ref mut c = SharedTensor::from([size, size]);
+
+        let hardware = &self.context.hardware()[device.index()];
+        let mut candidates = vec![];
+
+        for &wgd in &[8, 16, 32] {
+            for &(mdimcd, ndimcd) in &[(8, 8), (16, 16)] {
+                for &vw in &[1, 2, 4] {
+                    let candidate = GemmParameters { wgd, mdimcd, ndimcd, vwmd: vw, vwnd: vw };
+
+                    if candidate.check(hardware).is_ok() {
+                        candidates.push(candidate);
+                    }
+                }
+            }
+        }
+
+        self.fastest(device, "gemm", candidates, |parameters| {
+            let gemm = Gemm::compile(self.context, &[device], parameters)?;
+            let nn = Transposition::NoTranspose;
+            self.time(device, || super::gemm_direct(&gemm, device, alpha, nn, a, nn, b, beta, c))
+        })
+    }
+
+    /// Times each of the `candidates`, and returns the fastest one.
+    fn fastest<T, I, F>(&self, device: &OpenCLDevice, kernels: &str, candidates: I, mut time: F)
+        -> Result<T>
+        where I: IntoIterator<Item = T>,
+              F: FnMut(T) -> Result<Duration>,
+              T: Copy {
+
+        let fastest = candidates.into_iter()
+            .filter_map(|candidate| time(candidate).ok().map(|duration| (duration, candidate)))
+            .min_by_key(|&(duration, _)| duration);
+
+        match fastest {
+            Some((_, candidate)) => Ok(candidate),
+            _ => {
+                let message = format!(
+                    "no {} kernel parameters could be run on {}", kernels, device.identifier());
+                Err(Error::new(ErrorKind::Other, message))
+            }
+        }
+    }
+
+    /// Times `self.repetitions` launches of the kernel, after a launch to warm up.
+    ///
+    /// The first launch also transfers the operands to the device, which isn't timed.
+    fn time<F>(&self, device: &OpenCLDevice, mut launch: F) -> Result<Duration>
+        where F: FnMut() -> Result {
+
+        launch()?;
+        device.stream().synchronize()?;
+
+        let start = Instant::now();
+
+        for _ in 0..self.repetitions {
+            launch()?;
+        }
+
+        device.stream().synchronize()?;
+
+        Ok(start.elapsed())
+    }
+}
+
+fn table<'v>(value: &'v toml::Value, name: &str) -> Result<&'v toml::value::Table> {
+    value.as_table().ok_or_else(|| {
+        let message = format!("expected a table for `{}`", name);
+        Error::new(ErrorKind::InvalidConfiguration, message)
+    })
+}
+
+/// Reads a positive integer parameter from the `table`.
+fn parameter(table: &toml::Value, key: &str) -> Result<usize> {
+    table.get(key)
+        .and_then(|value| value.as_integer())
+        .filter(|&i| i > 0)
+        .map(|i| i as usize)
+        .ok_or_else(|| {
+            let message = format!("missing or invalid tuning parameter `{}`", key);
+            Error::new(ErrorKind::InvalidConfiguration, message)
+        })
+}
@@ -50,13 +50,14 @@
 //! [BLAS]: https://en.wikipedia.org/wiki/Basic_Linear_Algebra_Subprograms
 //! [Parenchyma]: https://github.com/lychee-eng/parenchyma
 #![allow(unused_variables)]
-#![feature(non_modrs_mods, type_ascription)]
 
 extern crate ocl;
 extern crate parenchyma;
+extern crate rayon;
 extern crate rblas;
+extern crate toml;
 
-pub use self::extension_package::{Extension, GenericMatrix, Package, Transposition};
+pub use self::extension_package::{Extension, Fallback, GenericMatrix, Package, Transposition};
 pub mod frameworks;
 
 mod extension_package;
\ No newline at end of file
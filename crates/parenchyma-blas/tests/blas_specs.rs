@@ -1,5 +1,3 @@
-#![feature(rustc_private)]
-
 #[macro_use]
 extern crate lazy_static;
 #[macro_use(array)]
@@ -26,6 +24,15 @@ mod blas_specification_native {
         static ref BACKEND: TestBackend = TestBackend(Backend::new::<Native<_>>().unwrap());
     }
 
+    #[test]
+    fn it_lists_the_native_operations() {
+        let capabilities = BACKEND.capabilities();
+        let operations: Vec<_> = capabilities.operations("parenchyma/blas").collect();
+        assert_eq!(
+            operations, 
+            ["asum", "axpby", "axpy", "copy", "dot", "gemm", "nrm2", "scal", "swap"]);
+    }
+
     #[test]
     fn it_computes_correct_asum_on_native_for_f32() {
         let ref x = array![1., -2., 3.].into();
@@ -68,6 +75,17 @@ mod blas_specification_native {
         assert_eq!(&[3.], result.as_slice().unwrap());
     }
 
+    #[test]
+    fn it_computes_the_nrm2_of_large_values_without_overflowing() {
+        // the squares of the norms of the chunks would overflow
+        let n = 2 * 16384 + 1;
+        let ref x = SharedTensor::with([n], vec![1e20; n]).unwrap();
+        let ref mut result = SharedTensor::from([]);
+        BACKEND.nrm2(x, result).unwrap();
+        let expected = 1e20 * (n as f32).sqrt();
+        assert!((result.as_slice().unwrap()[0] - expected).abs() / expected < 1e-4);
+    }
+
     #[test]
     fn it_computes_correct_scal_on_native_for_f32() {
         let ref a = array![2.].into();
@@ -136,12 +154,34 @@ mod blas_specification_native {
 
         assert_eq!(&[12., 12., 30., 30.], cmat.as_slice().unwrap());
     }
+
+    #[test]
+    fn it_computes_the_same_gemm_on_any_number_of_threads() {
+        let gemm = |threads| {
+            let mut backend: Backend<Package> = Backend::new::<Native<_>>().unwrap();
+            backend.set_num_threads(threads).unwrap();
+
+            let ref alpha = array![1.0].into();
+            let ref beta = array![0.0].into();
+            let a: Vec<f32> = (0..37 * 16).map(|i| (i % 7) as f32).collect();
+            let b: Vec<f32> = (0..16 * 5).map(|i| (i % 3) as f32).collect();
+            let ref amat = SharedTensor::with([37, 16], a).unwrap();
+            let ref bmat = SharedTensor::with([16, 5], b).unwrap();
+            let ref mut cmat = SharedTensor::from([37, 5]);
+            let t = Transposition::NoTranspose;
+
+            backend.gemm(alpha, t, amat, t, bmat, beta, cmat).unwrap();
+            cmat.as_slice().unwrap().to_vec()
+        };
+
+        assert_eq!(gemm(1), gemm(4));
+    }
 }
 
 #[cfg(test)]
 mod blas_specification_opencl {
     use parenchyma::frameworks::OpenCL;
-    use parenchyma::hardware::{Hardware, HardwareKind};
+    use parenchyma::hardware::HardwareKind;
     use parenchyma::prelude::*;
     use parenchyma_blas::*;
 
@@ -164,6 +204,42 @@ mod blas_specification_opencl {
         };
     }
 
+    /// Creates a backend of two devices - the first two devices of the platform, or two 
+    /// sub-devices of its first device.
+    fn create_backend_with_two_devices() -> Backend<Package> {
+        let mut framework: OpenCL<Package> = OpenCL::new().unwrap();
+        let mut selection = framework.default_selection();
+
+        if selection.len() < 2 {
+            let compute_units = selection[0].compute_units / 2;
+            selection = framework.partition_equally(&selection[0], compute_units)
+                .expect("requires two Open CL devices, or a device that can be partitioned");
+        }
+
+        selection.truncate(2);
+        let backend = Backend::with(framework, selection).unwrap();
+        assert_eq!(backend.selection().len(), 2);
+        backend
+    }
+
+    #[test]
+    fn it_falls_back_to_native_operations_on_opencl() {
+        let capabilities = BACKEND.capabilities();
+        assert!(capabilities.supports("parenchyma/blas", "asum"));
+        assert!(capabilities.supports("parenchyma/blas", "gemm"));
+
+        let ref a = SharedTensor::scalar(2.0);
+        let ref mut x = array![1., -2., 3.].into();
+        let ref mut result = SharedTensor::scalar(0.0);
+        BACKEND.asum(x, result).unwrap();
+        assert_eq!(&[6.], result.as_slice().unwrap());
+
+        // the operands synchronized with the host remain usable by the Open CL kernels
+        BACKEND.scal(a, x).unwrap();
+        BACKEND.dot(x, x, result).unwrap();
+        assert_eq!(&[56.], result.as_slice().unwrap());
+    }
+
     #[test]
     fn it_computes_correct_axpy_on_opencl_for_f32() {
         let ref a = SharedTensor::scalar(2.0);
@@ -240,4 +316,58 @@ mod blas_specification_opencl {
         BACKEND.scal(a, x).unwrap();
         assert_eq!(&[2., 4., 6.], x.as_slice().unwrap());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn it_computes_correct_data_parallel_gemm_on_opencl_for_f32() {
+        let mut backend = create_backend_with_two_devices();
+        backend.set_data_parallel(true).unwrap();
+
+        let ref alpha = array![1.0].into();
+        let ref amat = 
+            array![
+                [2.0, 5.0], 
+                [1.0, 0.0], 
+                [0.0, 1.0],
+                [3.0, 3.0],
+                [1.0, 1.0]
+            ].into();
+
+        let ref beta = array![0.0].into();
+        let ref bmat =
+            array![
+                [4.0, 1.0, 1.0],
+                [4.0, 1.0, 1.0]
+            ].into();
+
+        let ref mut cmat = SharedTensor::from([5, 3]);
+        let transposition = Transposition::NoTranspose;
+
+        backend.gemm(alpha, transposition, amat, transposition, bmat, beta, cmat).unwrap();
+
+        assert_eq!(
+            &[28., 7., 7., 4., 1., 1., 4., 1., 1., 24., 6., 6., 8., 2., 2.], 
+            cmat.as_slice().unwrap());
+    }
+
+    #[test]
+    fn it_computes_the_same_gemm_on_one_and_on_two_devices() {
+        let gemm = |data_parallel: bool| {
+            let mut backend = create_backend_with_two_devices();
+            backend.set_data_parallel(data_parallel).unwrap();
+
+            let ref alpha = array![1.5].into();
+            let ref amat = SharedTensor::with([37, 16], (0..37 * 16)
+                .map(|i| ((i * 7) % 11) as f32 - 5.).collect::<Vec<f32>>()).unwrap();
+            let ref beta = array![0.5].into();
+            let ref bmat = SharedTensor::with([16, 5], (0..16 * 5)
+                .map(|i| ((i * 3) % 7) as f32 - 3.).collect::<Vec<f32>>()).unwrap();
+            let ref mut cmat = SharedTensor::with([37, 5], vec![1.; 37 * 5]).unwrap();
+            let t = Transposition::NoTranspose;
+
+            backend.gemm(alpha, t, amat, t, bmat, beta, cmat).unwrap();
+            cmat.as_slice().unwrap().to_vec()
+        };
+
+        assert_eq!(gemm(false), gemm(true));
+    }
+}
@@ -0,0 +1,96 @@
+extern crate parenchyma;
+extern crate parenchyma_blas;
+
+#[cfg(test)]
+mod tuning_specification {
+    use parenchyma::error::ErrorKind;
+    use parenchyma_blas::frameworks::open_cl::*;
+    use std::env;
+
+    #[test]
+    fn it_classifies_gemm_shapes_by_their_largest_dimension() {
+        assert_eq!(ShapeClass::of(1, 1, 1), ShapeClass::Small);
+        assert_eq!(ShapeClass::of(256, 16, 256), ShapeClass::Small);
+        assert_eq!(ShapeClass::of(16, 257, 16), ShapeClass::Medium);
+        assert_eq!(ShapeClass::of(1024, 1024, 1024), ShapeClass::Medium);
+        assert_eq!(ShapeClass::of(8, 8, 4096), ShapeClass::Large);
+    }
+
+    #[test]
+    fn it_round_trips_the_tuning_database() {
+        let gemm = GemmParameters { wgd: 32, mdimcd: 16, ndimcd: 16, vwmd: 2, vwnd: 2 };
+        let level1 = Level1Parameters { wgs: 128, ..Level1Parameters::default() };
+
+        let mut database = TuningDatabase::new();
+        database.insert_level1("GPU (Vendor, driver 1.0)", level1);
+        database.insert_gemm("GPU (Vendor, driver 1.0)", ShapeClass::Large, gemm);
+        database.insert_gemm("CPU (Vendor, driver 2.0)", ShapeClass::Small, Default::default());
+
+        let parsed = TuningDatabase::from_toml(&database.to_toml()).unwrap();
+        assert_eq!(parsed, database);
+
+        let tuning = parsed.device("GPU (Vendor, driver 1.0)").unwrap();
+        assert_eq!(tuning.level1, Some(level1));
+        assert_eq!(tuning.gemm.get(&ShapeClass::Large), Some(&gemm));
+        assert_eq!(tuning.gemm.get(&ShapeClass::Small), None);
+    }
+
+    #[test]
+    fn it_rejects_invalid_tuning_databases() {
+        let databases = [
+            "[\"GPU\".gemm.huge]\nwgd = 8\nmdimcd = 8\nndimcd = 8\nvwmd = 1\nvwnd = 1",
+            "[\"GPU\".gemm.small]\nwgd = 8\nmdimcd = 8",
+            "[\"GPU\".level1]\nwgs = 0\nwgs1 = 64\nwgs2 = 64",
+            "[\"GPU\"]\nthreads = 4",
+        ];
+
+        for database in &databases {
+            let e = TuningDatabase::from_toml(database).unwrap_err();
+            assert_eq!(e.kind(), ErrorKind::InvalidConfiguration);
+        }
+    }
+
+    #[test]
+    fn it_opens_a_missing_tuning_database_as_empty() {
+        let path = env::temp_dir().join("parenchyma-blas-missing-tuning.toml");
+        assert_eq!(TuningDatabase::open(&path).unwrap(), TuningDatabase::new());
+    }
+}
+
+#[cfg(test)]
+mod tuning_specification_opencl {
+    use parenchyma::context::ContextCtor;
+    use parenchyma::frameworks::{OpenCL, OpenCLContext};
+    use parenchyma::prelude::*;
+    use parenchyma_blas::frameworks::open_cl::*;
+
+    fn context() -> OpenCLContext<()> {
+        let framework: OpenCL<()> = OpenCL::new().unwrap();
+        OpenCLContext::new(&framework, &framework.default_selection()).unwrap()
+    }
+
+    #[test]
+    fn it_compiles_the_package_with_the_tuned_parameters() {
+        let mut context = context();
+        let small = GemmParameters { wgd: 16, mdimcd: 8, ndimcd: 8, vwmd: 2, vwnd: 2 };
+
+        let mut database = TuningDatabase::new();
+        database.insert_gemm(&context.device().identifier(), ShapeClass::Small, small);
+
+        let package = OpenCLPackage::compile_with(&mut context, &database).unwrap();
+
+        let device = context.device();
+        assert_eq!(package.level1_parameters(device), Level1Parameters::default());
+        assert_eq!(package.gemm_parameters(device, ShapeClass::Small), small);
+        assert_eq!(package.gemm_parameters(device, ShapeClass::Large), GemmParameters::default());
+    }
+
+    #[test]
+    fn it_tunes_the_level1_kernels() {
+        let context = context();
+        let parameters = Tuner::new(&context).repetitions(1).tune_level1(context.device()).unwrap();
+
+        assert!([32, 64, 128, 256].contains(&parameters.wgs));
+        assert!(parameters.check(&context.hardware()[0]).is_ok());
+    }
+}
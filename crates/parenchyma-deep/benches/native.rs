@@ -0,0 +1,61 @@
+// Run with `cargo bench --features nightly` on a nightly toolchain.
+#![feature(test)]
+
+extern crate parenchyma;
+extern crate parenchyma_deep;
+extern crate test;
+
+use parenchyma::frameworks::Native;
+use parenchyma::prelude::*;
+use parenchyma_deep::*;
+use test::Bencher;
+
+/// Creates a native backend - a single thread reproduces the serial implementations.
+fn backend(threads: Option<usize>) -> Backend<Package> {
+    let mut backend: Backend<Package> = Backend::new::<Native<_>>().unwrap();
+
+    if let Some(n) = threads {
+        backend.set_num_threads(n).unwrap();
+    }
+
+    backend
+}
+
+fn tensors(n: usize) -> (SharedTensor, SharedTensor) {
+    let data: Vec<f32> = (0..n).map(|i| (i % 100) as f32 / 10.0 - 5.0).collect();
+    (SharedTensor::with([n], data).unwrap(), SharedTensor::from([n]))
+}
+
+fn bench_sigmoid(b: &mut Bencher, threads: Option<usize>, n: usize) {
+    let backend = backend(threads);
+    let (ref x, ref mut result) = tensors(n);
+    b.bytes = (n * 4) as u64;
+    b.iter(|| backend.sigmoid(x, result).unwrap());
+}
+
+fn bench_softmax(b: &mut Bencher, threads: Option<usize>, n: usize) {
+    let backend = backend(threads);
+    let (ref x, ref mut result) = tensors(n);
+    b.bytes = (n * 4) as u64;
+    b.iter(|| backend.softmax(x, result).unwrap());
+}
+
+#[bench]
+fn sigmoid_1m_serial(b: &mut Bencher) {
+    bench_sigmoid(b, Some(1), 1 << 20);
+}
+
+#[bench]
+fn sigmoid_1m_parallel(b: &mut Bencher) {
+    bench_sigmoid(b, None, 1 << 20);
+}
+
+#[bench]
+fn softmax_1m_serial(b: &mut Bencher) {
+    bench_softmax(b, Some(1), 1 << 20);
+}
+
+#[bench]
+fn softmax_1m_parallel(b: &mut Bencher) {
+    bench_softmax(b, None, 1 << 20);
+}
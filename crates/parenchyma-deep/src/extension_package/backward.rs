@@ -2,7 +2,7 @@ use parenchyma::error::Result;
 use parenchyma::prelude::SharedTensor;
 use super::{ConvolutionConfiguration, LrnConfiguration, PoolingConfiguration};
 
-pub trait Backward {
+pub trait Backward: super::Fallback {
     /// Computes the gradient of a [CNN convolution] over the input tensor `x` with respect 
     /// to the data.
     ///
@@ -16,7 +16,8 @@ pub trait Backward {
         result_diff: &mut SharedTensor, 
         workspace: &mut SharedTensor<u8>, 
         configuration: &ConvolutionConfiguration) -> Result {
-        unimplemented!()
+        super::fall_back(self, "convolution_grad_data", |native|
+            native.convolution_grad_data(filter, x_diff, result_diff, workspace, configuration))
     }
     /// Computes the gradient of a [CNN convolution][convolution] with respect to the filter.
     ///
@@ -30,7 +31,9 @@ pub trait Backward {
         filter_diff: &mut SharedTensor, 
         workspace: &mut SharedTensor<u8>, 
         configuration: &ConvolutionConfiguration) -> Result {
-        unimplemented!()
+        super::fall_back(self, "convolution_grad_filter", |native|
+            native.convolution_grad_filter(
+                src_data, dest_diff, filter_diff, workspace, configuration))
     }
     /// Computes the gradient of a logarithmic softmax over the input tensor `x`.
     ///
@@ -40,7 +43,8 @@ pub trait Backward {
         x: &SharedTensor, 
         x_diff: &SharedTensor, 
         result_diff: &mut SharedTensor) -> Result {
-        unimplemented!()
+        super::fall_back(self, "log_softmax_grad", |native|
+            native.log_softmax_grad(x, x_diff, result_diff))
     }
     /// Computes the gradient of a [LRN][lrn] over the input Tensor `x` with complete memory management.
     /// [lrn]: https://en.wikipedia.org/wiki/lrnal_neural_network
@@ -55,7 +59,8 @@ pub trait Backward {
         result: &SharedTensor, 
         result_diff: &mut SharedTensor, 
         configuration: &LrnConfiguration) -> Result {
-        unimplemented!()
+        super::fall_back(self, "lrn_grad", |native|
+            native.lrn_grad(x, x_diff, result, result_diff, configuration))
     }
     /// Computes the gradient of [max pooling] over the input Tensor `x`.
     ///
@@ -69,7 +74,8 @@ pub trait Backward {
         result: &SharedTensor, 
         result_diff: &mut SharedTensor, 
         configuration: &PoolingConfiguration) -> Result {
-        unimplemented!()
+        super::fall_back(self, "pooling_max_grad", |native|
+            native.pooling_max_grad(x, x_diff, result, result_diff, configuration))
     }
     /// Computes the gradient of [ReLU] over the input tensor `x`.
     ///
@@ -82,7 +88,8 @@ pub trait Backward {
         x_diff: &SharedTensor,
         result: &SharedTensor,
         result_diff: &mut SharedTensor) -> Result {
-        unimplemented!()
+        super::fall_back(self, "relu_grad", |native|
+            native.relu_grad(x, x_diff, result, result_diff))
     }
     /// Computes the gradient of [ReLU] over the input tensor `x`.
     ///
@@ -90,7 +97,8 @@ pub trait Backward {
     ///
     /// [ReLU]: https://en.wikipedia.org/wiki/Rectifier_(neural_networks)
     fn relu_pointwise_grad(&self, x: &SharedTensor, x_diff: &mut SharedTensor) -> Result {
-        unimplemented!()
+        super::fall_back(self, "relu_pointwise_grad", |native|
+            native.relu_pointwise_grad(x, x_diff))
     }
     /// Computes the gradient of a [sigmoid function] over the input tensor `x`.
     ///
@@ -103,7 +111,8 @@ pub trait Backward {
         x_diff: &SharedTensor,
         result: &SharedTensor,
         result_diff: &mut SharedTensor) -> Result {
-        unimplemented!()
+        super::fall_back(self, "sigmoid_grad", |native|
+            native.sigmoid_grad(x, x_diff, result, result_diff))
     }
     /// Computes the gradient of a [sigmoid function] over the input tensor `x`.
     ///
@@ -111,7 +120,8 @@ pub trait Backward {
     ///
     /// [sigmoid function]: https://en.wikipedia.org/wiki/Sigmoid_function
     fn sigmoid_pointwise_grad(&self, x: &SharedTensor, x_diff: &mut SharedTensor) -> Result {
-        unimplemented!()
+        super::fall_back(self, "sigmoid_pointwise_grad", |native|
+            native.sigmoid_pointwise_grad(x, x_diff))
     }
     /// Computes the gradient of a [softmax] over the input tensor `x`.
     ///
@@ -123,7 +133,7 @@ pub trait Backward {
         x: &SharedTensor, 
         x_diff: &SharedTensor, 
         result_diff: &mut SharedTensor) -> Result {
-        unimplemented!()
+        super::fall_back(self, "softmax_grad", |native| native.softmax_grad(x, x_diff, result_diff))
     }
     /// Computes the gradient of [tanh] over the input Tensor `x`.
     ///
@@ -136,7 +146,8 @@ pub trait Backward {
         x_diff: &SharedTensor, 
         result: &SharedTensor,
         result_diff: &mut SharedTensor) -> Result {
-        unimplemented!()
+        super::fall_back(self, "tanh_grad", |native|
+            native.tanh_grad(x, x_diff, result, result_diff))
     }
     /// Computes the gradient of [tanh] over the input Tensor `x`.
     ///
@@ -144,6 +155,7 @@ pub trait Backward {
     ///
     /// [tanh]: https://en.wikipedia.org/wiki/Hyperbolic_function
     fn tanh_pointwise_grad(&self, x: &SharedTensor, x_diff: &mut SharedTensor) -> Result {
-        unimplemented!()
+        super::fall_back(self, "tanh_pointwise_grad", |native|
+            native.tanh_pointwise_grad(x, x_diff))
     }
 }
\ No newline at end of file
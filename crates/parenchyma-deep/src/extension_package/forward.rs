@@ -2,7 +2,7 @@ use parenchyma::error::Result;
 use parenchyma::prelude::SharedTensor;
 use super::{ConvolutionConfiguration, LrnConfiguration, PoolingConfiguration};
 
-pub trait Forward {
+pub trait Forward: super::Fallback {
     /// Computes a [CNN convolution] over the input tensor `x`, and then saves the `result`.
     ///
     /// [CNN convolution]: https://en.wikipedia.org/wiki/Convolutional_neural_network
@@ -13,17 +13,18 @@ pub trait Forward {
         result: &mut SharedTensor,
         workspace: &mut SharedTensor<u8>,
         configuration: &ConvolutionConfiguration) -> Result {
-        unimplemented!()
+        super::fall_back(self, "convolution", |native|
+            native.convolution(filter, x, result, workspace, configuration))
     }
     /// Computes the exponential linear unit [new] over tensor `x`.
     ///
     /// Saves the `result`.
     fn elu(&self, x: &SharedTensor, result: &mut SharedTensor) -> Result {
-        unimplemented!()
+        super::fall_back(self, "elu", |native| native.elu(x, result))
     }
     /// Computes a logarithmic softmax over the input tensor `x`, and then saves the `result`.
     fn log_softmax(&self, x: &SharedTensor, result: &mut SharedTensor) -> Result {
-        unimplemented!()
+        super::fall_back(self, "log_softmax", |native| native.log_softmax(x, result))
     }
     /// Computes a [local response normalization] over the input tensor `x`.
     ///
@@ -35,7 +36,7 @@ pub trait Forward {
         x: &SharedTensor, 
         result: &mut SharedTensor, 
         configuration: &LrnConfiguration) -> Result {
-        unimplemented!()
+        super::fall_back(self, "lrn", |native| native.lrn(x, result, configuration))
     }
     /// Computes non-linear down-sampling ([max pooling]) over the input tensor `x`.
     ///
@@ -47,7 +48,7 @@ pub trait Forward {
         x: &SharedTensor, 
         result: &mut SharedTensor, 
         configuration: &PoolingConfiguration) -> Result {
-        unimplemented!()
+        super::fall_back(self, "pooling_max", |native| native.pooling_max(x, result, configuration))
     }
     /// Computes the [rectified linear units] over tensor `x`.
     ///
@@ -55,7 +56,7 @@ pub trait Forward {
     ///
     /// [rectified linear units]: https://en.wikipedia.org/wiki/Rectifier_(neural_networks)
     fn relu(&self, x: &SharedTensor, result: &mut SharedTensor) -> Result {
-        unimplemented!()
+        super::fall_back(self, "relu", |native| native.relu(x, result))
     }
     /// Computes the [rectified linear units] over the input Tensor `x`.
     ///
@@ -63,7 +64,7 @@ pub trait Forward {
     ///
     /// [rectified linear units]: https://en.wikipedia.org/wiki/Rectifier_(neural_networks)
     fn relu_pointwise(&self, x: &mut SharedTensor) -> Result {
-        unimplemented!()
+        super::fall_back(self, "relu_pointwise", |native| native.relu_pointwise(x))
     }
     /// Computes the [sigmoid function] over tensor `x`.
     ///
@@ -71,7 +72,7 @@ pub trait Forward {
     ///
     /// [sigmoid function]: https://en.wikipedia.org/wiki/Sigmoid_function
     fn sigmoid(&self, x: &SharedTensor, result: &mut SharedTensor) -> Result {
-        unimplemented!()
+        super::fall_back(self, "sigmoid", |native| native.sigmoid(x, result))
     }
     /// Computes the [sigmoid function][sigmoid] over the input tensor `x`.
     ///
@@ -79,7 +80,7 @@ pub trait Forward {
     ///
     /// [sigmoid function]: https://en.wikipedia.org/wiki/Sigmoid_function
     fn sigmoid_pointwise(&self, x: &mut SharedTensor) -> Result {
-        unimplemented!()
+        super::fall_back(self, "sigmoid_pointwise", |native| native.sigmoid_pointwise(x))
     }
     /// Computes a [softmax] over the input tensor `x`.
     ///
@@ -87,7 +88,7 @@ pub trait Forward {
     ///
     /// [softmax]: https://en.wikipedia.org/wiki/Softmax_function
     fn softmax(&self, x: &SharedTensor, result: &mut SharedTensor) -> Result {
-        unimplemented!()
+        super::fall_back(self, "softmax", |native| native.softmax(x, result))
     }
     /// Computes the [hyperbolic tangent] over tensor `x`.
     ///
@@ -95,7 +96,7 @@ pub trait Forward {
     ///
     /// [hyperbolic tangent]: https://en.wikipedia.org/wiki/Hyperbolic_function
     fn tanh(&self, x: &SharedTensor, result: &mut SharedTensor) -> Result {
-        unimplemented!()
+        super::fall_back(self, "tanh", |native| native.tanh(x, result))
     }
     /// Computes the [hyperbolic tangent][tanh] over the input Tensor `x`.
     ///
@@ -103,6 +104,6 @@ pub trait Forward {
     ///
     /// [hyperbolic tangent]: https://en.wikipedia.org/wiki/Hyperbolic_function
     fn tanh_pointwise(&self, x: &mut SharedTensor) -> Result {
-        unimplemented!()
+        super::fall_back(self, "tanh_pointwise", |native| native.tanh_pointwise(x))
     }
 }
\ No newline at end of file
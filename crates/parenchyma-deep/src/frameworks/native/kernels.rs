@@ -0,0 +1,526 @@
+//! Runtime-dispatched SIMD kernels of the native implementations.
+//!
+//! Most kernels are plain loops without branches, written so that the compiler vectorizes them.
+//! Each one is compiled several times - with AVX-512, AVX2 (+ FMA), SSE 4.1 and the baseline of
+//! the target (SSE2 on x86-64, NEON on AArch64) enabled - and the best version supported by the 
+//! host CPU is selected at runtime. The kernels evaluating `exp`, `sigmoid` and `tanh` don't
+//! reliably vectorize that way, so they're written with the intrinsics of each instruction set 
+//! instead (see the `intrinsics` module), falling back to scalar loops on other targets. The 
+//! kernels process a single slice - the tensors are split into chunks for the thread pool by the
+//! callers.
+//!
+//! Reductions keep `LANES` partial results and combine them in a fixed order, so the results are
+//! the same regardless of the selected instruction set.
+//!
+//! # Approximations
+//!
+//! `exp`, `sigmoid` and `tanh` use polynomial approximations (adapted from Cephes) instead of the
+//! scalar functions of the standard library:
+//!
+//! | function  | domain           | maximum relative error          |
+//! |-----------|------------------|---------------------------------|
+//! | `exp`     | `[-87.3, 88.7]`  | `1.0e-7` (about 1 ULP)          |
+//! | `sigmoid` | `[-87.3, inf)`   | `2.0e-7`                        |
+//! | `tanh`    | all finite `x`   | `2.0e-7`                        |
+//!
+//! `exp` saturates to `exp(-87.3)` below its domain and to `exp(88.7)` above it, so `sigmoid` 
+//! bottoms out at about `3e-39`.
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+mod intrinsics;
+
+/// The number of elements processed per iteration (16 `f32`s fill an AVX-512 register).
+const LANES: usize = 16;
+
+/// The instruction sets the kernels are compiled for.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Isa {
+    /// The baseline of the target (e.g., SSE2 on x86-64 or NEON on AArch64).
+    Baseline,
+    Sse41,
+    Avx2,
+    Avx512,
+}
+
+/// The detected instruction set (`0` if not yet detected).
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+static ISA: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns the best instruction set supported by the host CPU.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+pub fn isa() -> Isa {
+    const ISAS: [Isa; 4] = [Isa::Baseline, Isa::Sse41, Isa::Avx2, Isa::Avx512];
+
+    match ISA.load(Ordering::Relaxed) {
+        0 => {
+            let detected = if is_x86_feature_detected!("avx512f") {
+                Isa::Avx512
+            } else if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma") {
+                Isa::Avx2
+            } else if is_x86_feature_detected!("sse4.1") {
+                Isa::Sse41
+            } else {
+                Isa::Baseline
+            };
+
+            let index = ISAS.iter().position(|&isa| isa == detected).unwrap();
+            ISA.store(index + 1, Ordering::Relaxed);
+            detected
+        },
+
+        n => ISAS[n - 1],
+    }
+}
+
+/// Returns the best instruction set supported by the host CPU.
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+pub fn isa() -> Isa {
+    Isa::Baseline
+}
+
+/// Defines a public kernel compiled for each instruction set, dispatching at runtime.
+macro_rules! kernel {
+    ($(#[$attr:meta])* pub fn $name:ident($($arg:ident: $ty:ty),*) $(-> $ret:ty)* $body:block) => {
+        $(#[$attr])*
+        pub fn $name($($arg: $ty),*) $(-> $ret)* {
+            #[inline(always)]
+            fn generic($($arg: $ty),*) $(-> $ret)* $body
+
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            #[target_feature(enable = "avx512f")]
+            unsafe fn avx512($($arg: $ty),*) $(-> $ret)* { generic($($arg),*) }
+
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            #[target_feature(enable = "avx2,fma")]
+            unsafe fn avx2($($arg: $ty),*) $(-> $ret)* { generic($($arg),*) }
+
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            #[target_feature(enable = "sse4.1")]
+            unsafe fn sse41($($arg: $ty),*) $(-> $ret)* { generic($($arg),*) }
+
+            match isa() {
+                #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+                Isa::Avx512 => unsafe { avx512($($arg),*) },
+                #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+                Isa::Avx2 => unsafe { avx2($($arg),*) },
+                #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+                Isa::Sse41 => unsafe { sse41($($arg),*) },
+                _ => generic($($arg),*),
+            }
+        }
+    }
+}
+
+/// Defines a public kernel implemented with intrinsics, dispatching at runtime to the version of
+/// the best instruction set, or to the scalar `$body` if there's none.
+macro_rules! intrinsic_kernel {
+    ($(#[$attr:meta])* pub fn $name:ident($($arg:ident: $ty:ty),*) $(-> $ret:ty)* $body:block) => {
+        $(#[$attr])*
+        pub fn $name($($arg: $ty),*) $(-> $ret)* {
+            match isa() {
+                #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+                Isa::Avx512 => unsafe { intrinsics::avx512::$name($($arg),*) },
+                #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+                Isa::Avx2 => unsafe { intrinsics::avx2::$name($($arg),*) },
+                #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+                Isa::Sse41 => unsafe { intrinsics::sse41::$name($($arg),*) },
+                #[cfg(target_arch = "aarch64")]
+                Isa::Baseline => unsafe { intrinsics::neon::$name($($arg),*) },
+                _ => $body,
+            }
+        }
+    }
+}
+
+// === scalar approximations (branch-free, so that they map onto vector instructions)
+
+const LOG2_E: f32 = ::std::f32::consts::LOG2_E;
+const LN_2_HI: f32 = 0.693_359_375;
+const LN_2_LO: f32 = -2.121_944_4e-4;
+const EXP_MIN: f32 = -87.336_54;
+const EXP_MAX: f32 = 88.722_83;
+
+/// The coefficients of the polynomial of `exp_approx`, highest degree first.
+const EXP_P: [f32; 6] = 
+    [1.987_569_1e-4, 1.398_199_9e-3, 8.333_452e-3, 4.166_579_6e-2, 1.666_666_5e-1, 5.000_000_1e-1];
+
+/// The coefficients of the polynomial of `tanh_approx` (in `x^2`), highest degree first.
+const TANH_P: [f32; 5] = 
+    [-5.704_988_7e-3, 2.063_908_9e-2, -5.373_971_6e-2, 1.333_144_2e-1, -3.333_328_2e-1];
+
+/// Approximates `e^x`.
+#[inline(always)]
+pub fn exp_approx(x: f32) -> f32 {
+    let x = x.max(EXP_MIN).min(EXP_MAX);
+    // x = n * ln(2) + r, |r| <= ln(2) / 2
+    let n = (x * LOG2_E + 0.5).floor();
+    let r = x - n * LN_2_HI - n * LN_2_LO;
+
+    let mut p = EXP_P[0];
+
+    for &c in &EXP_P[1..] {
+        p = p * r + c;
+    }
+
+    let y = p * r * r + r + 1.0;
+
+    // 2^n is assembled from the exponent bits - `n` is in [-126, 128], so the product is split
+    // into two factors to cover 2^128 without overflowing the exponent.
+    let n = n as i32;
+    let half = n >> 1;
+    let a = f32::from_bits(((half + 127) as u32) << 23);
+    let b = f32::from_bits(((n - half + 127) as u32) << 23);
+
+    y * a * b
+}
+
+/// Approximates `1 / (1 + e^-x)`.
+#[inline(always)]
+pub fn sigmoid_approx(x: f32) -> f32 {
+    1.0 / (1.0 + exp_approx(-x))
+}
+
+/// Approximates `tanh(x)`.
+#[inline(always)]
+pub fn tanh_approx(x: f32) -> f32 {
+    let a = x.abs();
+
+    // |x| < 0.625: odd polynomial
+    let z = x * x;
+    let mut p = TANH_P[0];
+
+    for &c in &TANH_P[1..] {
+        p = p * z + c;
+    }
+
+    let small = p * z * x + x;
+
+    // otherwise: 1 - 2 / (e^2|x| + 1), with the sign of `x`
+    let magnitude = 1.0 - 2.0 / (exp_approx(2.0 * a) + 1.0);
+    let large = f32::from_bits(magnitude.to_bits() | (x.to_bits() & 0x8000_0000));
+
+    if a < 0.625 { small } else { large }
+}
+
+// === element-wise kernels
+
+kernel! {
+    /// `out[i] = max(x[i], 0)`
+    pub fn relu(x: &[f32], out: &mut [f32]) {
+        for (o, &x) in out.iter_mut().zip(x) {
+            *o = x.max(0.0);
+        }
+    }
+}
+
+kernel! {
+    /// `out[i] = dx[i]` if `x[i] > 0`, otherwise `0`
+    pub fn relu_grad(x: &[f32], dx: &[f32], out: &mut [f32]) {
+        for ((o, &x), &dx) in out.iter_mut().zip(x).zip(dx) {
+            *o = if x > 0.0 { dx } else { 0.0 };
+        }
+    }
+}
+
+intrinsic_kernel! {
+    /// `out[i] = exp(x[i] - shift)`
+    pub fn exp(x: &[f32], shift: f32, out: &mut [f32]) {
+        for (o, &x) in out.iter_mut().zip(x) {
+            *o = exp_approx(x - shift);
+        }
+    }
+}
+
+intrinsic_kernel! {
+    /// `out[i] = sigmoid(x[i])`
+    pub fn sigmoid(x: &[f32], out: &mut [f32]) {
+        for (o, &x) in out.iter_mut().zip(x) {
+            *o = sigmoid_approx(x);
+        }
+    }
+}
+
+kernel! {
+    /// `out[i] = t[i] * (1 - t[i]) * dt[i]` (`t` being the output of the sigmoid)
+    pub fn sigmoid_grad(t: &[f32], dt: &[f32], out: &mut [f32]) {
+        for ((o, &t), &dt) in out.iter_mut().zip(t).zip(dt) {
+            *o = t * (1.0 - t) * dt;
+        }
+    }
+}
+
+intrinsic_kernel! {
+    /// `out[i] = tanh(x[i])`
+    pub fn tanh(x: &[f32], out: &mut [f32]) {
+        for (o, &x) in out.iter_mut().zip(x) {
+            *o = tanh_approx(x);
+        }
+    }
+}
+
+kernel! {
+    /// `out[i] = (1 - t[i]^2) * dt[i]` (`t` being the output of the tanh)
+    pub fn tanh_grad(t: &[f32], dt: &[f32], out: &mut [f32]) {
+        for ((o, &t), &dt) in out.iter_mut().zip(t).zip(dt) {
+            *o = (1.0 - t * t) * dt;
+        }
+    }
+}
+
+kernel! {
+    /// `x[i] /= divisor`
+    pub fn divide(x: &mut [f32], divisor: f32) {
+        for x in x.iter_mut() {
+            *x /= divisor;
+        }
+    }
+}
+
+kernel! {
+    /// `out[i] = x[i] - shift`
+    pub fn shift(x: &[f32], shift: f32, out: &mut [f32]) {
+        for (o, &x) in out.iter_mut().zip(x) {
+            *o = x - shift;
+        }
+    }
+}
+
+intrinsic_kernel! {
+    /// `out[i] = dx[i] - exp(x[i]) * sum` (the gradient of the log softmax)
+    pub fn log_softmax_grad(x: &[f32], dx: &[f32], sum: f32, out: &mut [f32]) {
+        for ((o, &x), &dx) in out.iter_mut().zip(x).zip(dx) {
+            *o = dx - exp_approx(x) * sum;
+        }
+    }
+}
+
+kernel! {
+    /// `out[i] = t[i] * (dt[i] - dot)` (the gradient of the softmax)
+    pub fn softmax_grad(t: &[f32], dt: &[f32], dot: f32, out: &mut [f32]) {
+        for ((o, &t), &dt) in out.iter_mut().zip(t).zip(dt) {
+            *o = t * (dt - dot);
+        }
+    }
+}
+
+// === reductions
+
+/// Combines the lanes of a partial result in a fixed order.
+#[inline(always)]
+fn horizontal<F>(lanes: [f32; LANES], f: F) -> f32 where F: Fn(f32, f32) -> f32 {
+    let mut width = LANES / 2;
+    let mut lanes = lanes;
+
+    while width > 0 {
+        for i in 0..width {
+            lanes[i] = f(lanes[i], lanes[i + width]);
+        }
+
+        width /= 2;
+    }
+
+    lanes[0]
+}
+
+kernel! {
+    /// Returns the sum of `x`.
+    pub fn sum(x: &[f32]) -> f32 {
+        let mut acc = [0.0; LANES];
+        let chunks = x.chunks_exact(LANES);
+        let remainder = chunks.remainder();
+
+        for chunk in chunks {
+            for i in 0..LANES {
+                acc[i] += chunk[i];
+            }
+        }
+
+        horizontal(acc, |a, b| a + b) + remainder.iter().fold(0.0, |acc, &x| acc + x)
+    }
+}
+
+kernel! {
+    /// Returns the dot product of `x` and `y`.
+    pub fn dot(x: &[f32], y: &[f32]) -> f32 {
+        let n = x.len().min(y.len());
+        let (x, y) = (&x[..n], &y[..n]);
+        let mut acc = [0.0; LANES];
+        let split = n - n % LANES;
+
+        for (x, y) in x[..split].chunks_exact(LANES).zip(y[..split].chunks_exact(LANES)) {
+            for i in 0..LANES {
+                acc[i] += x[i] * y[i];
+            }
+        }
+
+        let tail = x[split..].iter().zip(&y[split..]).fold(0.0, |acc, (&x, &y)| acc + x * y);
+        horizontal(acc, |a, b| a + b) + tail
+    }
+}
+
+kernel! {
+    /// Returns the maximum of `x` (`-inf` if `x` is empty).
+    pub fn max(x: &[f32]) -> f32 {
+        let mut acc = [::std::f32::NEG_INFINITY; LANES];
+        let chunks = x.chunks_exact(LANES);
+        let remainder = chunks.remainder();
+
+        for chunk in chunks {
+            for i in 0..LANES {
+                acc[i] = acc[i].max(chunk[i]);
+            }
+        }
+
+        remainder.iter().fold(horizontal(acc, f32::max), |acc, &x| acc.max(x))
+    }
+}
+
+intrinsic_kernel! {
+    /// Returns the sum of `exp(x[i] - shift)`.
+    pub fn sum_exp(x: &[f32], shift: f32) -> f32 {
+        let mut acc = [0.0; LANES];
+        let chunks = x.chunks_exact(LANES);
+        let remainder = chunks.remainder();
+
+        for chunk in chunks {
+            for i in 0..LANES {
+                acc[i] += exp_approx(chunk[i] - shift);
+            }
+        }
+
+        horizontal(acc, |a, b| a + b) +
+            remainder.iter().fold(0.0, |acc, &x| acc + exp_approx(x - shift))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{EXP_MAX, EXP_MIN, exp_approx, intrinsics, sigmoid_approx, tanh_approx};
+
+    /// Returns the relative error of `approx` with respect to `exact`.
+    fn relative(approx: f32, exact: f64) -> f64 {
+        if exact == 0.0 { approx.abs() as f64 } else { ((approx as f64 - exact) / exact).abs() }
+    }
+
+    fn samples(low: f32, high: f32) -> Vec<f32> {
+        let n = 100_000;
+        (0..n + 1).map(|i| low + (high - low) * i as f32 / n as f32).collect()
+    }
+
+    #[test]
+    fn exp_is_within_its_error_bound() {
+        for x in samples(-87.3, 88.7) {
+            assert!(relative(exp_approx(x), (x as f64).exp()) < 1.0e-7, "exp({})", x);
+        }
+    }
+
+    #[test]
+    fn sigmoid_is_within_its_error_bound() {
+        for x in samples(-80.0, 80.0) {
+            let exact = 1.0 / (1.0 + (-x as f64).exp());
+            assert!(relative(sigmoid_approx(x), exact) < 2.0e-7, "sigmoid({})", x);
+        }
+    }
+
+    #[test]
+    fn tanh_is_within_its_error_bound() {
+        for x in samples(-20.0, 20.0) {
+            assert!(relative(tanh_approx(x), (x as f64).tanh()) < 2.0e-7, "tanh({})", x);
+        }
+    }
+
+    /// The intrinsic kernels of an instruction set.
+    struct Intrinsics {
+        name: &'static str,
+        exp: unsafe fn(&[f32], f32, &mut [f32]),
+        sigmoid: unsafe fn(&[f32], &mut [f32]),
+        tanh: unsafe fn(&[f32], &mut [f32]),
+        log_softmax_grad: unsafe fn(&[f32], &[f32], f32, &mut [f32]),
+        sum_exp: unsafe fn(&[f32], f32) -> f32,
+    }
+
+    /// Returns the intrinsic kernels supported by the host CPU.
+    fn supported_intrinsics() -> Vec<Intrinsics> {
+        let mut supported = vec![];
+
+        macro_rules! intrinsics {
+            ($isa:ident) => {
+                Intrinsics {
+                    name: stringify!($isa),
+                    exp: intrinsics::$isa::exp,
+                    sigmoid: intrinsics::$isa::sigmoid,
+                    tanh: intrinsics::$isa::tanh,
+                    log_softmax_grad: intrinsics::$isa::log_softmax_grad,
+                    sum_exp: intrinsics::$isa::sum_exp,
+                }
+            }
+        }
+
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        {
+            if is_x86_feature_detected!("sse4.1") {
+                supported.push(intrinsics!(sse41));
+            }
+
+            if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma") {
+                supported.push(intrinsics!(avx2));
+            }
+
+            if is_x86_feature_detected!("avx512f") {
+                supported.push(intrinsics!(avx512));
+            }
+        }
+
+        #[cfg(target_arch = "aarch64")]
+        supported.push(intrinsics!(neon));
+
+        supported
+    }
+
+    /// Returns `true` if the values are the same bits (or both NaN).
+    fn identical(a: &[f32], b: &[f32]) -> bool {
+        a.len() == b.len() && 
+            a.iter().zip(b).all(|(a, b)| a.to_bits() == b.to_bits() || a.is_nan() && b.is_nan())
+    }
+
+    #[test]
+    fn the_intrinsics_match_the_scalar_approximations() {
+        // the length isn't a multiple of any vector width, so the remainders are covered too
+        let mut x = samples(-100.0, 100.0);
+        x.extend_from_slice(&[
+            0.0, -0.0, 0.625, -0.625, 1e-30, -1e-30, EXP_MIN, EXP_MAX, 
+            f32::INFINITY, f32::NEG_INFINITY, f32::NAN,
+        ]);
+        let dx: Vec<f32> = x.iter().map(|&x| 0.5 - x / 200.0).collect();
+
+        let exp: Vec<f32> = x.iter().map(|&x| exp_approx(x - 1.5)).collect();
+        let sigmoid: Vec<f32> = x.iter().map(|&x| sigmoid_approx(x)).collect();
+        let tanh: Vec<f32> = x.iter().map(|&x| tanh_approx(x)).collect();
+        let log_softmax_grad: Vec<f32> = 
+            x.iter().zip(&dx).map(|(&x, &dx)| dx - exp_approx(x) * 0.25).collect();
+        let sum_exp = super::super::kernels::sum_exp(&x[..1000], 1.5);
+
+        for intrinsics in supported_intrinsics() {
+            let mut out = vec![0.0; x.len()];
+
+            unsafe {
+                (intrinsics.exp)(&x, 1.5, &mut out);
+                assert!(identical(&out, &exp), "{} exp", intrinsics.name);
+
+                (intrinsics.sigmoid)(&x, &mut out);
+                assert!(identical(&out, &sigmoid), "{} sigmoid", intrinsics.name);
+
+                (intrinsics.tanh)(&x, &mut out);
+                assert!(identical(&out, &tanh), "{} tanh", intrinsics.name);
+
+                (intrinsics.log_softmax_grad)(&x, &dx, 0.25, &mut out);
+                assert!(identical(&out, &log_softmax_grad), "{} log_softmax_grad", intrinsics.name);
+
+                let sum = (intrinsics.sum_exp)(&x[..1000], 1.5);
+                assert_eq!(sum.to_bits(), sum_exp.to_bits(), "{} sum_exp", intrinsics.name);
+            }
+        }
+    }
+}
@@ -0,0 +1,520 @@
+//! Intrinsic implementations of the kernels evaluating `exp`, `sigmoid` and `tanh`.
+//!
+//! The compiler doesn't reliably vectorize the approximations on its own (the `floor`, the
+//! conversions and the exponent bit manipulations get in the way), so they're written with the
+//! intrinsics of each instruction set. Each module provides the same primitives over its vector
+//! type, and the `approximations!` macro implements the kernels on top of them.
+//!
+//! The primitives perform the exact operations of the scalar approximations, in the same order
+//! and without fused multiply-adds, so the results are bitwise identical to those of
+//! `exp_approx`, `sigmoid_approx` and `tanh_approx`, whatever the instruction set.
+
+/// Implements the approximations and the kernels using them, given the primitives of an
+/// instruction set (the vector type `V` of `WIDTH` lanes, along with `splat`, `load`, `store`,
+/// `add`, `sub`, `mul`, `div`, `max`, `min`, `floor`, `abs`, `neg`, `exp2`, `copysign` and
+/// `select_lt`), all enabling the target `$features`.
+macro_rules! approximations {
+    ($features:tt) => {
+        use super::super::{EXP_MAX, EXP_MIN, EXP_P, LANES, LN_2_HI, LN_2_LO, LOG2_E, TANH_P};
+        use super::super::{exp_approx, horizontal, sigmoid_approx, tanh_approx};
+
+        /// Approximates `e^x` (see `kernels::exp_approx`).
+        #[inline]
+        #[target_feature(enable = $features)]
+        unsafe fn exp_v(x: V) -> V {
+            let x = min(max(x, splat(EXP_MIN)), splat(EXP_MAX));
+            let n = floor(add(mul(x, splat(LOG2_E)), splat(0.5)));
+            let r = sub(sub(x, mul(n, splat(LN_2_HI))), mul(n, splat(LN_2_LO)));
+
+            let mut p = splat(EXP_P[0]);
+
+            for &c in &EXP_P[1..] {
+                p = add(mul(p, r), splat(c));
+            }
+
+            let y = add(add(mul(mul(p, r), r), r), splat(1.0));
+            let (a, b) = exp2(n);
+
+            mul(mul(y, a), b)
+        }
+
+        /// Approximates `1 / (1 + e^-x)` (see `kernels::sigmoid_approx`).
+        #[inline]
+        #[target_feature(enable = $features)]
+        unsafe fn sigmoid_v(x: V) -> V {
+            div(splat(1.0), add(splat(1.0), exp_v(neg(x))))
+        }
+
+        /// Approximates `tanh(x)` (see `kernels::tanh_approx`).
+        #[inline]
+        #[target_feature(enable = $features)]
+        unsafe fn tanh_v(x: V) -> V {
+            let a = abs(x);
+
+            let z = mul(x, x);
+            let mut p = splat(TANH_P[0]);
+
+            for &c in &TANH_P[1..] {
+                p = add(mul(p, z), splat(c));
+            }
+
+            let small = add(mul(mul(p, z), x), x);
+
+            let e = exp_v(mul(splat(2.0), a));
+            let magnitude = sub(splat(1.0), div(splat(2.0), add(e, splat(1.0))));
+            let large = copysign(magnitude, x);
+
+            select_lt(a, splat(0.625), small, large)
+        }
+
+        /// `out[i] = exp(x[i] - shift)`
+        #[target_feature(enable = $features)]
+        pub unsafe fn exp(x: &[f32], shift: f32, out: &mut [f32]) {
+            let n = x.len().min(out.len());
+            let split = n - n % WIDTH;
+            let s = splat(shift);
+
+            for i in (0..split).step_by(WIDTH) {
+                store(out.as_mut_ptr().add(i), exp_v(sub(load(x.as_ptr().add(i)), s)));
+            }
+
+            for i in split..n {
+                out[i] = exp_approx(x[i] - shift);
+            }
+        }
+
+        /// `out[i] = sigmoid(x[i])`
+        #[target_feature(enable = $features)]
+        pub unsafe fn sigmoid(x: &[f32], out: &mut [f32]) {
+            let n = x.len().min(out.len());
+            let split = n - n % WIDTH;
+
+            for i in (0..split).step_by(WIDTH) {
+                store(out.as_mut_ptr().add(i), sigmoid_v(load(x.as_ptr().add(i))));
+            }
+
+            for i in split..n {
+                out[i] = sigmoid_approx(x[i]);
+            }
+        }
+
+        /// `out[i] = tanh(x[i])`
+        #[target_feature(enable = $features)]
+        pub unsafe fn tanh(x: &[f32], out: &mut [f32]) {
+            let n = x.len().min(out.len());
+            let split = n - n % WIDTH;
+
+            for i in (0..split).step_by(WIDTH) {
+                store(out.as_mut_ptr().add(i), tanh_v(load(x.as_ptr().add(i))));
+            }
+
+            for i in split..n {
+                out[i] = tanh_approx(x[i]);
+            }
+        }
+
+        /// `out[i] = dx[i] - exp(x[i]) * sum`
+        #[target_feature(enable = $features)]
+        pub unsafe fn log_softmax_grad(x: &[f32], dx: &[f32], sum: f32, out: &mut [f32]) {
+            let n = x.len().min(dx.len()).min(out.len());
+            let split = n - n % WIDTH;
+            let s = splat(sum);
+
+            for i in (0..split).step_by(WIDTH) {
+                let e = exp_v(load(x.as_ptr().add(i)));
+                store(out.as_mut_ptr().add(i), sub(load(dx.as_ptr().add(i)), mul(e, s)));
+            }
+
+            for i in split..n {
+                out[i] = dx[i] - exp_approx(x[i]) * sum;
+            }
+        }
+
+        /// Returns the sum of `exp(x[i] - shift)`.
+        ///
+        /// The `LANES` partial sums of the other instruction sets are kept in `LANES / WIDTH`
+        /// vectors, so they're combined in the same order.
+        #[target_feature(enable = $features)]
+        pub unsafe fn sum_exp(x: &[f32], shift: f32) -> f32 {
+            let split = x.len() - x.len() % LANES;
+            let s = splat(shift);
+            let mut acc = [splat(0.0); LANES / WIDTH];
+
+            for i in (0..split).step_by(LANES) {
+                for (j, acc) in acc.iter_mut().enumerate() {
+                    let e = exp_v(sub(load(x.as_ptr().add(i + j * WIDTH)), s));
+                    *acc = add(*acc, e);
+                }
+            }
+
+            let mut lanes = [0.0; LANES];
+
+            for (j, &acc) in acc.iter().enumerate() {
+                store(lanes.as_mut_ptr().add(j * WIDTH), acc);
+            }
+
+            horizontal(lanes, |a, b| a + b) +
+                x[split..].iter().fold(0.0, |acc, &x| acc + exp_approx(x - shift))
+        }
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+pub mod sse41 {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::*;
+
+    type V = __m128;
+
+    const WIDTH: usize = 4;
+
+    #[inline]
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn splat(x: f32) -> V { _mm_set1_ps(x) }
+
+    #[inline]
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn load(p: *const f32) -> V { _mm_loadu_ps(p) }
+
+    #[inline]
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn store(p: *mut f32, v: V) { _mm_storeu_ps(p, v) }
+
+    #[inline]
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn add(a: V, b: V) -> V { _mm_add_ps(a, b) }
+
+    #[inline]
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn sub(a: V, b: V) -> V { _mm_sub_ps(a, b) }
+
+    #[inline]
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn mul(a: V, b: V) -> V { _mm_mul_ps(a, b) }
+
+    #[inline]
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn div(a: V, b: V) -> V { _mm_div_ps(a, b) }
+
+    /// Returns `b` where `a` is NaN, like `f32::max`.
+    #[inline]
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn max(a: V, b: V) -> V { _mm_max_ps(a, b) }
+
+    #[inline]
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn min(a: V, b: V) -> V { _mm_min_ps(a, b) }
+
+    #[inline]
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn floor(a: V) -> V { _mm_floor_ps(a) }
+
+    #[inline]
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn abs(a: V) -> V { _mm_andnot_ps(_mm_set1_ps(-0.0), a) }
+
+    #[inline]
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn neg(a: V) -> V { _mm_xor_ps(_mm_set1_ps(-0.0), a) }
+
+    /// Returns `magnitude` with the sign of `sign`.
+    #[inline]
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn copysign(magnitude: V, sign: V) -> V {
+        _mm_or_ps(magnitude, _mm_and_ps(_mm_set1_ps(-0.0), sign))
+    }
+
+    /// Returns `t` where `a < b`, and `f` elsewhere.
+    #[inline]
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn select_lt(a: V, b: V, t: V, f: V) -> V { _mm_blendv_ps(f, t, _mm_cmplt_ps(a, b)) }
+
+    /// Returns two factors of `2^n` (`n` being integral and in `[-126, 128]`).
+    #[inline]
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn exp2(n: V) -> (V, V) {
+        let n = _mm_cvttps_epi32(n);
+        let half = _mm_srai_epi32(n, 1);
+        let bias = _mm_set1_epi32(127);
+        let a = _mm_slli_epi32(_mm_add_epi32(half, bias), 23);
+        let b = _mm_slli_epi32(_mm_add_epi32(_mm_sub_epi32(n, half), bias), 23);
+        (_mm_castsi128_ps(a), _mm_castsi128_ps(b))
+    }
+
+    approximations!("sse4.1");
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+pub mod avx2 {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::*;
+
+    type V = __m256;
+
+    const WIDTH: usize = 8;
+
+    #[inline]
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn splat(x: f32) -> V { _mm256_set1_ps(x) }
+
+    #[inline]
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn load(p: *const f32) -> V { _mm256_loadu_ps(p) }
+
+    #[inline]
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn store(p: *mut f32, v: V) { _mm256_storeu_ps(p, v) }
+
+    #[inline]
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn add(a: V, b: V) -> V { _mm256_add_ps(a, b) }
+
+    #[inline]
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn sub(a: V, b: V) -> V { _mm256_sub_ps(a, b) }
+
+    #[inline]
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn mul(a: V, b: V) -> V { _mm256_mul_ps(a, b) }
+
+    #[inline]
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn div(a: V, b: V) -> V { _mm256_div_ps(a, b) }
+
+    /// Returns `b` where `a` is NaN, like `f32::max`.
+    #[inline]
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn max(a: V, b: V) -> V { _mm256_max_ps(a, b) }
+
+    #[inline]
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn min(a: V, b: V) -> V { _mm256_min_ps(a, b) }
+
+    #[inline]
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn floor(a: V) -> V { _mm256_floor_ps(a) }
+
+    #[inline]
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn abs(a: V) -> V { _mm256_andnot_ps(_mm256_set1_ps(-0.0), a) }
+
+    #[inline]
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn neg(a: V) -> V { _mm256_xor_ps(_mm256_set1_ps(-0.0), a) }
+
+    /// Returns `magnitude` with the sign of `sign`.
+    #[inline]
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn copysign(magnitude: V, sign: V) -> V {
+        _mm256_or_ps(magnitude, _mm256_and_ps(_mm256_set1_ps(-0.0), sign))
+    }
+
+    /// Returns `t` where `a < b`, and `f` elsewhere.
+    #[inline]
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn select_lt(a: V, b: V, t: V, f: V) -> V {
+        _mm256_blendv_ps(f, t, _mm256_cmp_ps(a, b, _CMP_LT_OQ))
+    }
+
+    /// Returns two factors of `2^n` (`n` being integral and in `[-126, 128]`).
+    #[inline]
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn exp2(n: V) -> (V, V) {
+        let n = _mm256_cvttps_epi32(n);
+        let half = _mm256_srai_epi32(n, 1);
+        let bias = _mm256_set1_epi32(127);
+        let a = _mm256_slli_epi32(_mm256_add_epi32(half, bias), 23);
+        let b = _mm256_slli_epi32(_mm256_add_epi32(_mm256_sub_epi32(n, half), bias), 23);
+        (_mm256_castsi256_ps(a), _mm256_castsi256_ps(b))
+    }
+
+    approximations!("avx2,fma");
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+pub mod avx512 {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::*;
+
+    type V = __m512;
+
+    const WIDTH: usize = 16;
+
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn splat(x: f32) -> V { _mm512_set1_ps(x) }
+
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn load(p: *const f32) -> V { _mm512_loadu_ps(p) }
+
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn store(p: *mut f32, v: V) { _mm512_storeu_ps(p, v) }
+
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn add(a: V, b: V) -> V { _mm512_add_ps(a, b) }
+
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn sub(a: V, b: V) -> V { _mm512_sub_ps(a, b) }
+
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn mul(a: V, b: V) -> V { _mm512_mul_ps(a, b) }
+
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn div(a: V, b: V) -> V { _mm512_div_ps(a, b) }
+
+    /// Returns `b` where `a` is NaN, like `f32::max`.
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn max(a: V, b: V) -> V { _mm512_max_ps(a, b) }
+
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn min(a: V, b: V) -> V { _mm512_min_ps(a, b) }
+
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn floor(a: V) -> V {
+        _mm512_roundscale_ps(a, _MM_FROUND_TO_NEG_INF | _MM_FROUND_NO_EXC)
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn abs(a: V) -> V { _mm512_abs_ps(a) }
+
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn neg(a: V) -> V {
+        _mm512_castsi512_ps(_mm512_xor_si512(_mm512_set1_epi32(SIGN), _mm512_castps_si512(a)))
+    }
+
+    /// Returns `magnitude` with the sign of `sign`.
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn copysign(magnitude: V, sign: V) -> V {
+        let sign = _mm512_and_si512(_mm512_set1_epi32(SIGN), _mm512_castps_si512(sign));
+        _mm512_castsi512_ps(_mm512_or_si512(_mm512_castps_si512(magnitude), sign))
+    }
+
+    /// Returns `t` where `a < b`, and `f` elsewhere.
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn select_lt(a: V, b: V, t: V, f: V) -> V {
+        _mm512_mask_blend_ps(_mm512_cmp_ps_mask(a, b, _CMP_LT_OQ), f, t)
+    }
+
+    /// Returns two factors of `2^n` (`n` being integral and in `[-126, 128]`).
+    #[inline]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn exp2(n: V) -> (V, V) {
+        let n = _mm512_cvttps_epi32(n);
+        let half = _mm512_srai_epi32(n, 1);
+        let bias = _mm512_set1_epi32(127);
+        let a = _mm512_slli_epi32(_mm512_add_epi32(half, bias), 23);
+        let b = _mm512_slli_epi32(_mm512_add_epi32(_mm512_sub_epi32(n, half), bias), 23);
+        (_mm512_castsi512_ps(a), _mm512_castsi512_ps(b))
+    }
+
+    /// The sign bit of an `f32`.
+    const SIGN: i32 = 0x8000_0000u32 as i32;
+
+    approximations!("avx512f");
+}
+
+/// NEON is part of the AArch64 baseline, so these are used whenever the other kernels use their
+/// baseline versions.
+#[cfg(target_arch = "aarch64")]
+pub mod neon {
+    use std::arch::aarch64::*;
+
+    type V = float32x4_t;
+
+    const WIDTH: usize = 4;
+
+    #[inline]
+    #[target_feature(enable = "neon")]
+    unsafe fn splat(x: f32) -> V { vdupq_n_f32(x) }
+
+    #[inline]
+    #[target_feature(enable = "neon")]
+    unsafe fn load(p: *const f32) -> V { vld1q_f32(p) }
+
+    #[inline]
+    #[target_feature(enable = "neon")]
+    unsafe fn store(p: *mut f32, v: V) { vst1q_f32(p, v) }
+
+    #[inline]
+    #[target_feature(enable = "neon")]
+    unsafe fn add(a: V, b: V) -> V { vaddq_f32(a, b) }
+
+    #[inline]
+    #[target_feature(enable = "neon")]
+    unsafe fn sub(a: V, b: V) -> V { vsubq_f32(a, b) }
+
+    #[inline]
+    #[target_feature(enable = "neon")]
+    unsafe fn mul(a: V, b: V) -> V { vmulq_f32(a, b) }
+
+    #[inline]
+    #[target_feature(enable = "neon")]
+    unsafe fn div(a: V, b: V) -> V { vdivq_f32(a, b) }
+
+    /// Returns `b` where `a` is NaN, like `f32::max`.
+    #[inline]
+    #[target_feature(enable = "neon")]
+    unsafe fn max(a: V, b: V) -> V { vmaxnmq_f32(a, b) }
+
+    #[inline]
+    #[target_feature(enable = "neon")]
+    unsafe fn min(a: V, b: V) -> V { vminnmq_f32(a, b) }
+
+    #[inline]
+    #[target_feature(enable = "neon")]
+    unsafe fn floor(a: V) -> V { vrndmq_f32(a) }
+
+    #[inline]
+    #[target_feature(enable = "neon")]
+    unsafe fn abs(a: V) -> V { vabsq_f32(a) }
+
+    #[inline]
+    #[target_feature(enable = "neon")]
+    unsafe fn neg(a: V) -> V { vnegq_f32(a) }
+
+    /// Returns `magnitude` with the sign of `sign`.
+    #[inline]
+    #[target_feature(enable = "neon")]
+    unsafe fn copysign(magnitude: V, sign: V) -> V {
+        let sign = vandq_u32(vdupq_n_u32(0x8000_0000), vreinterpretq_u32_f32(sign));
+        vreinterpretq_f32_u32(vorrq_u32(vreinterpretq_u32_f32(magnitude), sign))
+    }
+
+    /// Returns `t` where `a < b`, and `f` elsewhere.
+    #[inline]
+    #[target_feature(enable = "neon")]
+    unsafe fn select_lt(a: V, b: V, t: V, f: V) -> V { vbslq_f32(vcltq_f32(a, b), t, f) }
+
+    /// Returns two factors of `2^n` (`n` being integral and in `[-126, 128]`).
+    #[inline]
+    #[target_feature(enable = "neon")]
+    unsafe fn exp2(n: V) -> (V, V) {
+        let n = vcvtq_s32_f32(n);
+        let half = vshrq_n_s32::<1>(n);
+        let bias = vdupq_n_s32(127);
+        let a = vshlq_n_s32::<23>(vaddq_s32(half, bias));
+        let b = vshlq_n_s32::<23>(vaddq_s32(vsubq_s32(n, half), bias));
+        (vreinterpretq_f32_s32(a), vreinterpretq_f32_s32(b))
+    }
+
+    approximations!("neon");
+}
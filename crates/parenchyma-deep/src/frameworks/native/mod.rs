@@ -2,9 +2,77 @@ use parenchyma::error::Result;
 use parenchyma::extension_package::Dependency;
 use parenchyma::frameworks::NativeContext as Context;
 use parenchyma::tensor::SharedTensor;
+use rayon::ThreadPool;
+use rayon::prelude::*;
 use super::super::{Extension, Package};
 use super::super::extension_package::{Backward, Forward};
 
+pub mod kernels;
+
+/// The number of elements processed by a task - smaller tensors are processed by a single task.
+///
+/// The chunks are also the units of the reductions, so the results don't depend on the number 
+/// of threads.
+const CHUNK_LEN: usize = 16384;
+
+/// Applies the element-wise `kernel` to `x` on the thread pool.
+fn map<K>(pool: &ThreadPool, x: &SharedTensor, result: &mut SharedTensor, kernel: K) -> Result 
+    where K: Fn(&[f32], &mut [f32]) + Sync {
+
+    let x = x.as_slice()?;
+    let result = result.as_mut_slice_unsynched()?;
+
+    pool.install(|| {
+        result.par_chunks_mut(CHUNK_LEN).zip(x.par_chunks(CHUNK_LEN))
+            .for_each(|(r, x)| kernel(x, r))
+    });
+
+    Ok(())
+}
+
+/// Applies the element-wise `kernel` to `x` and `y` on the thread pool.
+fn zip_map<K>(
+    pool: &ThreadPool, 
+    x: &SharedTensor, 
+    y: &SharedTensor, 
+    result: &mut SharedTensor, 
+    kernel: K) -> Result 
+    where K: Fn(&[f32], &[f32], &mut [f32]) + Sync {
+
+    let x = x.as_slice()?;
+    let y = y.as_slice()?;
+    let result = result.as_mut_slice_unsynched()?;
+
+    pool.install(|| {
+        result.par_chunks_mut(CHUNK_LEN).zip(x.par_chunks(CHUNK_LEN).zip(y.par_chunks(CHUNK_LEN)))
+            .for_each(|(r, (x, y))| kernel(x, y, r))
+    });
+
+    Ok(())
+}
+
+/// Reduces the chunks of `x` with `kernel` on the thread pool and combines the partial results
+/// in order with `combine`.
+fn reduce<K, C>(pool: &ThreadPool, x: &[f32], kernel: K, combine: C) -> f32 
+    where K: Fn(&[f32]) -> f32 + Sync, 
+          C: Fn(f32, f32) -> f32 {
+
+    let partials: Vec<f32> = pool.install(|| x.par_chunks(CHUNK_LEN).map(&kernel).collect());
+    let mut partials = partials.into_iter();
+    let first = partials.next().unwrap_or_else(|| kernel(&[]));
+    partials.fold(first, combine)
+}
+
+fn add(a: f32, b: f32) -> f32 {
+    a + b
+}
+
+/// The operations implemented below (see `Package::capabilities`).
+pub(crate) const OPERATIONS: &'static [&'static str] = &[
+    "log_softmax", "log_softmax_grad", "relu", "relu_grad", "sigmoid", "sigmoid_grad",
+    "softmax", "softmax_grad", "tanh", "tanh_grad",
+];
+
 impl<P> Backward for Context<P> where 
     P: Dependency<Package> {
     fn log_softmax_grad(
@@ -12,18 +80,10 @@ impl<P> Backward for Context<P> where
         x: &SharedTensor, 
         x_diff: &SharedTensor, 
         result_diff: &mut SharedTensor) -> Result {
-        let x_slice = x.as_slice().unwrap();
-        let x_diff_slice = x_diff.as_slice().unwrap();
-        let mut sum = 0.0;
-        for &grad_val in x_diff_slice.iter() {
-            sum += grad_val;
-        }
-        let res = x_slice.iter().zip(x_diff_slice.iter())
-            .map(|(x_val, x_diff_val)| {
-                x_diff_val - x_val.exp() * sum
-            });
-        result_diff.write_iter(res)?;
-        Ok(())
+        let sum = reduce(self.pool(), x_diff.as_slice()?, kernels::sum, add);
+        zip_map(self.pool(), x, x_diff, result_diff, |x, dx, r| {
+            kernels::log_softmax_grad(x, dx, sum, r)
+        })
     }
 
     fn relu_grad(
@@ -32,11 +92,7 @@ impl<P> Backward for Context<P> where
         x_diff: &SharedTensor,
         result: &SharedTensor,
         result_diff: &mut SharedTensor) -> Result {
-        let res = x.as_slice().unwrap().iter()
-            .zip(x_diff.as_slice().unwrap().iter())
-            .map(|(x, dx)| if *x > 0.0 { *dx } else { 0.0 });
-        result_diff.write_iter(res)?;
-        Ok(())
+        zip_map(self.pool(), x, x_diff, result_diff, kernels::relu_grad)
     }
 
     fn sigmoid_grad(
@@ -45,10 +101,7 @@ impl<P> Backward for Context<P> where
         x_diff: &SharedTensor,
         result: &SharedTensor,
         result_diff: &mut SharedTensor) -> Result {
-        let res = x.as_slice().unwrap().iter().zip(x_diff.as_slice().unwrap().iter())
-            .map(|(t, dt)| *t * (1.0 -*t) * *dt);
-        result_diff.write_iter(res)?;
-        Ok(())
+        zip_map(self.pool(), x, x_diff, result_diff, kernels::sigmoid_grad)
     }
 
     fn softmax_grad(
@@ -56,15 +109,16 @@ impl<P> Backward for Context<P> where
         x: &SharedTensor, 
         x_diff: &SharedTensor, 
         result_diff: &mut SharedTensor) -> Result {
-        let mut dot = 0.0;
-        let sig_data_slice = x.as_slice().unwrap();
-        let sig_dx_slice = x_diff.as_slice().unwrap();
-        for (t, dt) in sig_data_slice.iter().zip(sig_dx_slice.iter()) {
-            dot += t * dt;
-        }
-        let res = sig_data_slice.iter().zip(sig_dx_slice.iter()).map(|(t, dt)| t * (dt - dot));
-        result_diff.write_iter(res)?;
-        Ok(())
+        let (t, dt) = (x.as_slice()?, x_diff.as_slice()?);
+        let partials: Vec<f32> = self.pool().install(|| {
+            t.par_chunks(CHUNK_LEN).zip(dt.par_chunks(CHUNK_LEN))
+                .map(|(t, dt)| kernels::dot(t, dt))
+                .collect()
+        });
+        let dot = partials.into_iter().fold(0.0, add);
+        zip_map(self.pool(), x, x_diff, result_diff, |t, dt, r| {
+            kernels::softmax_grad(t, dt, dot, r)
+        })
     }
 
     fn tanh_grad(
@@ -73,63 +127,45 @@ impl<P> Backward for Context<P> where
         x_diff: &SharedTensor, 
         result: &SharedTensor,
         result_diff: &mut SharedTensor) -> Result {
-        let res = x.as_slice().unwrap().iter()
-            .zip(x_diff.as_slice().unwrap().iter())
-            .map(|(x, dx)| (1.0 - x.powi(2)) * *dx);
-        result_diff.write_iter(res)?;
-        Ok(())
+        zip_map(self.pool(), x, x_diff, result_diff, kernels::tanh_grad)
     }
 }
 
 impl<P> Forward for Context<P> where 
     P: Dependency<Package> {
     fn log_softmax(&self, x: &SharedTensor, result: &mut SharedTensor) -> Result {
-        let mut max_input = ::std::f32::NEG_INFINITY;
-        for &input in x.as_slice().unwrap() {
-            max_input = max_input.max(input);
-        }
-        let mut logsum = 0.;
-        for exp in x.as_slice().unwrap().iter().map(|t| (-(max_input - t)).exp()) {
-            logsum += exp;
-        }
-        logsum = max_input + logsum.ln();
-        let res = x.as_slice().unwrap().iter().map(|t| t - logsum);
-        result.write_iter(res)?;
-        Ok(())
+        let max_input = reduce(self.pool(), x.as_slice()?, kernels::max, f32::max);
+        let sum = reduce(self.pool(), x.as_slice()?, |x| kernels::sum_exp(x, max_input), add);
+        let logsum = max_input + sum.ln();
+        map(self.pool(), x, result, |x, r| kernels::shift(x, logsum, r))
     }
 
     fn relu(&self, x: &SharedTensor, result: &mut SharedTensor) -> Result {
-        let res = x.as_slice().unwrap().iter().map(|elem| elem.max(0.0));
-        result.write_iter(res)?;
-        Ok(())
+        map(self.pool(), x, result, kernels::relu)
     }
 
     fn sigmoid(&self, x: &SharedTensor, result: &mut SharedTensor) -> Result {
-        let res = x.as_slice().unwrap().iter().map(|x| 1.0 / (1.0 + (-*x).exp()));
-        result.write_iter(res)?;
-        Ok(())
+        map(self.pool(), x, result, kernels::sigmoid)
     }
 
     fn softmax(&self, x: &SharedTensor, result: &mut SharedTensor) -> Result {
-        let mut exps = Vec::with_capacity(x.shape().capacity());
-        let mut sum = 0.0;
-        for exp in x.as_slice().unwrap().iter().map(|t| t.exp()) {
-            exps.push(exp);
-            sum += exp;
-        }
-        let res = exps.iter().map(|t| t / sum);
-        result.write_iter(res)?;
+        let max_input = reduce(self.pool(), x.as_slice()?, kernels::max, f32::max);
+        // the exponentials are computed once, and then normalized in place
+        map(self.pool(), x, result, |x, r| kernels::exp(x, max_input, r))?;
+        let sum = reduce(self.pool(), result.as_slice()?, kernels::sum, add);
+        let result = result.as_mut_slice()?;
+        self.pool().install(|| {
+            result.par_chunks_mut(CHUNK_LEN).for_each(|r| kernels::divide(r, sum))
+        });
         Ok(())
     }
 
     fn tanh(&self, x: &SharedTensor, result: &mut SharedTensor) -> Result {
-        let res = x.as_slice().unwrap().iter().map(|elem| elem.tanh());
-        result.write_iter(res)?;
-        Ok(())
+        map(self.pool(), x, result, kernels::tanh)
     }
 }
 
 impl<P> Extension for Context<P> where 
     P: Dependency<Package> {
     // ..
-}
\ No newline at end of file
+}
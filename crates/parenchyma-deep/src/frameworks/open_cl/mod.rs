@@ -5,10 +5,11 @@ mod package;
 use super::super::{Extension, Package};
 use super::super::extension_package::{Backward, Forward};
 
-use ocl;
 use parenchyma::error::Result;
 use parenchyma::extension_package::{Dependency, ExtensionPackageCtor};
-use parenchyma::frameworks::{OpenCLContext as Context, OpenCLMemory as Memory};
+use parenchyma::frameworks::{OpenCLAccess as Access, OpenCLContext as Context, OpenCLDevice};
+use parenchyma::frameworks::OpenCLKernels;
+use parenchyma::frameworks::OpenCLMemory as Memory;
 use parenchyma::tensor::{self, SharedTensor};
 
 impl ExtensionPackageCtor<Context<()>> for super::super::Package {
@@ -17,6 +18,10 @@ impl ExtensionPackageCtor<Context<()>> for super::super::Package {
     }
 }
 
+/// The operations implemented below (see `Package::capabilities`).
+pub(crate) const OPERATIONS: &'static [&'static str] = 
+    &["log_softmax", "log_softmax_grad", "sigmoid", "sigmoid_grad"];
+
 impl<P> Backward for Context<P> where 
     P: Dependency<Package> {
     fn log_softmax_grad(
@@ -31,34 +36,24 @@ impl<P> Backward for Context<P> where
         let result: &mut Memory<_> = tensor::mut_reference(result, /*on:*/ self.device())?;
 
         unsafe {
-            ocl::Kernel::new("log_softmax_backward_float", &self.extension_package().dependency().open_cl().program)?
+            let kernels = &self.extension_package().dependency().open_cl().kernels;
+
+            let kernel = kernels.get(self.device(), "log_softmax_backward_float")?
                 .arg_buf(x)
                 .arg_buf(x_diff)
-                .arg_buf(result)
+                .arg_buf(&*result)
                 .arg_scl(n as i32)
 
                 .gws([1, 1, 1])
-                .lws([1, 1, 1])
-                .queue(self.device().queue().clone())
-                .enq()?;
+                .lws([1, 1, 1]);
+
+            let accesses = [Access::read(x), Access::read(x_diff), Access::write(result)];
+            self.device().enqueue("log_softmax_backward_float", &kernel, &accesses)?;
         }
 
         Ok(())
     }
 
-    // fn relu_grad(
-    //     self: &Self, 
-    //     x: &SharedTensor, 
-    //     x_diff: &SharedTensor,
-    //     result: &SharedTensor,
-    //     result_diff: &mut SharedTensor) -> Result {
-    //     let res = x.as_slice().unwrap().iter()
-    //         .zip(x_diff.as_slice().unwrap().iter())
-    //         .map(|(x, dx)| if *x > 0.0 { *dx } else { 0.0 });
-    //     result_diff.write_iter(res)?;
-    //     Ok(())
-    // }
-
     fn sigmoid_grad(
         self: &Self, 
         x: &SharedTensor, 
@@ -66,55 +61,13 @@ impl<P> Backward for Context<P> where
         _: &SharedTensor,
         result_diff: &mut SharedTensor) -> Result {
 
-        let n = x.shape().capacity;
-        let x: &Memory<_> = tensor::reference(x, /*on:*/ self.device())?;
-        let x_diff: &Memory<_> = tensor::reference(x_diff, /*on:*/ self.device())?;
-        // let result: &Memory<_> = tensor::reference(result, /*on:*/ self.device())?;
-        let result_diff: &mut Memory<_> = tensor::mut_reference(result_diff, /*on:*/ self.device())?;
+        let kernels = &self.extension_package().dependency().open_cl().kernels;
 
-        unsafe {
-            ocl::Kernel::new("sigmoid_backward_float", &self.extension_package().dependency().open_cl().program)?
-                .arg_buf(x)
-                .arg_buf(x_diff)
-                .arg_buf(result_diff)
-                .arg_scl(n as i32)
-
-                .gws([n])
-                .queue(self.device().queue().clone())
-                .enq()?;
-        }
-
-        Ok(())
+        self.batched(&[x, x_diff], result_diff, |device, inputs, result_diff| {
+            let (x, x_diff) = (inputs[0], inputs[1]);
+            activation_grad(kernels, "sigmoid_backward_float", device, x, x_diff, result_diff)
+        })
     }
-
-    // fn softmax_grad(
-    //     self: &Self,
-    //     x: &SharedTensor, 
-    //     x_diff: &SharedTensor, 
-    //     result_diff: &mut SharedTensor) -> Result {
-    //     let mut dot = 0.0;
-    //     let sig_data_slice = x.as_slice().unwrap();
-    //     let sig_dx_slice = x_diff.as_slice().unwrap();
-    //     for (t, dt) in sig_data_slice.iter().zip(sig_dx_slice.iter()) {
-    //         dot += t * dt;
-    //     }
-    //     let res = sig_data_slice.iter().zip(sig_dx_slice.iter()).map(|(t, dt)| t * (dt - dot));
-    //     result_diff.write_iter(res)?;
-    //     Ok(())
-    // }
-
-    // fn tanh_grad(
-    //     self: &Self, 
-    //     x: &SharedTensor, 
-    //     x_diff: &SharedTensor, 
-    //     result: &SharedTensor,
-    //     result_diff: &mut SharedTensor) -> Result {
-    //     let res = x.as_slice().unwrap().iter()
-    //         .zip(x_diff.as_slice().unwrap().iter())
-    //         .map(|(x, dx)| (1.0 - x.powi(2)) * *dx);
-    //     result_diff.write_iter(res)?;
-    //     Ok(())
-    // }
 }
 
 impl<P> Forward for Context<P> where 
@@ -144,80 +97,89 @@ impl<P> Forward for Context<P> where
         let result: &mut Memory<_> = tensor::mut_reference(result, /*on:*/ self.device())?;
 
         unsafe {
-            ocl::Kernel::new("log_softmax_float", &self.extension_package().dependency().open_cl().program)?
+            let kernels = &self.extension_package().dependency().open_cl().kernels;
+
+            let kernel = kernels.get(self.device(), "log_softmax_float")?
                 .arg_buf(x)
-                .arg_buf(result)
+                .arg_buf(&*result)
                 .arg_scl(n as i32)
 
                 .gws([1, 1, 1])
-                .lws([1, 1, 1])
-                .queue(self.device().queue().clone())
-                .enq()?;
+                .lws([1, 1, 1]);
+
+            let accesses = [Access::read(x), Access::write(result)];
+            self.device().enqueue("log_softmax_float", &kernel, &accesses)?;
         }
 
         Ok(())
     }
 
-    // fn relu(&self, x: &SharedTensor, result: &mut SharedTensor) -> Result {
-    //     let n = x.shape().capacity;
-    //     let x: &Memory<_> = tensor::reference(x, /*on:*/ self.device())?;
-    //     let result: &mut Memory<_> = tensor::mut_reference(result, /*on:*/ self.device())?;
+    fn sigmoid(&self, x: &SharedTensor, result: &mut SharedTensor) -> Result {
+        let kernels = &self.extension_package().dependency().open_cl().kernels;
 
-    //     unsafe {
-    //         ocl::Kernel::new("relu_float", &self.extension_package().dependency().open_cl().program)?
-    //             .arg_buf(x)
-    //             .arg_buf(result)
-    //             .arg_scl(n as i32)
+        self.batched(&[x], result, |device, inputs, result| 
+            activation(kernels, "sigmoid_float", device, inputs[0], result))
+    }
+}
 
-    //             .gws([n])
-    //             .queue(self.device().queue().clone())
-    //             .enq()?;
-    //     }
+impl<P> Extension for Context<P> where 
+    P: Dependency<Package> {
+    // ..
+}
 
-    //     Ok(())
-    // }
+/// Applies the element-wise activation `kernel` over `x` on the `device`.
+fn activation(
+    kernels: &OpenCLKernels, 
+    kernel: &str, 
+    device: &OpenCLDevice, 
+    x: &SharedTensor, 
+    result: &mut SharedTensor) -> Result {
 
-    fn sigmoid(&self, x: &SharedTensor, result: &mut SharedTensor) -> Result {
-        let n = x.shape().capacity;
-        let x: &Memory<_> = tensor::reference(x, /*on:*/ self.device())?;
-        let result: &mut Memory<_> = tensor::mut_reference(result, /*on:*/ self.device())?;
+    let n = x.shape().capacity;
+    let x: &Memory<_> = tensor::reference(x, /*on:*/ device)?;
+    let result: &mut Memory<_> = tensor::mut_reference_unsynched(result, /*on:*/ device)?;
 
-        unsafe {
-            ocl::Kernel::new("sigmoid_float", &self.extension_package().dependency().open_cl().program)?
-                .arg_buf(x)
-                .arg_buf(result)
-                .arg_scl(n as i32)
+    unsafe {
+        let k = kernels.get(device, kernel)?
+            .arg_buf(x)
+            .arg_buf(&*result)
+            .arg_scl(n as i32)
 
-                .gws([n])
-                .queue(self.device().queue().clone())
-                .enq()?;
-        }
+            .gws([n]);
 
-        Ok(())
+        device.enqueue(kernel, &k, &[Access::read(x), Access::write(result)])?;
     }
 
-    // fn softmax(&self, x: &SharedTensor, result: &mut SharedTensor) -> Result {
-    //     let n = x.shape().capacity;
-    //     let x: &Memory<_> = tensor::reference(x, /*on:*/ self.device())?;
-    //     let result: &mut Memory<_> = tensor::mut_reference(result, /*on:*/ self.device())?;
-
-    //     unsafe {
-    //         ocl::Kernel::new("softmax_float", &self.extension_package().dependency().open_cl().program)?
-    //             .arg_buf(x)
-    //             .arg_buf(result)
-    //             .arg_scl(n as i32)
+    Ok(())
+}
 
-    //             .gws([1, 1, 1])
-    //             .lws([1, 1, 1])
-    //             .queue(self.device().queue().clone())
-    //             .enq()?;
-    //     }
+/// Applies the gradient `kernel` of an element-wise activation over `x` on the `device`.
+fn activation_grad(
+    kernels: &OpenCLKernels, 
+    kernel: &str, 
+    device: &OpenCLDevice, 
+    x: &SharedTensor, 
+    x_diff: &SharedTensor, 
+    result_diff: &mut SharedTensor) -> Result {
+
+    let n = x.shape().capacity;
+    let x: &Memory<_> = tensor::reference(x, /*on:*/ device)?;
+    let x_diff: &Memory<_> = tensor::reference(x_diff, /*on:*/ device)?;
+    let result_diff: &mut Memory<_> = 
+        tensor::mut_reference_unsynched(result_diff, /*on:*/ device)?;
+
+    unsafe {
+        let k = kernels.get(device, kernel)?
+            .arg_buf(x)
+            .arg_buf(x_diff)
+            .arg_buf(&*result_diff)
+            .arg_scl(n as i32)
+
+            .gws([n]);
+
+        let accesses = [Access::read(x), Access::read(x_diff), Access::write(result_diff)];
+        device.enqueue(kernel, &k, &accesses)?;
+    }
 
-    //     Ok(())
-    // }
+    Ok(())
 }
-
-impl<P> Extension for Context<P> where 
-    P: Dependency<Package> {
-    // ..
-}
\ No newline at end of file
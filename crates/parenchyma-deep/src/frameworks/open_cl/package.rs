@@ -1,12 +1,12 @@
-use ocl;
 use std::ffi::CString;
 use parenchyma::error::Result;
-use parenchyma::frameworks::OpenCLContext;
+use parenchyma::frameworks::{OpenCLContext, OpenCLKernels};
 
 /// Caches instances of `Kernel`
 #[derive(Debug)]
 pub struct OpenCLPackage {
-    pub(in frameworks::open_cl) program: ocl::Program,
+    /// The kernels used by the operations (which keep their program alive).
+    pub(in frameworks::open_cl) kernels: OpenCLKernels,
 }
 
 impl OpenCLPackage {
@@ -18,22 +18,11 @@ impl OpenCLPackage {
             CString::new(include_str!("source/softmax.cl")).unwrap()
         ])?;
 
-        // let cl_package = Package {
-        //     tanh: program.create_kernel("tanh_float")?,
-        //     sigmoid: program.create_kernel("sigmoid_float")?,
-        //     relu: program.create_kernel("relu_float")?,
-        //     elu: program.create_kernel("elu_float")?,
-
-        //     tanh_backward: program.create_kernel("tanh_backward_float")?,
-        //     sigmoid_backward: program.create_kernel("sigmoid_backward_float")?,
-        //     relu_backward: program.create_kernel("relu_backward_float")?,
-        //     elu_backward: program.create_kernel("elu_backward_float")?,
-
-        //     convolution: program.create_kernel("convolve_ints")?,
-
-        //     program,
-        // };
+        let kernels = OpenCLKernels::new(&program, cx.devices(), &[
+            "log_softmax_float", "log_softmax_backward_float", 
+            "sigmoid_float", "sigmoid_backward_float",
+        ])?;
 
-        Ok(OpenCLPackage { program })
+        Ok(OpenCLPackage { kernels })
     }
 }
\ No newline at end of file
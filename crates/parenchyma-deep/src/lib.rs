@@ -1,12 +1,12 @@
 //! Parenchyma extension package for backend-agnostic deep neural network (NN) operations.
 
 #![allow(unused_variables)]
-#![feature(non_modrs_mods)]
 
 extern crate ocl;
 extern crate parenchyma;
+extern crate rayon;
 
-pub use self::extension_package::{Extension, Package};
+pub use self::extension_package::{Extension, Fallback, Package};
 pub mod frameworks;
 
 mod extension_package;
\ No newline at end of file
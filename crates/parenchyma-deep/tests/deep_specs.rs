@@ -1,5 +1,3 @@
-#![feature(rustc_private)]
-
 #[macro_use]
 extern crate lazy_static;
 extern crate parenchyma;
@@ -97,6 +95,34 @@ mod deep_specification_native {
         BACKEND.softmax_grad(&mut x, &mut x_diff, &mut result_diff).unwrap();
         assert_eq!(&[-5., -5., -8.], result_diff.as_slice().unwrap());
     }
+
+    #[test]
+    fn it_reports_unsupported_operations() {
+        use parenchyma::error::ErrorKind;
+
+        let (x, mut result) = get_memory();
+        let e = BACKEND.elu(&x, &mut result).unwrap_err();
+        let kind = ErrorKind::Unsupported { package: "parenchyma/deep", operation: "elu" };
+        assert_eq!(e.kind(), kind);
+        assert!(!BACKEND.capabilities().supports("parenchyma/deep", "elu"));
+        assert!(BACKEND.capabilities().supports("parenchyma/deep", "sigmoid"));
+    }
+
+    #[test]
+    fn it_computes_the_same_sigmoid_on_any_number_of_threads() {
+        let sigmoid = |threads| {
+            let mut backend: Backend<Package> = Backend::new::<Native<_>>().unwrap();
+            backend.set_num_threads(threads).unwrap();
+
+            let data: Vec<f32> = (0..100_000).map(|i| (i % 200) as f32 / 20.0 - 5.0).collect();
+            let ref x = SharedTensor::with([100, 1000], data).unwrap();
+            let ref mut result = SharedTensor::from([100, 1000]);
+            backend.sigmoid(x, result).unwrap();
+            result.as_slice().unwrap().to_vec()
+        };
+
+        assert_eq!(sigmoid(1), sigmoid(4));
+    }
 }
 
 #[cfg(test)]
@@ -169,6 +195,22 @@ mod deep_specification_opencl {
         BACKEND.softmax(&mut x, &mut result).unwrap();
         assert_eq!(&[0.25, 0.25, 0.25, 0.25], result.as_slice().unwrap());
     }
+
+    #[test]
+    fn it_falls_back_to_native_operations() {
+        use parenchyma::error::ErrorKind;
+
+        let (x, mut result) = get_memory();
+        let mut y: SharedTensor = SharedTensor::from([1, 1, 3]);
+        BACKEND.relu(&x, &mut result).unwrap();
+        BACKEND.sigmoid(&result, &mut y).unwrap();
+        assert_eq!(&[0.7310585786, 0.7310586, 0.880797], y.as_slice().unwrap());
+
+        // operations without a native implementation are still unsupported
+        let e = BACKEND.elu(&x, &mut result).unwrap_err();
+        let kind = ErrorKind::Unsupported { package: "parenchyma/deep", operation: "elu" };
+        assert_eq!(e.kind(), kind);
+    }
 }
 
 #[cfg(test)]
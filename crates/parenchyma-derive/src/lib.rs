@@ -0,0 +1,229 @@
+//! Procedural derives for Parenchyma.
+//!
+//! # Package Bundles
+//!
+//! A bundle is an extension package made of other packages (e.g., BLAS and Deep NN). Deriving
+//! `ExtensionPackage` on a struct of sub-packages generates:
+//!
+//! * the `ExtensionPackage` implementation, whose capabilities are the union of those of the
+//! sub-packages,
+//! * the `Upcast` implementation of the extension trait, converting each context implementing it
+//! into the trait object,
+//! * a `Dependency` implementation for each sub-package, so the framework implementations of the
+//! sub-packages' extensions apply to contexts packaged with the bundle,
+//! * an `ExtensionPackageCtor` implementation for each framework all of the sub-packages can be
+//! built for, building the sub-packages in the order of the fields,
+//! * optionally, a trait alias of the `Dependency` bounds.
+//!
+//! # Example Usage
+//!
+//! ```ignore
+//! extern crate parenchyma;
+//! #[macro_use]
+//! extern crate parenchyma_derive;
+//!
+//! #[derive(ExtensionPackage)]
+//! #[package(name = "parenchyma/ml", extension = "Extension", dependencies = "Dependencies")]
+//! pub struct Package {
+//!     blas: parenchyma_blas::Package,
+//!     deep: parenchyma_deep::Package,
+//! }
+//!
+//! pub trait Extension where Self: parenchyma_blas::Extension + parenchyma_deep::Extension {
+//!     // ..
+//! }
+//!
+//! impl<P> Extension for OpenCLContext<P> where P: Dependencies { }
+//! ```
+//!
+//! The attribute takes:
+//!
+//! * `name` - the name of the package (see `ExtensionPackage::package_name`).
+//! * `extension` - the extension trait of the package, defined in the same crate.
+//! * `dependencies` (optional) - the name of the trait alias to generate, implemented by any
+//! package depending on each of the sub-packages.
+
+#![recursion_limit = "128"]
+
+extern crate proc_macro;
+extern crate proc_macro2;
+#[macro_use]
+extern crate quote;
+#[macro_use]
+extern crate syn;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::ToTokens;
+use syn::{Data, DeriveInput, Error, Fields, Ident, Lit, LitStr, Meta, NestedMeta, Path};
+
+/// Derives `ExtensionPackage` for a bundle of packages (see the crate documentation).
+#[proc_macro_derive(ExtensionPackage, attributes(package))]
+pub fn derive_extension_package(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+/// The arguments of the `package` attribute.
+struct Attributes {
+    name: LitStr,
+    extension: Path,
+    dependencies: Option<Ident>,
+}
+
+fn expand(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(&input.generics, "a package bundle can't be generic"));
+    }
+
+    let fields = match input.data {
+        Data::Struct(ref data) => match data.fields {
+            Fields::Named(ref fields) => &fields.named,
+            _ => return Err(Error::new_spanned(&input.ident,
+                "a package bundle has to be a struct with named fields")),
+        },
+        _ => return Err(Error::new_spanned(&input.ident, "a package bundle has to be a struct")),
+    };
+
+    if fields.is_empty() {
+        return Err(Error::new_spanned(&input.ident, "a package bundle needs a sub-package"));
+    }
+
+    // the `Dependency` implementations would conflict
+    let keys: Vec<String> = fields.iter().map(|field| field.ty.clone().into_token_stream())
+        .map(|ty| ty.to_string())
+        .collect();
+
+    for (i, field) in fields.iter().enumerate() {
+        if keys[..i].contains(&keys[i]) {
+            let message = "the sub-packages of a bundle have to be distinct";
+            return Err(Error::new_spanned(&field.ty, message));
+        }
+    }
+
+    let Attributes { name, extension, dependencies } = attributes(input)?;
+
+    let package = &input.ident;
+    let vis = &input.vis;
+    let names: Vec<_> = fields.iter().map(|field| field.ident.as_ref().unwrap()).collect();
+    let types: Vec<_> = fields.iter().map(|field| &field.ty).collect();
+    let types = &types;
+
+    let dependencies = dependencies.map(|dependencies| {
+        let doc = format!("A package depending on each of the sub-packages of `{}`.", package);
+
+        quote! {
+            #[doc = #doc]
+            #vis trait #dependencies:
+                #(::parenchyma::extension_package::Dependency<#types>)+* {
+                // ..
+            }
+
+            impl<D> #dependencies for D
+                where D: #(::parenchyma::extension_package::Dependency<#types>)+* {
+                // ..
+            }
+        }
+    });
+
+    let ctor = quote!(::parenchyma::extension_package::ExtensionPackageCtor<TargetContext>);
+
+    let dependency_impls = names.iter().zip(types).map(|(field, ty)| quote! {
+        impl ::parenchyma::extension_package::Dependency<#ty> for #package {
+            fn dependency(&self) -> &#ty {
+                &self.#field
+            }
+        }
+    });
+
+    let bounds = types.iter().map(|ty| quote!(#ty: #ctor));
+    let members = names.iter().zip(types).map(|(field, ty)| quote! {
+        #field: <#ty as #ctor>::package(target)?
+    });
+
+    Ok(quote! {
+        impl ::parenchyma::extension_package::ExtensionPackage for #package {
+            type Extension = dyn #extension;
+
+            fn package_name(&self) -> &'static str {
+                #name
+            }
+
+            fn capabilities(framework: &str) -> ::parenchyma::extension_package::Capabilities {
+                ::parenchyma::extension_package::Capabilities::default()
+                    #(.union(<#types as ::parenchyma::extension_package::ExtensionPackage>
+                        ::capabilities(framework)))*
+            }
+        }
+
+        impl<C> ::parenchyma::extension_package::Upcast<C> for dyn #extension
+            where C: #extension + 'static {
+
+            fn upcast(context: &C) -> &(dyn #extension + 'static) {
+                context
+            }
+        }
+
+        #(#dependency_impls)*
+
+        impl<TargetContext> #ctor for #package where #(#bounds),* {
+            fn package(target: &mut TargetContext) -> ::parenchyma::error::Result<Self> {
+                Ok(#package {
+                    #(#members,)*
+                })
+            }
+        }
+
+        #dependencies
+    })
+}
+
+fn attributes(input: &DeriveInput) -> Result<Attributes, Error> {
+    let mut name = None;
+    let mut extension = None;
+    let mut dependencies = None;
+
+    let is_package = |attribute: &&syn::Attribute| attribute.path.segments.len() == 1 &&
+        attribute.path.segments[0].ident == "package";
+
+    for attribute in input.attrs.iter().filter(is_package) {
+        let list = match attribute.parse_meta()? {
+            Meta::List(list) => list,
+            meta => return Err(Error::new_spanned(meta,
+                "expected `#[package(name = \"..\", extension = \"..\")]`")),
+        };
+
+        for nested in list.nested {
+            let pair = match nested {
+                NestedMeta::Meta(Meta::NameValue(pair)) => pair,
+                nested => return Err(Error::new_spanned(nested, "expected `key = \"value\"`")),
+            };
+
+            let value = match pair.lit {
+                Lit::Str(ref value) => value.clone(),
+                ref lit => return Err(Error::new_spanned(lit, "expected a string")),
+            };
+
+            match &pair.ident.to_string()[..] {
+                "name" => name = Some(value),
+                "extension" => extension = Some(value.parse()?),
+                "dependencies" => dependencies = Some(value.parse()?),
+                _ => return Err(Error::new_spanned(&pair.ident,
+                    "unknown key - expected `name`, `extension`, or `dependencies`")),
+            }
+        }
+    }
+
+    let missing = |key: &str| Error::new(Span::call_site(),
+        format!("#[derive(ExtensionPackage)] requires `#[package({} = \"..\")]`", key));
+
+    Ok(Attributes {
+        name: name.ok_or_else(|| missing("name"))?,
+        extension: extension.ok_or_else(|| missing("extension"))?,
+        dependencies,
+    })
+}
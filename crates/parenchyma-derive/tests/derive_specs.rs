@@ -0,0 +1,83 @@
+extern crate parenchyma;
+#[macro_use]
+extern crate parenchyma_derive;
+
+#[cfg(test)]
+mod derive_spec {
+    use parenchyma::error::Result;
+    use parenchyma::extension_package::*;
+    use parenchyma::frameworks::{Mock, MockContext};
+    use parenchyma::prelude::*;
+
+    macro_rules! package {
+        ($package:ident, $name:expr, $operation:expr) => {
+            pub struct $package {
+                devices: usize,
+            }
+
+            impl ExtensionPackage for $package {
+                type Extension = ::std::any::Any;
+
+                fn package_name(&self) -> &'static str {
+                    $name
+                }
+
+                fn capabilities(framework: &str) -> Capabilities {
+                    match framework {
+                        "mock" => Capabilities::new($name, &[$operation]),
+                        _ => Capabilities::default(),
+                    }
+                }
+            }
+
+            impl ExtensionPackageCtor<MockContext<()>> for $package {
+                fn package(target: &mut MockContext<()>) -> Result<Self> {
+                    Ok($package { devices: target.devices().len() })
+                }
+            }
+        }
+    }
+
+    package!(Blas, "test/blas", "axpy");
+    package!(Deep, "test/deep", "sigmoid");
+
+    #[derive(ExtensionPackage)]
+    #[package(name = "test/bundle", extension = "Extension", dependencies = "Dependencies")]
+    pub struct Bundle {
+        blas: Blas,
+        deep: Deep,
+    }
+
+    pub trait Extension {
+        fn package_devices(&self) -> (usize, usize);
+    }
+
+    impl<P> Extension for MockContext<P> where P: Dependencies {
+        fn package_devices(&self) -> (usize, usize) {
+            let blas: &Blas = self.extension_package().dependency();
+            let deep: &Deep = self.extension_package().dependency();
+            (blas.devices, deep.devices)
+        }
+    }
+
+    #[test]
+    fn it_builds_each_sub_package_for_the_framework() {
+        let backend: Backend<Bundle> = Backend::new::<Mock<_>>().unwrap();
+        let devices = Mock::<()>::DEVICES;
+
+        assert_eq!(backend.package_devices(), (devices, devices));
+    }
+
+    #[test]
+    fn it_names_the_bundle_and_unites_the_capabilities() {
+        let bundle = Bundle { blas: Blas { devices: 1 }, deep: Deep { devices: 1 } };
+        assert_eq!(bundle.package_name(), "test/bundle");
+
+        let capabilities = Bundle::capabilities("mock");
+        assert!(capabilities.supports("test/blas", "axpy"));
+        assert!(capabilities.supports("test/deep", "sigmoid"));
+        assert!(!capabilities.supports("test/blas", "sigmoid"));
+        assert_eq!(capabilities.operations("test/deep").collect::<Vec<_>>(), vec!["sigmoid"]);
+        assert!(Bundle::capabilities("Open CL").is_empty());
+    }
+}
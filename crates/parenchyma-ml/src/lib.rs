@@ -13,8 +13,9 @@
 //! use parenchyma::prelude::*;
 //! use extension_package::package::Package as MachLrnPackage;
 //! 
-//! // Initialize an OpenCL or CUDA backend packaged with the NN extension.
-//! let backend = BackendConfig::<MachLrnPackage>::new::<OpenCL>()?;
+//! // Initialize an OpenCL or CUDA backend packaged with the NN extension. The device can be chosen
+//! // through the environment (e.g., `PARENCHYMA_DEVICE_KIND=gpu`).
+//! let backend = BackendConfig::<MachLrnPackage>::from_env()?.backend()?;
 //! 
 //! // Initialize two tensors.
 //! let ref x: SharedTensor = array![3.5, 12.4, 0.5, 6.5].into();
@@ -31,6 +32,8 @@
 extern crate parenchyma;
 extern crate parenchyma_blas;
 extern crate parenchyma_deep;
+#[macro_use]
+extern crate parenchyma_derive;
 
 pub use self::extension_package::{Dependencies, Extension, Package};
 
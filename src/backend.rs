@@ -37,11 +37,17 @@ use std::fmt;
 use std::ops::Deref;
 
 use super::compute_device::ComputeDevice;
+use super::config::BackendConfig;
 use super::context::{Context, ContextCtor};
 use super::error::{Error, ErrorKind, Result};
-use super::extension_package::ExtensionPackage;
+use super::extension_package::{Capabilities, ExtensionPackage};
 use super::framework::{Framework, FrameworkCtor};
-use super::hardware::Hardware;
+use super::frameworks::{Native, NativeContext, OpenCL, OpenCLContext};
+use super::hardware::{Hardware, HardwareKind};
+use super::kernel::{CustomKernel, KernelArg};
+use super::plugin::Plugin;
+use super::profile::Profile;
+use super::stats::TransferStats;
 
 /// The representation of the backend.
 pub struct Backend<Package = ()> {
@@ -65,7 +71,8 @@ pub struct Backend<Package = ()> {
 }
 
 impl<P> Backend<P> where P: ExtensionPackage {
-    /// Constructs a backend of the provided type with its default configurations. 
+    /// Constructs a backend of the provided type with its default configurations (i.e., using
+    /// the framework's default selection of hardware). 
     ///
     /// # Return value
     ///
@@ -76,7 +83,7 @@ impl<P> Backend<P> where P: ExtensionPackage {
               F::Context: ContextCtor<P,F=F> {
 
         let framework = F::new()?;
-        let hardware = framework.hardware().to_vec();
+        let hardware = framework.default_selection();
         Self::with(framework, hardware)
     }
 
@@ -85,7 +92,9 @@ impl<P> Backend<P> where P: ExtensionPackage {
     /// # Arguments
     ///
     /// * `framework` - One of the available frameworks.
-    /// * `selection` - A selection of hardware provided by the specified `framework`.
+    /// * `selection` - A selection of hardware provided by the specified `framework`. The
+    /// framework may restrict which hardware can be combined (e.g., Open CL only accepts hardware
+    /// from a single platform).
     ///
     /// # Return value
     ///
@@ -96,18 +105,85 @@ impl<P> Backend<P> where P: ExtensionPackage {
               F::Context: ContextCtor<P,F=F>, {
 
         info!("[PARENCHYMA] Constructing a backend using the {} framework", framework.name());
-        let context = box F::Context::new(&framework, &selection)? as Box<Context<Package=P>>;
-        let framework = box framework as Box<Framework>;
+        let context = Box::new(F::Context::new(&framework, &selection)?) as Box<Context<Package=P>>;
+        let framework = Box::new(framework) as Box<Framework>;
         Ok(Self { framework, context, selection })
     }
 }
 
+impl<P> Backend<P> where P: ExtensionPackage {
+    /// Initializes the framework `F` and constructs a backend using all of its hardware.
+    ///
+    /// Unlike `new`, a framework that doesn't provide any hardware is treated as a failure.
+    fn probe<F>() -> Result<Self>
+        where F: FrameworkCtor,
+              F::Context: ContextCtor<P,F=F> {
+
+        let framework = F::new()?;
+        let hardware = framework.default_selection();
+
+        if hardware.is_empty() {
+            let message = format!("the {} framework doesn't provide any hardware", framework.name());
+            return Err(Error::new(ErrorKind::Other, message));
+        }
+
+        Self::with(framework, hardware)
+    }
+}
+
+impl<P> Default for Backend<P>
+    where P: ExtensionPackage,
+          NativeContext<P>: ContextCtor<P,F=Native<P>>,
+          OpenCLContext<P>: ContextCtor<P,F=OpenCL<P>> {
+
+    /// Constructs a backend using the most potent framework available on the machine.
+    ///
+    /// Frameworks are probed in the order CUDA -> Open CL -> Native. A framework is skipped if it 
+    /// fails to initialize, doesn't provide any hardware, or if the package fails to compile for
+    /// it. The native framework is always available, so this function never fails.
+    ///
+    /// If the selected framework provides a GPU or an accelerator, it is made the active device.
+    fn default() -> Self {
+        // todo: CUDA
+        let candidates: [(&str, fn() -> Result<Self>); 1] = [
+            ("Open CL", Self::probe::<OpenCL<P>>),
+        ];
+
+        for &(name, probe) in candidates.iter() {
+            match probe() {
+                Ok(mut backend) => {
+                    let _ = backend.select(&|hardware| match hardware.kind {
+                        HardwareKind::GPU | HardwareKind::Accelerator => true,
+                        _ => false,
+                    });
+
+                    info!("[PARENCHYMA] Selected the {} framework ({} device(s))", 
+                        name, backend.selection().len());
+                    return backend;
+                },
+
+                Err(e) => {
+                    warn!("[PARENCHYMA] Skipping the {} framework: {}", name, e.chain());
+                }
+            }
+        }
+
+        info!("[PARENCHYMA] Falling back to the native framework");
+        Self::new::<Native<P>>().expect("the native framework is always available")
+    }
+}
+
 impl<P> Backend<P> where P: ExtensionPackage {
     /// Returns the active framework's active context's active device.
     pub fn active_device(&self) -> &dyn ComputeDevice {
         self.context.active_codev()
     }
 
+    /// Returns the framework of the backend.
+    pub fn framework(&self) -> &Framework {
+        &*self.framework
+    }
+
     /// Simply returns the selected hardware.
     pub fn selection(&self) -> &[Hardware] {
         &self.selection
@@ -139,8 +215,100 @@ impl<P> Backend<P> where P: ExtensionPackage {
         }
     }
     
-    /// Synchronizes backend.
+    /// Enables or disables data-parallel execution across all of the selected devices.
+    ///
+    /// When enabled, batched operations (e.g., `gemm` or element-wise activations) split their 
+    /// operands along the batch axis, execute each share on a different device and gather the
+    /// results into the output tensor. Operations that can't be split still run on the active 
+    /// device.
+    pub fn set_data_parallel(&mut self, enabled: bool) -> Result {
+        self.context.set_data_parallel(enabled)
+    }
+
+    /// Sets the number of threads the native framework executes operations on.
+    ///
+    /// The native thread pool is sized from the number of physical cores by default. Open CL 
+    /// backends use it for the operations falling back to the native framework, and backends of
+    /// the other frameworks ignore this setting.
+    pub fn set_num_threads(&mut self, n: usize) -> Result {
+        self.context.set_num_threads(n)
+    }
+
+    /// Enables or disables profiling.
+    ///
+    /// While enabled, the timings of every kernel are recorded under the name of the operation 
+    /// that launched it, and those of every transfer under the reason of the synchronization that
+    /// issued it (e.g., `read access`; see the [`profile`](./profile/index.html) module). Fails if
+    /// the framework doesn't support profiling (currently, only Open CL does).
+    pub fn set_profiling(&mut self, enabled: bool) -> Result {
+        self.context.set_profiling(enabled)
+    }
+
+    /// Returns the events recorded since profiling was enabled or last reset.
+    pub fn profile(&self) -> Result<Profile> {
+        self.context.profile()
+    }
+
+    /// Discards the recorded events.
+    pub fn reset_profile(&self) {
+        self.context.reset_profile()
+    }
+
+    /// Returns the operations the backend's package implements for its framework, including 
+    /// those falling back to another framework (e.g., from Open CL to native).
+    ///
+    /// Calling any other operation returns an `ErrorKind::Unsupported` error.
+    pub fn capabilities(&self) -> Capabilities {
+        let capabilities = P::capabilities(self.framework().name());
+
+        match self.context.fallback_framework() {
+            Some(framework) => capabilities.union(P::capabilities(framework)),
+            _ => capabilities,
+        }
+    }
+
+    /// Returns the statistics of the transfers made to synchronize tensors with the backend's 
+    /// devices (see the [`stats`](./stats/index.html) module).
+    ///
+    /// The native framework doesn't keep any, since its memory never needs to be transferred to 
+    /// be used by it.
+    pub fn transfer_stats(&self) -> TransferStats {
+        self.context.transfer_stats()
+    }
+
+    /// Resets the transfer statistics.
+    pub fn reset_transfer_stats(&self) {
+        self.context.reset_transfer_stats()
+    }
+
+    /// Blocks until the commands enqueued on the devices (e.g., on each Open CL stream) have 
+    /// completed.
     pub fn synchronize(&self) -> Result {
+        self.context.synchronize()
+    }
+
+    /// Registers a custom kernel (see the [`kernel`](../kernel/index.html) module).
+    ///
+    /// Kernels are registered with the context, so they have to be registered again with a 
+    /// backend created for another framework.
+    pub fn register_kernel(&mut self, kernel: CustomKernel) -> Result {
+        self.context.register_kernel(&kernel)
+    }
+
+    /// Launches the custom kernel registered under the `name` on the active device.
+    ///
+    /// The tensor arguments are synchronized with the device, and the kernel is launched with a 
+    /// work-item for each element of its first mutable tensor argument.
+    pub fn launch(&self, name: &str, args: &mut [KernelArg]) -> Result {
+        self.context.launch_kernel(name, args)
+    }
+
+    /// Registers the kernels of a plugin loaded from a shared library (see `Plugin::load`).
+    pub fn register_plugin(&mut self, plugin: &Plugin) -> Result {
+        for kernel in plugin.kernels() {
+            self.context.register_kernel(kernel)?;
+        }
+
         Ok(())
     }
 }
@@ -153,6 +321,110 @@ impl<P> Deref for Backend<P> where P: ExtensionPackage {
     }
 }
 
+/// A backend whose framework is chosen, and can be replaced, at runtime.
+///
+/// `Backend::new` fixes the framework at compile time. An `AnyBackend` is constructed from a 
+/// `BackendConfig` instead and can be rebuilt for another framework (or other hardware) while the
+/// application is running. Live tensors follow it through their host copies.
+///
+/// # Example
+///
+/// ```
+/// extern crate parenchyma;
+///
+/// use parenchyma::backend::AnyBackend;
+/// use parenchyma::prelude::*;
+///
+/// let mut backend: AnyBackend = AnyBackend::default();
+/// let x: SharedTensor = SharedTensor::with([2], vec![1., 2.]).unwrap();
+///
+/// let native = BackendConfig::new().framework("native").unwrap();
+/// backend.switch(&native).unwrap();
+/// assert_eq!(backend.framework().name(), "native/host");
+/// assert_eq!(x.as_slice().unwrap(), &[1., 2.]);
+/// ```
+pub struct AnyBackend<P = ()> {
+    backend: Backend<P>,
+}
+
+impl<P> AnyBackend<P>
+    where P: ExtensionPackage,
+          NativeContext<P>: ContextCtor<P,F=Native<P>>,
+          OpenCLContext<P>: ContextCtor<P,F=OpenCL<P>> {
+
+    /// Constructs a backend from the provided `config`.
+    pub fn new(config: &BackendConfig<P>) -> Result<Self> {
+        Ok(AnyBackend { backend: config.backend()? })
+    }
+
+    /// Replaces the backend by one constructed from `config`.
+    ///
+    /// The new backend is constructed first - if that fails, the current backend is left
+    /// untouched. Dropping the current backend retires the memory allocated on its devices, so
+    /// the tensors move over lazily: the next time a tensor is accessed, it's synchronized with
+    /// the host if needed and its copies on the old devices are dropped (see `Memory::retired`).
+    pub fn switch(&mut self, config: &BackendConfig<P>) -> Result {
+        let backend = config.backend()?;
+
+        info!("[PARENCHYMA] Switching from the {} framework to the {} framework", 
+            self.backend.framework.name(), backend.framework.name());
+
+        self.backend = backend;
+
+        Ok(())
+    }
+}
+
+impl<P> AnyBackend<P> {
+    /// Returns the underlying backend.
+    pub fn backend(&self) -> &Backend<P> {
+        &self.backend
+    }
+
+    /// Returns the underlying backend, e.g., for selecting another device.
+    pub fn backend_mut(&mut self) -> &mut Backend<P> {
+        &mut self.backend
+    }
+}
+
+impl<P> AnyBackend<P> where P: ExtensionPackage {
+    /// Returns the framework of the current backend.
+    pub fn framework(&self) -> &Framework {
+        self.backend.framework()
+    }
+
+    /// Returns the active device of the current backend.
+    pub fn active_device(&self) -> &dyn ComputeDevice {
+        self.backend.active_device()
+    }
+}
+
+impl<P> Default for AnyBackend<P>
+    where P: ExtensionPackage,
+          NativeContext<P>: ContextCtor<P,F=Native<P>>,
+          OpenCLContext<P>: ContextCtor<P,F=OpenCL<P>> {
+
+    /// Constructs a backend using the most potent framework available (see `Backend::default`).
+    fn default() -> Self {
+        AnyBackend { backend: Backend::default() }
+    }
+}
+
+impl<P> Deref for AnyBackend<P> where P: ExtensionPackage {
+    type Target = P::Extension;
+
+    fn deref<'a>(&'a self) -> &'a Self::Target {
+        self.backend.context.extension()
+    }
+}
+
+impl<P> fmt::Debug for AnyBackend<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "A runtime-swappable backend provided by the {} framework", 
+            self.backend.framework.name())
+    }
+}
+
 impl<E> fmt::Debug for Backend<E> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "A backend provided by the {} framework", self.framework.name())
@@ -4,7 +4,7 @@ use std::any::{Any, TypeId};
 
 use super::error::Result;
 use super::memory::Memory;
-use super::tensor::TensorShape;
+use super::tensor::{Distribution, TensorShape};
 
 /// An device capable of processing data.
 ///
@@ -12,8 +12,21 @@ use super::tensor::TensorShape;
 ///
 /// ## Load Balancing Multiple Devices
 ///
-/// todo..
-pub trait ComputeDevice: Any + Allocate<f64> + Allocate<f32> { }
+/// Contexts holding several devices can execute batched operations in a data-parallel fashion:
+/// the batch axis (the first dimension) of the operands is split into one share per 
+/// device (see [`partition`](./fn.partition.html)), each device processes its share and the 
+/// results are gathered into the output tensor. Devices are currently weighted equally.
+pub trait ComputeDevice: 
+    Any + 
+    Allocate<f64> + Allocate<f32> + 
+    Initialize<f64> + Initialize<f32> { }
+
+/// Splits `n` items into `parts` contiguous shares whose sizes differ by at most one.
+///
+/// The first `n % parts` shares receive an extra item.
+pub fn partition(n: usize, parts: usize) -> Vec<usize> {
+    (0..parts).map(|i| n / parts + if i < n % parts { 1 } else { 0 }).collect()
+}
 
 /// Implemented by allocators.
 pub trait Allocate<T> {
@@ -21,6 +34,15 @@ pub trait Allocate<T> {
     fn allocate(&self, shape: &TensorShape) -> Result<Box<Memory<T>>>;
 }
 
+/// Implemented by devices capable of generating random numbers in place.
+pub trait Initialize<T> {
+    /// Overwrites the `memory` with samples of the `distribution` drawn from the stream 
+    /// identified by `seed`.
+    ///
+    /// The element at index `i` must be equal to `distribution.sample(seed, i)`.
+    fn initialize(&self, memory: &mut Memory<T>, distribution: &Distribution, seed: u64) -> Result;
+}
+
 impl ComputeDevice {
     /// Returns `true` if the boxed type is the same as `T`.
     #[inline]
@@ -28,7 +50,7 @@ impl ComputeDevice {
         // Get TypeId of the type this function is instantiated with
         let t = TypeId::of::<T>();
         // Get TypeId of the type in the trait object
-        let boxed = self.get_type_id();
+        let boxed = self.type_id();
         // Compare both TypeIds on equality
         t == boxed
     }
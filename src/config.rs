@@ -0,0 +1,388 @@
+//! Backend configurations.
+//!
+//! A `BackendConfig` describes which framework and hardware a backend should use, without
+//! hardcoding the choice into the application. The preferences can be read from the environment
+//! or from a TOML file, so that a deployed binary can be redirected to a different device.
+//!
+//! # Environment Variables
+//!
+//! * `PARENCHYMA_CONFIG` - the path of a TOML file loaded before reading the other variables
+//! * `PARENCHYMA_FRAMEWORK` - the framework (`native` or `opencl`)
+//! * `PARENCHYMA_PLATFORM` - the index of the platform (Open CL)
+//! * `PARENCHYMA_DEVICE_KIND` - the kind of device (e.g., `gpu` or `cpu`)
+//! * `PARENCHYMA_DEVICE` - a regular expression matched against the device names
+//! * `PARENCHYMA_COMPILER_OPTIONS` - the options passed to the Open CL compiler
+//! * `PARENCHYMA_PROGRAM_CACHE` - the directory the Open CL program binaries are cached in
+//! * `PARENCHYMA_NUM_THREADS` - the number of threads of the native thread pool
+//!
+//! # TOML
+//!
+//! The keys of a configuration file are the same as the variables' (without the prefix):
+//!
+//! ```toml
+//! framework = "opencl"
+//! platform = 0
+//! device_kind = "gpu"
+//! device = "Radeon|GeForce"
+//! compiler_options = "-cl-fast-relaxed-math"
+//! program_cache = "/var/cache/parenchyma"
+//! num_threads = 4
+//! ```
+//!
+//! # Example
+//!
+//! ```
+//! extern crate parenchyma;
+//!
+//! use parenchyma::prelude::*;
+//!
+//! let backend: Backend = BackendConfig::from_env().unwrap().backend().unwrap();
+//! ```
+
+use regex::Regex;
+use std::{env, fmt};
+use std::fs::File;
+use std::io::Read;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use toml;
+
+use super::backend::Backend;
+use super::context::ContextCtor;
+use super::error::{Error, ErrorKind, Result};
+use super::extension_package::ExtensionPackage;
+use super::framework::{Framework, FrameworkCtor};
+use super::frameworks::{Native, NativeContext, OpenCL, OpenCLContext};
+use super::hardware::{Hardware, HardwareKind};
+
+/// The frameworks a configuration can refer to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum FrameworkChoice {
+    Native,
+    OpenCL,
+}
+
+impl FrameworkChoice {
+    fn parse(name: &str) -> Result<FrameworkChoice> {
+        match &name.to_lowercase().replace(|c| c == ' ' || c == '_' || c == '-', "")[..] {
+            "native" | "host" => Ok(FrameworkChoice::Native),
+            "opencl" => Ok(FrameworkChoice::OpenCL),
+            _ => {
+                let message = format!("`{}` isn't a framework", name);
+                Err(Error::new(ErrorKind::InvalidConfiguration, message))
+            }
+        }
+    }
+}
+
+/// Device preferences used to construct a backend.
+///
+/// Unset preferences don't restrict the selection. If no framework is configured, the frameworks
+/// are tried in the same order as `Backend::default`.
+pub struct BackendConfig<P = ()> {
+    framework: Option<FrameworkChoice>,
+    platform: Option<usize>,
+    kind: Option<HardwareKind>,
+    device: Option<Regex>,
+    compiler_options: Option<String>,
+    program_cache: Option<PathBuf>,
+    num_threads: Option<usize>,
+    package: PhantomData<P>,
+}
+
+impl<P> BackendConfig<P> {
+    /// Creates an empty configuration.
+    pub fn new() -> Self {
+        BackendConfig {
+            framework: None,
+            platform: None,
+            kind: None,
+            device: None,
+            compiler_options: None,
+            program_cache: None,
+            num_threads: None,
+            package: PhantomData,
+        }
+    }
+
+    /// Reads the configuration from the environment (see the [module](./index.html) docs).
+    ///
+    /// The file referred to by `PARENCHYMA_CONFIG` is loaded first - the other variables take
+    /// precedence over its values.
+    pub fn from_env() -> Result<Self> {
+        let mut config = match env::var("PARENCHYMA_CONFIG") {
+            Ok(path) => Self::from_file(path)?,
+            _ => Self::new(),
+        };
+
+        if let Ok(name) = env::var("PARENCHYMA_FRAMEWORK") {
+            config = config.framework(&name)?;
+        }
+
+        if let Ok(platform) = env::var("PARENCHYMA_PLATFORM") {
+            let platform = platform.trim().parse().map_err(|_| {
+                let message = format!("`{}` isn't a platform index", platform);
+                Error::new(ErrorKind::InvalidConfiguration, message)
+            })?;
+
+            config = config.platform(platform);
+        }
+
+        if let Ok(kind) = env::var("PARENCHYMA_DEVICE_KIND") {
+            config = config.kind(kind.parse()?);
+        }
+
+        if let Ok(pattern) = env::var("PARENCHYMA_DEVICE") {
+            config = config.device(&pattern)?;
+        }
+
+        if let Ok(options) = env::var("PARENCHYMA_COMPILER_OPTIONS") {
+            config = config.compiler_options(options);
+        }
+
+        if let Ok(directory) = env::var("PARENCHYMA_PROGRAM_CACHE") {
+            config = config.program_cache(directory);
+        }
+
+        if let Ok(n) = env::var("PARENCHYMA_NUM_THREADS") {
+            let n = n.trim().parse().ok().filter(|&n: &usize| n > 0).ok_or_else(|| {
+                let message = format!("`{}` isn't a number of threads", n);
+                Error::new(ErrorKind::InvalidConfiguration, message)
+            })?;
+
+            config = config.num_threads(n);
+        }
+
+        Ok(config)
+    }
+
+    /// Reads the configuration from a TOML file.
+    pub fn from_file<Q>(path: Q) -> Result<Self> where Q: AsRef<Path> {
+        let mut contents = String::new();
+
+        File::open(path.as_ref())
+            .and_then(|mut file| file.read_to_string(&mut contents))
+            .map_err(|e| Error::new(ErrorKind::InvalidConfiguration, e))?;
+
+        Self::from_toml(&contents)
+    }
+
+    /// Parses a configuration from a TOML string.
+    pub fn from_toml(contents: &str) -> Result<Self> {
+        let value: toml::Value = contents.parse()
+            .map_err(|e: toml::de::Error| Error::new(ErrorKind::InvalidConfiguration, e))?;
+
+        let table = match value.as_table() {
+            Some(table) => table,
+            _ => return Err(Error::new(ErrorKind::InvalidConfiguration, "expected a table")),
+        };
+
+        let mut config = Self::new();
+
+        for (key, value) in table {
+            let invalid = || {
+                let message = format!("invalid value for `{}`: {}", key, value);
+                Error::new(ErrorKind::InvalidConfiguration, message)
+            };
+
+            config = match &key[..] {
+                "framework" => config.framework(value.as_str().ok_or_else(invalid)?)?,
+                "platform" => {
+                    let platform = value.as_integer().filter(|&i| i >= 0).ok_or_else(invalid)?;
+                    config.platform(platform as usize)
+                },
+                "device_kind" => config.kind(value.as_str().ok_or_else(invalid)?.parse()?),
+                "device" => config.device(value.as_str().ok_or_else(invalid)?)?,
+                "compiler_options" => config.compiler_options(value.as_str().ok_or_else(invalid)?),
+                "program_cache" => config.program_cache(value.as_str().ok_or_else(invalid)?),
+                "num_threads" => {
+                    let n = value.as_integer().filter(|&i| i > 0).ok_or_else(invalid)?;
+                    config.num_threads(n as usize)
+                },
+                _ => {
+                    let message = format!("unknown configuration key `{}`", key);
+                    return Err(Error::new(ErrorKind::InvalidConfiguration, message));
+                }
+            };
+        }
+
+        Ok(config)
+    }
+
+    /// Sets the framework (`native` or `opencl`).
+    pub fn framework(mut self, name: &str) -> Result<Self> {
+        self.framework = Some(FrameworkChoice::parse(name)?);
+        Ok(self)
+    }
+
+    /// Restricts the selection to the hardware of a single platform.
+    pub fn platform(mut self, platform: usize) -> Self {
+        self.platform = Some(platform);
+        self
+    }
+
+    /// Restricts the selection to a kind of hardware.
+    pub fn kind(mut self, kind: HardwareKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    /// Restricts the selection to the hardware whose name matches the regular expression.
+    pub fn device(mut self, pattern: &str) -> Result<Self> {
+        let regex = Regex::new(pattern)
+            .map_err(|e| Error::new(ErrorKind::InvalidConfiguration, e))?;
+
+        self.device = Some(regex);
+        Ok(self)
+    }
+
+    /// Sets the options passed to the Open CL compiler.
+    pub fn compiler_options<S>(mut self, options: S) -> Self where S: Into<String> {
+        self.compiler_options = Some(options.into());
+        self
+    }
+
+    /// Sets the directory the Open CL program binaries are cached in.
+    pub fn program_cache<D>(mut self, directory: D) -> Self where D: Into<PathBuf> {
+        self.program_cache = Some(directory.into());
+        self
+    }
+
+    /// Sets the number of threads of the native thread pool.
+    pub fn num_threads(mut self, n: usize) -> Self {
+        self.num_threads = Some(n);
+        self
+    }
+
+    /// Validates the preferences against the hardware of the `framework` and returns the
+    /// matching selection.
+    ///
+    /// If the matching hardware spans several platforms and no platform is configured, the
+    /// platform of the first match is used.
+    pub fn selection(&self, framework: &Framework) -> Result<Vec<Hardware>> {
+        if self.platform.is_none() && self.kind.is_none() && self.device.is_none() {
+            return Ok(framework.default_selection());
+        }
+
+        if let Some(platform) = self.platform {
+            if !framework.hardware().iter().any(|h| h.platform == platform) {
+                let message = format!(
+                    "the {} framework doesn't provide any hardware on the platform {}",
+                    framework.name(), platform);
+                return Err(Error::new(ErrorKind::InvalidSelection, message));
+            }
+        }
+
+        let matches: Vec<Hardware> = framework.hardware().iter()
+            .filter(|h| self.platform.map_or(true, |p| h.platform == p))
+            .filter(|h| self.kind.as_ref().map_or(true, |k| h.kind == *k))
+            .filter(|h| self.device.as_ref().map_or(true, |r| r.is_match(&h.name)))
+            .cloned()
+            .collect();
+
+        match matches.first().map(|h| h.platform) {
+            Some(platform) => Ok(matches.into_iter().filter(|h| h.platform == platform).collect()),
+            _ => {
+                let available: Vec<_> = framework.hardware().iter()
+                    .map(|h| format!("{} ({:?})", h.name, h.kind))
+                    .collect();
+                let message = format!(
+                    "no hardware of the {} framework matches the configuration {:?} - available: {}",
+                    framework.name(), self, available.join(", "));
+                Err(Error::new(ErrorKind::InvalidSelection, message))
+            }
+        }
+    }
+}
+
+impl<P> BackendConfig<P>
+    where P: ExtensionPackage,
+          NativeContext<P>: ContextCtor<P,F=Native<P>>,
+          OpenCLContext<P>: ContextCtor<P,F=OpenCL<P>> {
+
+    /// Constructs a backend from the configuration.
+    ///
+    /// If a framework is configured, failing to initialize it or to find matching hardware is an
+    /// error. Otherwise, frameworks are skipped until one provides matching hardware.
+    pub fn backend(&self) -> Result<Backend<P>> {
+        let mut backend = self.framework_backend()?;
+
+        if let Some(n) = self.num_threads {
+            backend.set_num_threads(n)?;
+        }
+
+        Ok(backend)
+    }
+
+    fn framework_backend(&self) -> Result<Backend<P>> {
+        match self.framework {
+            Some(FrameworkChoice::Native) => self.native(),
+            Some(FrameworkChoice::OpenCL) => self.open_cl(),
+            None => {
+                match self.open_cl() {
+                    Ok(backend) => return Ok(backend),
+                    Err(e) => warn!("[PARENCHYMA] Skipping the Open CL framework: {}", e.chain()),
+                }
+
+                self.native()
+            }
+        }
+    }
+
+    fn native(&self) -> Result<Backend<P>> {
+        let framework = Native::<P>::new()?;
+        let selection = self.selection(&framework)?;
+        Backend::with(framework, selection)
+    }
+
+    fn open_cl(&self) -> Result<Backend<P>> {
+        let mut framework = OpenCL::<P>::new()?;
+
+        if let Some(ref options) = self.compiler_options {
+            framework.set_compiler_options(options.clone());
+        }
+
+        framework.set_program_cache(self.program_cache.clone());
+
+        let selection = self.selection(&framework)?;
+
+        if selection.is_empty() {
+            let message = "the Open CL framework doesn't provide any hardware";
+            return Err(Error::new(ErrorKind::InvalidSelection, message));
+        }
+
+        Backend::with(framework, selection)
+    }
+}
+
+impl<P> Default for BackendConfig<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P> fmt::Debug for BackendConfig<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BackendConfig")
+            .field("framework", &self.framework)
+            .field("platform", &self.platform)
+            .field("kind", &self.kind)
+            .field("device", &self.device.as_ref().map(|r| r.as_str()))
+            .field("compiler_options", &self.compiler_options)
+            .field("program_cache", &self.program_cache)
+            .field("num_threads", &self.num_threads)
+            .finish()
+    }
+}
+
+impl<P> PartialEq for BackendConfig<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.framework == other.framework
+            && self.platform == other.platform
+            && self.kind == other.kind
+            && self.device.as_ref().map(|r| r.as_str()) == other.device.as_ref().map(|r| r.as_str())
+            && self.compiler_options == other.compiler_options
+            && self.program_cache == other.program_cache
+            && self.num_threads == other.num_threads
+    }
+}
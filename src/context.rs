@@ -6,9 +6,12 @@
 //! [`Backend`]: ./struct.Backend.html
 
 use super::compute_device::ComputeDevice;
-use super::error::Result;
-use super::extension_package::ExtensionPackage;
+use super::error::{Error, ErrorKind, Result};
+use super::extension_package::{self, ExtensionPackage};
 use super::hardware::Hardware;
+use super::kernel::{self, CustomKernel, KernelArg};
+use super::profile::Profile;
+use super::stats::TransferStats;
 
 /// A trait implemented by all contexts.
 pub trait Context: 'static {
@@ -23,6 +26,65 @@ pub trait Context: 'static {
     /// Only one device can be the _active_ device - the device in which operations are executed -
     /// if used through the context.
     fn activate(&mut self, index: usize) -> Result;
+    /// Enables or disables data-parallel execution.
+    ///
+    /// When enabled, batched operations are split along the batch axis across all of the 
+    /// selected devices. Frameworks that only ever use a single device ignore this setting.
+    #[allow(unused_variables)]
+    fn set_data_parallel(&mut self, enabled: bool) -> Result {
+        Ok(())
+    }
+    /// Sets the number of threads used by the host to execute operations.
+    ///
+    /// The native framework executes all of its operations on host threads, while the Open CL
+    /// framework only uses them for the operations falling back to the native framework. The
+    /// other frameworks ignore this setting.
+    #[allow(unused_variables)]
+    fn set_num_threads(&mut self, n: usize) -> Result {
+        Ok(())
+    }
+    /// Returns the name of the framework running the operations the context's framework doesn't
+    /// implement, if it falls back to another one (see `extension_package::Fallback`).
+    fn fallback_framework(&self) -> Option<&'static str> {
+        None
+    }
+    /// Enables or disables the recording of the timings of the executed commands.
+    ///
+    /// Fails if the framework doesn't support profiling.
+    #[allow(unused_variables)]
+    fn set_profiling(&mut self, enabled: bool) -> Result {
+        Err(Error::new(ErrorKind::Other, "the framework doesn't support profiling"))
+    }
+    /// Returns the events recorded since profiling was enabled or last reset.
+    ///
+    /// Waits for the recorded commands to complete.
+    fn profile(&self) -> Result<Profile> {
+        Ok(Profile::default())
+    }
+    /// Discards the recorded events.
+    fn reset_profile(&self) { }
+    /// Returns the statistics of the transfers to and from the context's devices.
+    fn transfer_stats(&self) -> TransferStats {
+        TransferStats::default()
+    }
+    /// Resets the transfer statistics.
+    fn reset_transfer_stats(&self) { }
+    /// Blocks until the commands enqueued on the context's devices have completed.
+    ///
+    /// Frameworks executing synchronously have nothing to wait for.
+    fn synchronize(&self) -> Result {
+        Ok(())
+    }
+    /// Registers a custom kernel, replacing the kernel previously registered under its name.
+    #[allow(unused_variables)]
+    fn register_kernel(&mut self, kernel: &CustomKernel) -> Result {
+        extension_package::unsupported(kernel::NAME, "register_kernel")
+    }
+    /// Launches the custom kernel registered under the `name` on the `args`.
+    #[allow(unused_variables)]
+    fn launch_kernel(&self, name: &str, args: &mut [KernelArg]) -> Result {
+        extension_package::unsupported(kernel::NAME, "launch_kernel")
+    }
 }
 
 /// The non-object-safe part of the `Context`.
@@ -3,6 +3,8 @@
 use std::{error, fmt};
 use std::ops::Deref;
 
+use super::tensor::TensorShape;
+
 /// A specialized `Result` typedef.
 pub type Result<T = (), E = Error> = ::std::result::Result<T, E>;
 
@@ -34,14 +36,38 @@ pub enum ErrorKind {
     MemoryAllocationFailed,
     /// An error occurred while downcasting
     MemoryDowncasting,
+    /// The hardware selection can't be used to create a context (e.g., it's empty or it mixes 
+    /// hardware from different platforms).
+    InvalidSelection,
+    /// The backend configuration is malformed (e.g., an unknown framework or an invalid regular 
+    /// expression).
+    InvalidConfiguration,
+    /// A program failed to build. The inner error is a `ProgramBuildError` holding the build log 
+    /// of each device.
+    ProgramBuild,
+    /// A kernel failed to launch. The inner error is a `KernelLaunchError` naming the kernel.
+    KernelLaunch,
+    /// A plugin failed to load (e.g., the shared library is missing its declaration, or it was 
+    /// built against an incompatible version of Parenchyma).
+    Plugin,
+    /// The `package` doesn't implement the `operation` for the framework of the backend (see 
+    /// `Backend::capabilities`).
+    Unsupported {
+        /// The name of the package (e.g., `parenchyma/blas`).
+        package: &'static str,
+        /// The name of the operation (e.g., `asum`).
+        operation: &'static str,
+    },
 
     // MARK: - A set of tensor error categories
 
     /// Maximum number of backing memories has been reached (`BitMap` - type alias for `u64`).
     CapacityExceeded,
     /// The tensor shape is incompatible with the shape of some data.
+    ///
+    /// The inner error is usually a `ShapeMismatch`.
     IncompatibleShape,
-    /// Invalid reshaped tensor size.
+    /// Invalid reshaped tensor size. The inner error is a `ShapeMismatch`.
     InvalidReshapedTensorSize,
 
     /// Any error not part of this list.
@@ -67,6 +93,12 @@ impl ErrorKind {
             NoAvailableSynchronizationRouteFound => "no available memory synchronization route",
             MemoryAllocationFailed => "memory allocation failed",
             MemoryDowncasting => "something went wrong while downcasting",
+            InvalidSelection => "invalid hardware selection",
+            InvalidConfiguration => "invalid backend configuration",
+            ProgramBuild => "failed to build a program",
+            KernelLaunch => "failed to launch a kernel",
+            Plugin => "failed to load a plugin",
+            Unsupported { .. } => "unsupported operation",
             Other => "other error",
             _ => unreachable!(),
         }
@@ -92,6 +124,18 @@ impl Error {
     pub fn kind(&self) -> ErrorKind {
         self.kind
     }
+    /// Formats the error followed by each of its sources (e.g., for the logs).
+    pub(crate) fn chain(&self) -> String {
+        let mut message = self.to_string();
+        let mut source = error::Error::source(self);
+
+        while let Some(error) = source {
+            message.push_str(&format!(": {}", error));
+            source = error.source();
+        }
+
+        message
+    }
 }
 
 impl Error {
@@ -103,22 +147,19 @@ impl Error {
 
 impl fmt::Display for Error {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        if let ErrorKind::Unsupported { package, operation } = self.kind {
+            return write!(fmt, "`{}` isn't supported by the {} package", operation, package);
+        }
+
+        // the inner error is left to `source`, so that reporters walking the chain print it once
         write!(fmt, "{}", self.kind.as_str())
     }
 }
 
 impl error::Error for Error {
-    fn description(&self) -> &str {
-        if let Some(ref error) = self.inner {
-            error.description()
-        } else {
-            self.kind.as_str()
-        }
-    }
-
-    fn cause(&self) -> Option<&error::Error> {
+    fn source(&self) -> Option<&(error::Error + 'static)> {
         match self.inner {
-            Some(ref error) => error.cause(),
+            Some(ref error) => Some(error.deref()),
             _ => None,
         }
     }
@@ -131,6 +172,71 @@ impl From<ErrorKind> for Error {
     }
 }
 
+/// The build logs of a program that failed to build.
+#[derive(Debug)]
+pub struct ProgramBuildError {
+    /// The name of each device along with its build log.
+    pub logs: Vec<(String, String)>,
+    /// The error returned by the framework.
+    pub error: Error,
+}
+
+impl fmt::Display for ProgramBuildError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        for &(ref device, ref log) in self.logs.iter() {
+            writeln!(fmt, "\n[{}]\n{}", device, log.trim_right())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl error::Error for ProgramBuildError {
+    fn source(&self) -> Option<&(error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// A kernel that failed to launch.
+#[derive(Debug)]
+pub struct KernelLaunchError {
+    /// The name of the kernel.
+    pub kernel: String,
+    /// The error returned by the framework.
+    pub error: Error,
+}
+
+impl fmt::Display for KernelLaunchError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "the kernel `{}` failed to launch", self.kernel)
+    }
+}
+
+impl error::Error for KernelLaunchError {
+    fn source(&self) -> Option<&(error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// A tensor shape that doesn't match the expected one.
+#[derive(Debug)]
+pub struct ShapeMismatch {
+    /// The shape that was expected.
+    pub expected: TensorShape,
+    /// The shape that was provided.
+    pub actual: TensorShape,
+}
+
+impl fmt::Display for ShapeMismatch {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "expected {:?} ({} elements), got {:?} ({} elements)", 
+            self.expected.dimensions(), self.expected.capacity, 
+            self.actual.dimensions(), self.actual.capacity)
+    }
+}
+
+impl error::Error for ShapeMismatch { }
+
 #[cfg(test)]
 mod test {
     use super::{Error, ErrorKind};
@@ -13,6 +13,8 @@
 //! ## Extensions
 //!
 //! A library can be a binary, a source file, c code, a single kernel, etc., or a collective.
+//! Kernels compiled into a shared library can be loaded at runtime as a plugin (see the `plugin`
+//! module).
 //!
 //! A backend is a Rust struct like any other, therefore you probably would like to implement
 //! certain methods for the Backend. As the whole purpose of a Backend is to provide an
@@ -39,8 +41,11 @@
 //! of [Parenchyma-BLAS][parenchyma-blas] or its documentation. Let us now about your extension 
 //! on the Gitter chat, we are happy to feature your Parenchyma Extension on the README.
 
+use std::any::Any;
+use std::collections::BTreeSet;
+
 use super::context::Context;
-use super::error::Result;
+use super::error::{ErrorKind, Result};
 
 /// Represents a package dependency.
 pub trait Dependency<P>: ExtensionPackage {
@@ -56,6 +61,8 @@ impl<P> Dependency<P> for P where P: ExtensionPackage {
 
 /// Provides the generic functionality for a backend-specific implementation of a library.
 pub trait ExtensionPackage: 'static {
+    /// The extension of the package - usually a trait object implemented by each context the 
+    /// package supports (see `Upcast`).
     type Extension: ?Sized;
 
     /// The name of the package.
@@ -63,6 +70,115 @@ pub trait ExtensionPackage: 'static {
     /// This associated constant is primarily used for logging/debugging purposes. The naming 
     /// convention is as follows: "[organization]/[package-name]" (e.g., "parenchyma/nn").
     fn package_name(&self) -> &'static str;
+
+    /// Returns the operations the package implements for the framework named `framework` (see
+    /// `Framework::name`).
+    ///
+    /// The other operations return an `ErrorKind::Unsupported` error.
+    #[allow(unused_variables)]
+    fn capabilities(framework: &str) -> Capabilities where Self: Sized {
+        Capabilities::default()
+    }
+}
+
+/// A set of operations, each named along with the package providing it.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Capabilities {
+    operations: BTreeSet<(&'static str, &'static str)>,
+}
+
+impl Capabilities {
+    /// Creates a set of the `operations` of the `package`.
+    pub fn new(package: &'static str, operations: &[&'static str]) -> Capabilities {
+        Capabilities { operations: operations.iter().map(|&op| (package, op)).collect() }
+    }
+
+    /// Returns `true` if the `operation` of the `package` is in the set.
+    pub fn supports(&self, package: &str, operation: &str) -> bool {
+        self.operations.iter().any(|&(p, op)| p == package && op == operation)
+    }
+
+    /// Returns the names of the operations of the `package`.
+    pub fn operations<'a>(&'a self, package: &'a str) -> impl Iterator<Item=&'static str> + 'a {
+        self.operations.iter().filter(move |&&(p, _)| p == package).map(|&(_, op)| op)
+    }
+
+    /// Returns the union of the sets.
+    pub fn union(mut self, other: Capabilities) -> Capabilities {
+        self.operations.extend(other.operations);
+        self
+    }
+
+    /// Returns `true` if the set contains no operations.
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+}
+
+/// Converts a reference to a context into a reference to the extension of a package.
+///
+/// Implemented by the extension trait object of a package for each context implementing the 
+/// trait, which is the coercion a context performs when the backend dereferences it:
+///
+/// ```ignore
+/// impl<C> Upcast<C> for Extension where C: Extension + 'static {
+///     fn upcast(context: &C) -> &(Extension + 'static) {
+///         context
+///     }
+/// }
+/// ```
+pub trait Upcast<C> {
+    /// Converts the `context` into the extension.
+    fn upcast(context: &C) -> &Self;
+}
+
+impl<C> Upcast<C> for Any where C: Any {
+    fn upcast(context: &C) -> &(Any + 'static) {
+        context
+    }
+}
+
+/// Returns an `ErrorKind::Unsupported` error for the `operation` of the `package`.
+pub fn unsupported(package: &'static str, operation: &'static str) -> Result {
+    Err(ErrorKind::Unsupported { package, operation }.into())
+}
+
+/// Implemented by each context for the extensions `E` of the packages, to provide the extension
+/// of another context running the operations the context's framework doesn't implement (e.g., 
+/// the native context of an Open CL context).
+///
+/// Packages usually wrap it in a trait of their own returning their extension, which their 
+/// extension traits extend - a supertrait can't mention the extension's trait object itself.
+pub trait Fallback<E: ?Sized> {
+    /// Returns the extension running the `operation` of the `package` instead, or `None` if the 
+    /// operation is unsupported.
+    #[allow(unused_variables)]
+    fn fallback(&self, package: &'static str, operation: &'static str) -> Option<&E> {
+        None
+    }
+}
+
+/// Runs the `operation` of the `package` on the extension returned by `Fallback::fallback`, or 
+/// returns an `ErrorKind::Unsupported` error if there's none.
+///
+/// Used by the default implementations of the operations of the extension traits, usually 
+/// through a function of the package:
+///
+/// ```ignore
+/// fn fall_back<C, F>(context: &C, operation: &'static str, f: F) -> Result
+///     where C: ?Sized + Fallback, F: FnOnce(&(Extension + 'static)) -> Result {
+///
+///     extension_package::fall_back(context.fallback(operation), NAME, operation, f)
+/// }
+/// ```
+pub fn fall_back<E, F>(fallback: Option<&E>, package: &'static str, operation: &'static str, f: F)
+    -> Result 
+    where E: ?Sized, F: FnOnce(&E) -> Result {
+
+    match fallback {
+        Some(extension) => f(extension),
+        None => unsupported(package, operation),
+    }
 }
 
 /// Builds a package and provides the functionality for turning a library into backend-specific, 
@@ -80,7 +196,7 @@ pub trait ExtensionPackageCtor<TargetContext>: Sized
 }
 
 impl ExtensionPackage for () {
-    type Extension = ::std::any::Any;
+    type Extension = Any;
     /// The default package.
     fn package_name(&self) -> &'static str {
         return "parenchyma/default";
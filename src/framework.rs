@@ -34,6 +34,12 @@ pub trait Framework: 'static {
     /// note: this method will likely be replaced 
     /// with a [field](https://github.com/rust-lang/rfcs/pull/1546).
     fn hardware(&self) -> &[Hardware];
+    /// Returns the hardware used when a selection isn't provided (e.g., by `Backend::new`).
+    ///
+    /// Defaults to all of the available hardware.
+    fn default_selection(&self) -> Vec<Hardware> {
+        self.hardware().to_vec()
+    }
 }
 
 /// The non-object-safe part of the framework trait.
@@ -0,0 +1,99 @@
+use super::{Mock, MockDevice};
+use super::super::super::compute_device::ComputeDevice;
+use super::super::super::context::{Context, ContextCtor};
+use super::super::super::error::{Error, ErrorKind, Result};
+use super::super::super::extension_package::{ExtensionPackage, ExtensionPackageCtor, Fallback};
+use super::super::super::extension_package::Upcast;
+use super::super::super::hardware::Hardware;
+use super::super::super::stats::TransferStats;
+
+/// Defines a mock context.
+pub struct MockContext<P> {
+    /// The index of the _active_ device.
+    active: usize,
+    /// The simulated devices, one per selected hardware.
+    devices: Vec<MockDevice>,
+    extension_package: P,
+}
+
+impl<P> MockContext<P> {
+    /// Returns the active device.
+    pub fn device(&self) -> &MockDevice {
+        &self.devices[self.active]
+    }
+
+    /// Returns all of the devices associated with the context.
+    pub fn devices(&self) -> &[MockDevice] {
+        &self.devices
+    }
+
+    pub fn extension_package(&self) -> &P {
+        &self.extension_package
+    }
+}
+
+impl<Package> Context for MockContext<Package> 
+    where Package: ExtensionPackage, 
+          Package::Extension: Upcast<MockContext<Package>> {
+
+    type Package = Package;
+
+    fn active_codev(&self) -> &ComputeDevice {
+        &self.devices[self.active]
+    }
+
+    fn extension(&self) -> &<Package as ExtensionPackage>::Extension {
+        <Package::Extension as Upcast<Self>>::upcast(self)
+    }
+
+    fn activate(&mut self, index: usize) -> Result {
+        if index >= self.devices.len() {
+            return Err(Error::new(ErrorKind::Other, "device index out of range"));
+        }
+
+        self.active = index;
+
+        Ok(())
+    }
+
+    fn transfer_stats(&self) -> TransferStats {
+        let mut stats = TransferStats::default();
+
+        for device in self.devices.iter() {
+            stats.merge(&device.transfer_stats());
+        }
+
+        stats
+    }
+
+    fn reset_transfer_stats(&self) {
+        for device in self.devices.iter() {
+            device.state.stats.borrow_mut().clear();
+        }
+    }
+}
+
+impl<P, E: ?Sized> Fallback<E> for MockContext<P> { }
+
+impl<P> ContextCtor<P> for MockContext<P>
+    where P: 'static + ExtensionPackage + ExtensionPackageCtor<MockContext<()>>, 
+          P::Extension: Upcast<MockContext<P>> {
+            
+    type F = Mock<P>;
+
+    fn new(_: &Self::F, selection: &[Hardware]) -> Result<Self> {
+        if selection.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidSelection, "no hardware was selected"));
+        }
+
+        let devices = selection.iter().map(|h| MockDevice::new(h.id)).collect();
+        let mut unpackaged = MockContext { active: 0, devices, extension_package: () };
+        let package = P::package(&mut unpackaged)?;
+
+        Ok(MockContext {
+            active: unpackaged.active,
+            devices: unpackaged.devices,
+            extension_package: package,
+        })
+    }
+}
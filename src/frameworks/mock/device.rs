@@ -0,0 +1,143 @@
+use num::Zero;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use super::MockMemory;
+use super::super::super::compute_device::{Allocate, ComputeDevice, Initialize};
+use super::super::super::error::{Error, ErrorKind, Result};
+use super::super::super::memory::Memory;
+use super::super::super::stats::TransferStats;
+use super::super::super::tensor::{Distribution, Sample, TensorShape};
+
+/// The counters and injected failures of a device, shared with its memories.
+#[derive(Debug, Default)]
+pub(in super) struct MockState {
+    pub(in super) allocations: Cell<usize>,
+    pub(in super) transfers_in: Cell<usize>,
+    pub(in super) transfers_out: Cell<usize>,
+    pub(in super) peer_transfers: Cell<usize>,
+    pub(in super) fail_allocations: Cell<bool>,
+    pub(in super) fail_transfers: Cell<bool>,
+    pub(in super) transfer_in_route: Cell<bool>,
+    pub(in super) transfer_out_route: Cell<bool>,
+    pub(in super) retired: Cell<bool>,
+    pub(in super) stats: RefCell<TransferStats>,
+}
+
+/// A simulated device.
+///
+/// Clones refer to the same device (i.e., they share the counters and the injected failures, and 
+/// memory allocated through one of them is synchronized with all of them).
+#[derive(Clone, Debug)]
+pub struct MockDevice {
+    id: usize,
+    pub(in super) state: Rc<MockState>,
+}
+
+impl MockDevice {
+    /// Creates a new device with all routes available and no failures injected.
+    pub fn new(id: usize) -> MockDevice {
+        let state = MockState::default();
+        state.transfer_in_route.set(true);
+        state.transfer_out_route.set(true);
+
+        MockDevice { id, state: Rc::new(state) }
+    }
+
+    /// Returns the ID of the device.
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Returns `true` if `other` refers to the same device.
+    pub fn same(&self, other: &MockDevice) -> bool {
+        Rc::ptr_eq(&self.state, &other.state)
+    }
+
+    /// Returns the number of allocations made on the device.
+    pub fn allocations(&self) -> usize {
+        self.state.allocations.get()
+    }
+
+    /// Returns the number of transfers from the host to the device.
+    pub fn transfers_in(&self) -> usize {
+        self.state.transfers_in.get()
+    }
+
+    /// Returns the number of transfers from the device to the host.
+    pub fn transfers_out(&self) -> usize {
+        self.state.transfers_out.get()
+    }
+
+    /// Returns the number of transfers between the device and other mock devices, initiated by
+    /// the memory of this device.
+    pub fn peer_transfers(&self) -> usize {
+        self.state.peer_transfers.get()
+    }
+
+    /// Returns the statistics of the transfers to the device, and from the device to the host.
+    pub fn transfer_stats(&self) -> TransferStats {
+        self.state.stats.borrow().clone()
+    }
+
+    /// Resets all of the counters.
+    pub fn reset(&self) {
+        self.state.allocations.set(0);
+        self.state.transfers_in.set(0);
+        self.state.transfers_out.set(0);
+        self.state.peer_transfers.set(0);
+        self.state.stats.borrow_mut().clear();
+    }
+
+    /// Makes the allocations fail with `ErrorKind::MemoryAllocationFailed`.
+    pub fn fail_allocations(&self, fail: bool) {
+        self.state.fail_allocations.set(fail);
+    }
+
+    /// Makes the transfers fail with `ErrorKind::MemorySynchronizationFailed`.
+    pub fn fail_transfers(&self, fail: bool) {
+        self.state.fail_transfers.set(fail);
+    }
+
+    /// Retires the memory allocated on the device, as if the context owning it was dropped (see
+    /// `Memory::retired`).
+    pub fn retire(&self) {
+        self.state.retired.set(true);
+    }
+
+    /// Enables or disables the routes of the device's memory. A disabled route fails with
+    /// `ErrorKind::NoAvailableSynchronizationRouteFound`.
+    pub fn set_routes(&self, transfer_in: bool, transfer_out: bool) {
+        self.state.transfer_in_route.set(transfer_in);
+        self.state.transfer_out_route.set(transfer_out);
+    }
+}
+
+impl ComputeDevice for MockDevice { }
+
+impl<T> Allocate<T> for MockDevice where T: Clone + Zero + 'static {
+    fn allocate(&self, shape: &TensorShape) -> Result<Box<Memory<T>>> {
+        if self.state.fail_allocations.get() {
+            let message = format!("injected allocation failure on mock device {}", self.id);
+            return Err(Error::new(ErrorKind::MemoryAllocationFailed, message));
+        }
+
+        self.state.allocations.set(self.state.allocations.get() + 1);
+
+        Ok(Box::new(MockMemory { data: vec![T::zero(); shape.capacity()], device: self.clone() }))
+    }
+}
+
+impl<T> Initialize<T> for MockDevice where T: Sample {
+    fn initialize(&self, memory: &mut Memory<T>, distribution: &Distribution, seed: u64) 
+        -> Result {
+        let mock = memory.downcast_mut::<MockMemory<T>>()
+            .ok_or(ErrorKind::MemoryDowncasting)?;
+
+        for (i, element) in mock.data.iter_mut().enumerate() {
+            *element = distribution.sample(seed, i as u64);
+        }
+
+        Ok(())
+    }
+}
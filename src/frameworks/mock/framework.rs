@@ -0,0 +1,61 @@
+use std::marker::PhantomData;
+
+use super::MockContext;
+use super::super::super::error::Result;
+use super::super::super::framework::{Framework, FrameworkCtor};
+use super::super::super::hardware::{Hardware, HardwareKind};
+
+/// The mock framework.
+#[derive(Debug)]
+pub struct Mock<P = ()> {
+    hardware: Vec<Hardware>,
+    package: PhantomData<P>,
+}
+
+impl<P> Mock<P> {
+    /// The name of the framework.
+    pub const ID: &'static str = "mock";
+
+    /// The number of devices provided by `Mock::new`.
+    pub const DEVICES: usize = 2;
+
+    /// Initializes the framework with `n` simulated devices.
+    pub fn with_devices(n: usize) -> Self {
+        let hardware = (0..n).map(|id| Hardware {
+            id,
+            framework: Mock::<P>::ID,
+            platform: 0,
+            kind: HardwareKind::Accelerator,
+            name: format!("Mock Device #{}", id),
+            compute_units: 1,
+            global_memory: 0,
+            local_memory: 0,
+            max_work_group_size: 1,
+            version: String::from("mock"),
+            fp64: true,
+            fp16: false,
+            extensions: vec![],
+        })
+        .collect();
+
+        Mock { hardware, package: PhantomData }
+    }
+}
+
+impl<P> Framework for Mock<P> where P: 'static {
+    fn name(&self) -> &'static str {
+        return Mock::<P>::ID;
+    }
+
+    fn hardware(&self) -> &[Hardware] {
+        &self.hardware
+    }
+}
+
+impl<P> FrameworkCtor for Mock<P> where P: 'static {
+    type Context = MockContext<P>;
+
+    fn new() -> Result<Self> {
+        Ok(Mock::with_devices(Mock::<P>::DEVICES))
+    }
+}
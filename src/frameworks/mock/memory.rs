@@ -0,0 +1,146 @@
+use std::cell::RefCell;
+
+use super::MockDevice;
+use super::super::NativeMemory;
+use super::super::super::compute_device::ComputeDevice;
+use super::super::super::error::{Error, ErrorKind, Result};
+use super::super::super::memory::{Memory, TransferDirection};
+use super::super::super::stats::TransferStats;
+
+/// Memory of a simulated device - a buffer separate from the host's.
+pub struct MockMemory<T> {
+    pub(in super) data: Vec<T>,
+    pub(in super) device: MockDevice,
+}
+
+impl<T> MockMemory<T> {
+    /// Returns the data held by the device.
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Returns the device the memory was allocated on.
+    pub fn device(&self) -> &MockDevice {
+        &self.device
+    }
+
+    /// Fails if the route of the `dir`ection is disabled or transfer failures are injected.
+    fn check_route(&self, dir: &TransferDirection) -> Result {
+        let state = &self.device.state;
+
+        let available = match *dir {
+            TransferDirection::TransferIn => state.transfer_in_route.get(),
+            TransferDirection::TransferOut => state.transfer_out_route.get(),
+        };
+
+        if !available {
+            return Err(ErrorKind::NoAvailableSynchronizationRouteFound.into());
+        }
+
+        if state.fail_transfers.get() {
+            let message = format!("injected transfer failure on mock device {}", self.device.id());
+            return Err(Error::new(ErrorKind::MemorySynchronizationFailed, message));
+        }
+
+        Ok(())
+    }
+}
+
+impl<T> Memory<T> for MockMemory<T> where T: Clone + 'static {
+    fn synchronized(&self, compute_device: &ComputeDevice) -> bool {
+        compute_device.downcast_ref::<MockDevice>().map_or(false, |d| self.device.same(d))
+    }
+
+    fn location(&self) -> String {
+        format!("Mock Device #{}", self.device.id())
+    }
+
+    fn transfer_stats(&self) -> Option<&RefCell<TransferStats>> {
+        Some(&self.device.state.stats)
+    }
+
+    fn retired(&self) -> bool {
+        self.device.state.retired.get()
+    }
+
+    fn transfer(&mut self, dir: TransferDirection, m: &mut Memory<T>, _: &str) -> Result {
+        self.check_route(&dir)?;
+        let state = self.device.state.clone();
+
+        if m.is::<NativeMemory<T>>() {
+            let native = m.downcast_mut::<NativeMemory<T>>().unwrap();
+
+            match dir {
+                TransferDirection::TransferIn => {
+                    self.data.clear();
+                    self.data.extend(native.iter().cloned());
+                    state.transfers_in.set(state.transfers_in.get() + 1);
+                },
+                TransferDirection::TransferOut => {
+                    for (dst, src) in native.iter_mut().zip(self.data.iter()) {
+                        *dst = src.clone();
+                    }
+                    state.transfers_out.set(state.transfers_out.get() + 1);
+                },
+            }
+        } else if let Some(mock) = m.downcast_mut::<MockMemory<T>>() {
+            match dir {
+                TransferDirection::TransferIn => self.data.clone_from(&mock.data),
+                TransferDirection::TransferOut => mock.data.clone_from(&self.data),
+            }
+
+            state.peer_transfers.set(state.peer_transfers.get() + 1);
+        } else {
+            return Err(ErrorKind::NoAvailableSynchronizationRouteFound.into());
+        }
+
+        Ok(())
+    }
+
+    fn transfer_range(
+        &mut self,
+        dir: TransferDirection,
+        m: &mut Memory<T>,
+        offset: usize,
+        m_offset: usize,
+        length: usize,
+        _: &str) -> Result {
+
+        self.check_route(&dir)?;
+        let state = self.device.state.clone();
+        let own = offset..offset + length;
+        let other = m_offset..m_offset + length;
+
+        if let Some(native) = m.downcast_mut::<NativeMemory<T>>() {
+            let native = native.as_slice_memory_order_mut()
+                .expect("the array's data is not contiguous");
+
+            match dir {
+                TransferDirection::TransferIn => {
+                    self.data[own].clone_from_slice(&native[other]);
+                    state.transfers_in.set(state.transfers_in.get() + 1);
+                },
+                TransferDirection::TransferOut => {
+                    native[other].clone_from_slice(&self.data[own]);
+                    state.transfers_out.set(state.transfers_out.get() + 1);
+                },
+            }
+        } else if let Some(mock) = m.downcast_mut::<MockMemory<T>>() {
+            match dir {
+                TransferDirection::TransferIn => {
+                    self.data[own].clone_from_slice(&mock.data[other])
+                },
+                TransferDirection::TransferOut => {
+                    mock.data[other].clone_from_slice(&self.data[own])
+                },
+            }
+
+            state.peer_transfers.set(state.peer_transfers.get() + 1);
+        } else {
+            return Err(ErrorKind::NoAvailableSynchronizationRouteFound.into());
+        }
+
+        Ok(())
+    }
+}
+
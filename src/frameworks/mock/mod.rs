@@ -0,0 +1,19 @@
+//! A simulated framework for testing memory synchronization without real hardware.
+//!
+//! The devices of the mock framework keep their data in buffers of their own, separate from the
+//! host's, so that every synchronization performed by a `SharedTensor` goes through an actual
+//! transfer. Transfers and allocations are counted, and failures can be injected.
+//!
+//! Mock memory only knows how to transfer to and from native memory and other mock memory, while
+//! native memory knows nothing about mock memory. Host -> mock transfers are therefore routed 
+//! through `TransferIn` on the mock memory, and mock -> host transfers through `TransferOut`.
+
+pub use self::context::MockContext;
+pub use self::device::MockDevice;
+pub use self::framework::Mock;
+pub use self::memory::MockMemory;
+
+mod context;
+mod device;
+mod framework;
+mod memory;
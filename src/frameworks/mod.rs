@@ -1,7 +1,12 @@
 //! Exposes the specific framework implementations.
 
+pub use self::mock::{Mock, MockContext, MockDevice, MockMemory};
 pub use self::native::{HOST, Native, NativeContext, NativeDevice, NativeMemory};
-pub use self::open_cl::{OpenCL, OpenCLBuf, OpenCLContext, OpenCLDevice, OpenCLMemory};
+pub use self::open_cl::{
+    OpenCL, OpenCLAccess, OpenCLBuf, OpenCLContext, OpenCLDevice, OpenCLKernel, OpenCLKernels, 
+    OpenCLMemory, OpenCLProgramBuilder, OpenCLStream,
+};
 
+pub mod mock;
 mod native;
-mod open_cl;
\ No newline at end of file
+mod open_cl;
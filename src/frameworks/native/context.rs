@@ -1,19 +1,110 @@
+use num_cpus;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::collections::HashMap;
 use std::marker::PhantomData;
+use std::sync::Arc;
 
-use std::marker::Unsize;
 use super::Native;
 use super::super::super::compute_device::ComputeDevice;
 use super::super::super::context::{Context, ContextCtor};
-use super::super::super::error::Result;
-use super::super::super::extension_package::ExtensionPackage;
+use super::super::super::error::{Error, ErrorKind, Result};
+use super::super::super::extension_package::{ExtensionPackage, Fallback, Upcast};
 use super::super::super::hardware::Hardware;
+use super::super::super::kernel::{CustomKernel, KernelArg, NativeArgs, NativeKernel};
 
 /// Defines a Native context.
-pub struct NativeContext<P>(PhantomData<P>);
+///
+/// The context owns a thread pool on which the native implementations of the extension packages
+/// run their parallel operations. The pool is sized from the number of physical cores of the host
+/// CPU by default and can be resized through `Backend::set_num_threads`.
+pub struct NativeContext<P> {
+    /// The thread pool executing the operations.
+    pool: Arc<ThreadPool>,
+    /// The native implementations of the registered custom kernels, by name.
+    kernels: HashMap<String, Arc<NativeKernel>>,
+    package: PhantomData<P>,
+}
+
+impl<P> NativeContext<P> {
+    /// Returns the thread pool of the context.
+    pub fn pool(&self) -> &ThreadPool {
+        &self.pool
+    }
+
+    /// Returns the number of threads of the pool.
+    pub fn num_threads(&self) -> usize {
+        self.pool.current_num_threads()
+    }
+
+    /// Creates a context with a thread pool sized from the number of physical cores of the host.
+    pub(in frameworks) fn host() -> Result<Self> {
+        let pool = Arc::new(pool(num_cpus::get_physical().max(1))?);
+
+        Ok(NativeContext { pool, kernels: HashMap::new(), package: PhantomData })
+    }
+
+    /// Returns the context for the package `Q`, keeping the thread pool.
+    pub(in frameworks) fn with_package<Q>(self) -> NativeContext<Q> {
+        NativeContext { pool: self.pool, kernels: self.kernels, package: PhantomData }
+    }
+
+    /// Replaces the thread pool with a pool of `n` threads.
+    pub(in frameworks) fn resize_pool(&mut self, n: usize) -> Result {
+        if n == 0 {
+            return Err(Error::new(ErrorKind::Other, "a thread pool needs at least one thread"));
+        }
+
+        self.pool = Arc::new(pool(n)?);
+
+        Ok(())
+    }
+
+    /// Registers the native implementation of the custom `kernel`.
+    pub(in frameworks) fn register(&mut self, kernel: &CustomKernel) -> Result {
+        match kernel.native_kernel() {
+            Some(f) => {
+                self.kernels.insert(kernel.name().to_string(), f.clone());
+                Ok(())
+            },
+            _ => {
+                let message = format!(
+                    "the kernel `{}` has no native implementation", kernel.name());
+                Err(Error::new(ErrorKind::InvalidConfiguration, message))
+            }
+        }
+    }
+
+    /// Removes the native implementation of the custom kernel `name`, if it's registered.
+    pub(in frameworks) fn unregister(&mut self, name: &str) {
+        self.kernels.remove(name);
+    }
+
+    /// Runs the native implementation of the custom kernel `name` on the `args`.
+    pub(in frameworks) fn launch(&self, name: &str, args: &mut [KernelArg]) -> Result {
+        let f = self.kernels.get(name).ok_or_else(|| {
+            let message = format!("no kernel named `{}` is registered", name);
+            Error::new(ErrorKind::Other, message)
+        })?;
+
+        f(&mut NativeArgs::new(args, &self.pool)?)
+    }
+}
+
+/// The native framework implements each operation it supports.
+impl<P, E: ?Sized> Fallback<E> for NativeContext<P> { }
+
+/// Builds a thread pool of `n` threads.
+fn pool(n: usize) -> Result<ThreadPool> {
+    ThreadPoolBuilder::new()
+        .num_threads(n)
+        .thread_name(|i| format!("parenchyma-native-{}", i))
+        .build()
+        .map_err(|e| Error::new(ErrorKind::Framework(Native::<()>::ID), e))
+}
 
 impl<Package> Context for NativeContext<Package> 
     where Package: ExtensionPackage, 
-          NativeContext<Package>: Unsize<Package::Extension> {
+          Package::Extension: Upcast<NativeContext<Package>> {
 
     type Package = Package;
 
@@ -26,17 +117,33 @@ impl<Package> Context for NativeContext<Package>
     }
 
     fn extension(&self) -> &<Package as ExtensionPackage>::Extension {
-        self
+        <Package::Extension as Upcast<Self>>::upcast(self)
+    }
+
+    fn set_num_threads(&mut self, n: usize) -> Result {
+        self.resize_pool(n)
+    }
+
+    fn register_kernel(&mut self, kernel: &CustomKernel) -> Result {
+        self.register(kernel)
+    }
+
+    fn launch_kernel(&self, name: &str, args: &mut [KernelArg]) -> Result {
+        self.launch(name, args)
     }
 }
 
 impl<P> ContextCtor<P> for NativeContext<P>
     where P: 'static + ExtensionPackage, 
-          NativeContext<P>: Unsize<P::Extension> {
+          P::Extension: Upcast<NativeContext<P>> {
             
     type F = Native<P>;
 
-    fn new(_: &Self::F, _: &[Hardware]) -> Result<Self> {
-        Ok(NativeContext(PhantomData))
+    fn new(_: &Self::F, selection: &[Hardware]) -> Result<Self> {
+        let n = selection.first().map(|h| h.compute_units).unwrap_or(1).max(1);
+
+        let pool = Arc::new(pool(n)?);
+
+        Ok(NativeContext { pool, kernels: HashMap::new(), package: PhantomData })
     }
-}
\ No newline at end of file
+}
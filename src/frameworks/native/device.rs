@@ -1,10 +1,10 @@
 use ndarray::Array;
 
 use super::NativeMemory;
-use super::super::super::compute_device::{Allocate, ComputeDevice};
-use super::super::super::error::Result;
+use super::super::super::compute_device::{Allocate, ComputeDevice, Initialize};
+use super::super::super::error::{ErrorKind, Result};
 use super::super::super::memory::Memory;
-use super::super::super::tensor::TensorShape;
+use super::super::super::tensor::{Distribution, Sample, TensorShape};
 
 /// The native device.
 #[derive(Debug)]
@@ -25,4 +25,18 @@ impl<T: 'static> Allocate<T> for NativeDevice {
 
         return Ok(Box::new(memory));
     }
-}
\ No newline at end of file
+}
+
+impl<T> Initialize<T> for NativeDevice where T: Sample {
+    fn initialize(&self, memory: &mut Memory<T>, distribution: &Distribution, seed: u64) 
+        -> Result {
+        let native = memory.downcast_mut::<NativeMemory<T>>()
+            .ok_or(ErrorKind::MemoryDowncasting)?;
+
+        for (i, element) in native.0.iter_mut().enumerate() {
+            *element = distribution.sample(seed, i as u64);
+        }
+
+        Ok(())
+    }
+}
@@ -3,6 +3,7 @@ use super::super::super::error::Result;
 use super::super::super::framework::{Framework, FrameworkCtor};
 use super::super::super::hardware::{Hardware, HardwareKind};
 
+use num_cpus;
 use std::marker::PhantomData;
 
 /// The native framework
@@ -13,7 +14,8 @@ pub struct Native<P> {
 }
 
 impl<P> Native<P> {
-    const ID: &'static str = "native/host";
+    /// The name of the framework.
+    pub const ID: &'static str = "native/host";
 }
 
 impl<P> Framework for Native<P> where P: 'static {
@@ -34,11 +36,48 @@ impl<P> FrameworkCtor for Native<P> where P: 'static {
             hardware: [Hardware {
                 id: 0usize,
                 framework: Native::<P>::ID,
+                platform: 0,
                 kind: HardwareKind::CPU,
                 name: String::from("Host CPU"),
-                compute_units: 1,
+                compute_units: num_cpus::get_physical(),
+                global_memory: 0,
+                local_memory: 0,
+                max_work_group_size: 1,
+                version: format!("parenchyma {}", env!("CARGO_PKG_VERSION")),
+                fp64: true,
+                fp16: false,
+                extensions: simd_features(),
             }],
             package: PhantomData,
         })
     }
-}
\ No newline at end of file
+}
+
+/// Returns the SIMD instruction sets supported by the host CPU, detected at runtime.
+fn simd_features() -> Vec<String> {
+    #[allow(unused_mut)]
+    let mut features = vec![];
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))] {
+        let detected = [
+            ("sse2", is_x86_feature_detected!("sse2")),
+            ("sse4.1", is_x86_feature_detected!("sse4.1")),
+            ("avx", is_x86_feature_detected!("avx")),
+            ("avx2", is_x86_feature_detected!("avx2")),
+            ("fma", is_x86_feature_detected!("fma")),
+            ("avx512f", is_x86_feature_detected!("avx512f")),
+        ];
+
+        for &(name, supported) in detected.iter() {
+            if supported {
+                features.push(String::from(name));
+            }
+        }
+    }
+
+    // NEON is mandatory on AArch64
+    #[cfg(target_arch = "aarch64")]
+    features.push(String::from("neon"));
+
+    features
+}
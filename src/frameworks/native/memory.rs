@@ -17,6 +17,10 @@ impl<T: 'static> Memory<T> for NativeMemory<T> {
     fn synchronized(&self, compute_device: &ComputeDevice) -> bool {
         compute_device.is::<NativeDevice>()
     }
+
+    fn location(&self) -> String {
+        String::from("host")
+    }
 }
 
 impl<T> Deref for NativeMemory<T> {
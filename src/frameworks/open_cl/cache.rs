@@ -0,0 +1,229 @@
+use ocl;
+use ocl::enums::{DeviceInfo, ProgramInfo, ProgramInfoResult};
+use std::ffi::CString;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::process;
+
+/// Identifies the files written by the cache (and the version of their layout).
+const MAGIC: &'static [u8; 8] = b"PRNCHYM1";
+
+/// The length of the header preceding the binary: the magic bytes, the length of the binary and
+/// its checksum.
+const HEADER_LEN: usize = 24;
+
+/// Stores the binaries of the programs built from source, one file per program and device.
+///
+/// An entry is keyed by a hash of the source strings, the compiler options, the device and the
+/// version of its driver, so updating any of them misses the cache rather than loading a stale
+/// binary. Entries that are corrupt or rejected by the driver are removed, and the program is
+/// built from source instead.
+#[derive(Clone, Debug)]
+pub(in frameworks::open_cl) struct ProgramCache {
+    directory: PathBuf,
+}
+
+impl ProgramCache {
+    pub fn new(directory: PathBuf) -> ProgramCache {
+        ProgramCache { directory }
+    }
+
+    /// Returns the key of the program built from the `src_strings` with the `options` for the
+    /// `device`.
+    pub fn key(src_strings: &[CString], device: &ocl::Device, options: &str) -> String {
+        let mut hash = Fnv::new();
+
+        for src in src_strings {
+            hash.write(src.as_bytes_with_nul());
+        }
+
+        for info in &[DeviceInfo::Vendor, DeviceInfo::Version, DeviceInfo::DriverVersion] {
+            hash.write(device.info(info.clone()).to_string().as_bytes());
+            hash.write(&[0]);
+        }
+
+        hash.write(device.name().as_bytes());
+        hash.write(&[0]);
+        hash.write(options.as_bytes());
+
+        format!("{:016x}", hash.finish())
+    }
+
+    /// Creates and builds a program from the cached binaries of the `devices`.
+    ///
+    /// Returns `None` unless every device has a valid entry.
+    pub fn load(
+        &self,
+        context: &ocl::Context,
+        devices: &[ocl::Device],
+        keys: &[String],
+        options: &CString) -> Option<ocl::core::Program> {
+
+        let mut binaries = vec![];
+
+        for key in keys {
+            binaries.push(self.read(key)?);
+        }
+
+        let binaries: Vec<&[u8]> = binaries.iter().map(|b| &b[..]).collect();
+
+        let program = ocl::core::create_program_with_binary(context.core(), devices, &binaries)
+            .and_then(|program| {
+                ocl::core::build_program(&program, Some(devices), options, None, None)
+                    .map(|_| program)
+            });
+
+        match program {
+            Ok(program) => {
+                debug!("[PARENCHYMA] Loaded a program from the cache ({})", keys.join(", "));
+                Some(program)
+            },
+            Err(e) => {
+                warn!("[PARENCHYMA] Discarding the cached binaries of a program: {}", e);
+
+                for key in keys {
+                    let _ = fs::remove_file(self.path(key));
+                }
+
+                None
+            }
+        }
+    }
+
+    /// Stores the binaries of the `program`, built for the `devices`.
+    ///
+    /// Failures are logged, but otherwise ignored - the program is simply rebuilt next time.
+    pub fn store(&self, program: &ocl::core::Program, devices: &[ocl::Device], keys: &[String]) {
+        // the binaries are listed in the order of the program's devices
+        let program_devices = match ocl::core::get_program_info(program, ProgramInfo::Devices) {
+            ProgramInfoResult::Devices(devices) => devices,
+            other => {
+                warn!("[PARENCHYMA] Failed to list the devices of a program: {}", other);
+                return;
+            }
+        };
+
+        let binaries = match ocl::core::get_program_info(program, ProgramInfo::Binaries) {
+            ProgramInfoResult::Binaries(binaries) => binaries,
+            other => {
+                warn!("[PARENCHYMA] Failed to read the binaries of a program: {}", other);
+                return;
+            }
+        };
+
+        if let Err(e) = fs::create_dir_all(&self.directory) {
+            warn!("[PARENCHYMA] Failed to create the program cache directory: {}", e);
+            return;
+        }
+
+        for (device, key) in devices.iter().zip(keys) {
+            let binary = program_devices.iter()
+                .position(|id| id == device.as_core())
+                .and_then(|i| binaries.get(i))
+                .filter(|binary| !binary.is_empty());
+
+            if let Some(binary) = binary {
+                if let Err(e) = self.write(key, binary) {
+                    warn!("[PARENCHYMA] Failed to cache the binary of a program: {}", e);
+                }
+            }
+        }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.directory.join(format!("{}.bin", key))
+    }
+
+    /// Reads the binary stored under the `key`, removing the entry if it's corrupt.
+    fn read(&self, key: &str) -> Option<Vec<u8>> {
+        let path = self.path(key);
+        let mut data = vec![];
+        File::open(&path).and_then(|mut file| file.read_to_end(&mut data)).ok()?;
+
+        match unpack(&data) {
+            Some(binary) => Some(binary.to_vec()),
+            _ => {
+                warn!("[PARENCHYMA] Removing the corrupt program cache entry {}", path.display());
+                let _ = fs::remove_file(&path);
+                None
+            }
+        }
+    }
+
+    /// Writes the `binary` under the `key`.
+    ///
+    /// The entry is written to a temporary file first, so that concurrent processes never read a
+    /// partially written entry.
+    fn write(&self, key: &str, binary: &[u8]) -> ::std::io::Result<()> {
+        let temporary = self.directory.join(format!("{}.{}.tmp", key, process::id()));
+
+        {
+            let mut file = File::create(&temporary)?;
+            file.write_all(MAGIC)?;
+            file.write_all(&to_bytes(binary.len() as u64))?;
+            file.write_all(&to_bytes(checksum(binary)))?;
+            file.write_all(binary)?;
+        }
+
+        fs::rename(&temporary, self.path(key))
+    }
+}
+
+/// Returns the binary of an entry, or `None` if the entry is corrupt.
+fn unpack(data: &[u8]) -> Option<&[u8]> {
+    if data.len() < HEADER_LEN || &data[..8] != &MAGIC[..] {
+        return None;
+    }
+
+    let len = from_bytes(&data[8..16]) as usize;
+    let binary = &data[HEADER_LEN..];
+
+    if binary.len() != len || checksum(binary) != from_bytes(&data[16..24]) {
+        return None;
+    }
+
+    Some(binary)
+}
+
+fn checksum(binary: &[u8]) -> u64 {
+    let mut hash = Fnv::new();
+    hash.write(binary);
+    hash.finish()
+}
+
+fn to_bytes(n: u64) -> [u8; 8] {
+    let mut bytes = [0; 8];
+
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = (n >> (8 * i)) as u8;
+    }
+
+    bytes
+}
+
+fn from_bytes(bytes: &[u8]) -> u64 {
+    bytes.iter().enumerate().fold(0, |n, (i, &byte)| n | (byte as u64) << (8 * i))
+}
+
+/// The 64-bit FNV-1a hash.
+///
+/// Unlike the hasher of the standard library, its output is stable across Rust releases, which is
+/// required for keys persisted to disk.
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Fnv {
+        Fnv(0xcbf29ce484222325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x100000001b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
@@ -1,12 +1,24 @@
 use ocl;
+use ocl::enums::ProgramBuildInfo;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::ffi::CString;
-use std::marker::Unsize;
-use super::{OpenCL, OpenCLDevice};
-use super::super::super::compute_device::ComputeDevice;
+use std::rc::Rc;
+use super::{OpenCL, OpenCLAccess, OpenCLDevice, OpenCLKernels, OpenCLMemory, OpenCLProgramBuilder};
+use super::cache::ProgramCache;
+use super::profiler::Profiler;
+use super::stream::Streams;
+use super::super::{Native, NativeContext};
+use super::super::super::compute_device::{self, Allocate, ComputeDevice};
 use super::super::super::context::{Context, ContextCtor};
-use super::super::super::error::{Error, ErrorKind, Result};
-use super::super::super::extension_package::{ExtensionPackage, ExtensionPackageCtor};
+use super::super::super::error::{Error, ErrorKind, ProgramBuildError, Result};
+use super::super::super::extension_package::{ExtensionPackage, ExtensionPackageCtor, Fallback};
+use super::super::super::extension_package::Upcast;
 use super::super::super::hardware::Hardware;
+use super::super::super::kernel::{CustomKernel, KernelArg, Scalar};
+use super::super::super::profile::Profile;
+use super::super::super::stats::TransferStats;
+use super::super::super::tensor::{self, SharedTensor};
 
 /// Defines a Open CL context.
 ///
@@ -19,7 +31,8 @@ use super::super::super::hardware::Hardware;
 /// * a single context for a single device
 /// * a context for each device
 ///
-/// note: multi-platform contexts are not supported in OpenCL.
+/// note: multi-platform contexts are not supported in OpenCL - constructing a context from a 
+/// selection that mixes platforms fails with `ErrorKind::InvalidSelection`.
 ///
 /// ## Programs
 ///
@@ -27,6 +40,18 @@ use super::super::super::hardware::Hardware;
 /// individually to avoid possible name clashes due to using packages from multiple package 
 /// authors.
 ///
+/// ## Data Parallelism
+///
+/// Only the active device executes operations by default. If data-parallel execution is enabled,
+/// operations implemented through [`batched`](#method.batched) are split along the batch axis
+/// across all of the selected devices, which can also be several CPU devices or sub-devices.
+///
+/// ## Fallback
+///
+/// Operations a package doesn't implement for Open CL, but implements natively, automatically run
+/// on the host instead (see `extension_package::Fallback`). Auto-sync moves the operands to the 
+/// host and back, so the fallback is transparent - but slow, which is why it's logged.
+///
 /// [buffer]: ./frameworks/opencl/struct.Memory.html
 /// [event]: ./frameworks/opencl/struct.Event.html
 pub struct OpenCLContext<P> {
@@ -38,6 +63,26 @@ pub struct OpenCLContext<P> {
     selected_devices: Vec<OpenCLDevice>,
     /// The `Device`s' corresponding `Hardware`.
     selected_hardware: Vec<Hardware>,
+    /// Whether or not batched operations are split across all of the selected devices.
+    data_parallel: bool,
+    /// The options passed to the compiler when building programs.
+    compiler_options: String,
+    /// The cache of the program binaries, if enabled.
+    program_cache: Option<ProgramCache>,
+    /// The profiler shared by the selected devices.
+    profiler: Rc<Profiler>,
+    /// The transfer statistics shared by the selected devices.
+    stats: Rc<RefCell<TransferStats>>,
+    /// Referred to weakly by the selected devices, so that the memory allocated on them is retired
+    /// along with the context (see `Memory::retired`).
+    #[allow(dead_code)]
+    alive: Rc<()>,
+    /// The native context running the operations that aren't implemented for Open CL.
+    fallback: NativeContext<P>,
+    /// The operations that already fell back to the native context, as `(package, operation)`.
+    fallen_back: RefCell<HashSet<(&'static str, &'static str)>>,
+    /// The registered custom kernels with an Open CL source, by name.
+    kernels: HashMap<String, OpenCLKernels>,
     // todo document this:
     // package is stored here because
     // a) the program depends on the selected devices
@@ -51,27 +96,124 @@ impl<P> OpenCLContext<P> {
         &self.selected_devices[self.active]
     }
 
+    /// Returns all of the devices associated with the context.
+    pub fn devices(&self) -> &[OpenCLDevice] {
+        &self.selected_devices
+    }
+
+    /// Returns the hardware corresponding to each of the devices.
+    pub fn hardware(&self) -> &[Hardware] {
+        &self.selected_hardware
+    }
+
     pub fn extension_package(&self) -> &P {
         &self.extension_package
     }
+
+    /// Executes a batched operation, possibly split across all of the selected devices.
+    ///
+    /// The `inputs` and the `output` must share the same batch size (first dimension). If 
+    /// data-parallel execution is disabled, or the batch can't be split, `op` is simply called 
+    /// with the active device and the provided tensors. Otherwise, the tensors are split into one
+    /// share per device and `op` is called once per non-empty share - it's expected to only 
+    /// enqueue work on the device's queue, so the devices compute concurrently. The shares of 
+    /// the output are then gathered into `output` on the active device. The shares are copied 
+    /// between the devices without going through the host.
+    ///
+    /// The output is only split if it's initialized (e.g., a gemm accumulating into it) - the 
+    /// shares of an uninitialized output are left for `op` to allocate, so `op` should write 
+    /// them with `tensor::mut_reference_unsynched` unless it reads them.
+    ///
+    /// Operands that aren't batched (e.g., scalars or weights) should be captured by `op` - they
+    /// are synchronized with each device as needed.
+    pub fn batched<T, F>(
+        &self, 
+        inputs: &[&SharedTensor<T>], 
+        output: &mut SharedTensor<T>, 
+        mut op: F) -> Result
+        where T: Copy + 'static,
+              ComputeDevice: Allocate<T>,
+              F: FnMut(&OpenCLDevice, &[&SharedTensor<T>], &mut SharedTensor<T>) -> Result {
+
+        let batch_size = output.shape().dimensions().get(0).cloned().unwrap_or(0);
+
+        let splittable = 
+            self.data_parallel && 
+            self.selected_devices.len() > 1 && 
+            batch_size > 1 && 
+            inputs.iter().all(|x| x.shape().dimensions().get(0) == Some(&batch_size));
+
+        if !splittable {
+            return op(self.device(), inputs, output);
+        }
+
+        let sizes = compute_device::partition(batch_size, self.selected_devices.len());
+        let devices: Vec<&ComputeDevice> = self.selected_devices.iter()
+            .map(|device| device as &ComputeDevice)
+            .collect();
+
+        let split_inputs = inputs.iter()
+            .map(|x| x.split_batch(&sizes, &devices))
+            .collect::<Result<Vec<_>>>()?;
+
+        // the shares of an uninitialized output are only allocated by `op`
+        let mut split_output = if output.is_initialized() {
+            output.split_batch(&sizes, &devices)?
+        } else {
+            sizes.iter().map(|&n| {
+                let mut dimensions = output.shape().dimensions().to_vec();
+                dimensions[0] = n;
+                unsafe { SharedTensor::uninitialized(dimensions) }
+            })
+            .collect()
+        };
+
+        for (i, device) in self.selected_devices.iter().enumerate() {
+            if sizes[i] == 0 {
+                continue;
+            }
+
+            let share: Vec<_> = split_inputs.iter().map(|chunks| &chunks[i]).collect();
+            op(device, &share, &mut split_output[i])?;
+        }
+
+        output.join_batch(&split_output, self.device())
+    }
     
-    /// Builds and returns a program.
-    pub fn program(&self, src_strings: Vec<CString>) -> Result<ocl::Program> {
-        let cmplr_opts = CString::new("").unwrap();
-        let device_ids: Vec<_> = self.selected_devices.iter().map(|d| d.device.clone()).collect();
-
-        Ok(ocl::Program::new(
-            self.context.core(), 
-            src_strings, 
-            Some(&device_ids), 
-            cmplr_opts
-        )?)
+    /// Returns the options passed to the compiler when building programs.
+    pub fn compiler_options(&self) -> &str {
+        &self.compiler_options
+    }
+
+    /// Builds and returns a program using the compiler options of the framework.
+    ///
+    /// The binaries are loaded from the program cache instead, if it's enabled and holds them
+    /// (see `OpenCL::set_program_cache`). A failed build returns an `ErrorKind::ProgramBuild`
+    /// error holding the build log of each device.
+    pub fn program(&self, src_strings: Vec<CString>) -> Result<ocl::core::Program> {
+        self.program_builder().srcs(src_strings).build()
+    }
+
+    /// Returns a builder of programs taking preprocessor defines and additional compiler options.
+    pub fn program_builder(&self) -> OpenCLProgramBuilder<P> {
+        OpenCLProgramBuilder::new(self)
+    }
+
+    /// Builds a program for the `device_ids` with the `options` (which replace the compiler 
+    /// options of the framework).
+    pub(in frameworks::open_cl) fn build_program(
+        &self, 
+        src_strings: Vec<CString>, 
+        device_ids: &[ocl::Device],
+        options: &str) -> Result<ocl::core::Program> {
+
+        build(&self.context, src_strings, device_ids, options, self.program_cache.as_ref())
     }
 }
 
 impl<Package> Context for OpenCLContext<Package> 
     where Package: ExtensionPackage, 
-          OpenCLContext<Package>: Unsize<Package::Extension> {
+          Package::Extension: Upcast<OpenCLContext<Package>> {
 
     type Package = Package;
 
@@ -80,7 +222,7 @@ impl<Package> Context for OpenCLContext<Package>
     }
 
     fn extension(&self) -> &<Package as ExtensionPackage>::Extension {
-        self
+        <Package::Extension as Upcast<Self>>::upcast(self)
     }
 
     fn activate(&mut self, index: usize) -> Result {
@@ -92,30 +234,193 @@ impl<Package> Context for OpenCLContext<Package>
 
         Ok(())
     }
+
+    fn set_data_parallel(&mut self, enabled: bool) -> Result {
+        self.data_parallel = enabled;
+
+        Ok(())
+    }
+
+    /// Sets the number of threads used by the operations falling back to the native framework.
+    fn set_num_threads(&mut self, n: usize) -> Result {
+        self.fallback.resize_pool(n)
+    }
+
+    fn fallback_framework(&self) -> Option<&'static str> {
+        Some(Native::<()>::ID)
+    }
+
+    fn set_profiling(&mut self, enabled: bool) -> Result {
+        self.profiler.set_enabled(enabled);
+
+        Ok(())
+    }
+
+    fn profile(&self) -> Result<Profile> {
+        Ok(Profile { events: self.profiler.events()? })
+    }
+
+    fn reset_profile(&self) {
+        self.profiler.reset()
+    }
+
+    fn transfer_stats(&self) -> TransferStats {
+        self.stats.borrow().clone()
+    }
+
+    fn reset_transfer_stats(&self) {
+        self.stats.borrow_mut().clear()
+    }
+
+    /// Builds the Open CL source of the `kernel` for the selected devices, and registers its native
+    /// implementation with the fallback context.
+    ///
+    /// A kernel without an Open CL source runs on the host.
+    fn register_kernel(&mut self, kernel: &CustomKernel) -> Result {
+        let name = kernel.name();
+
+        let source = match kernel.opencl_source() {
+            Some(source) => source,
+            _ => {
+                warn!("[PARENCHYMA] The kernel `{}` has no Open CL source - it runs on the host",
+                    name);
+                self.kernels.remove(name);
+                return self.fallback.register(kernel);
+            }
+        };
+
+        let program = self.program_builder().src(source).build()?;
+        let kernels = OpenCLKernels::new(&program, &self.selected_devices, &[name])?;
+        self.kernels.insert(name.to_string(), kernels);
+
+        match kernel.native_kernel() {
+            Some(_) => self.fallback.register(kernel),
+            _ => Ok(self.fallback.unregister(name)),
+        }
+    }
+
+    /// Launches the custom kernel `name` on the active device, or on the host if it has no Open
+    /// CL source.
+    fn launch_kernel(&self, name: &str, args: &mut [KernelArg]) -> Result {
+        let kernels = match self.kernels.get(name) {
+            Some(kernels) => kernels,
+            _ => return self.fallback.launch(name, args),
+        };
+
+        let device = self.device();
+        let n = KernelArg::work_size(args);
+        let mut kernel = kernels.get(device, name)?;
+        let mut accesses = vec![];
+
+        for arg in args.iter_mut() {
+            kernel = match *arg {
+                KernelArg::Scalar(Scalar::F32(value)) => kernel.arg_scl(value),
+                KernelArg::Scalar(Scalar::F64(value)) => kernel.arg_scl(value),
+                KernelArg::Scalar(Scalar::I32(value)) => kernel.arg_scl(value),
+                KernelArg::Scalar(Scalar::U32(value)) => kernel.arg_scl(value),
+                KernelArg::Tensor(x) => {
+                    let memory: &OpenCLMemory<f32> = tensor::reference(x, device)?;
+                    accesses.push(OpenCLAccess::read(memory));
+                    kernel.arg_buf(memory)
+                },
+                KernelArg::TensorMut(ref mut x) => {
+                    let memory: &mut OpenCLMemory<f32> = tensor::mut_reference(&mut **x, device)?;
+                    accesses.push(OpenCLAccess::write(memory));
+                    kernel.arg_buf(&*memory)
+                },
+            };
+        }
+
+        unsafe { device.enqueue(name, &kernel.gws([n]), &accesses) }
+    }
+
+    /// Blocks until the commands enqueued on each stream of each device have completed.
+    fn synchronize(&self) -> Result {
+        for device in &self.selected_devices {
+            device.synchronize()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Falls back to the native context for the operations the package implements natively.
+impl<P, E: ?Sized> Fallback<E> for OpenCLContext<P> 
+    where P: ExtensionPackage, 
+          E: Upcast<NativeContext<P>> {
+
+    fn fallback(&self, package: &'static str, operation: &'static str) -> Option<&E> {
+        if !P::capabilities(Native::<()>::ID).supports(package, operation) {
+            return None;
+        }
+
+        if self.fallen_back.borrow_mut().insert((package, operation)) {
+            warn!("[PARENCHYMA] `{}` of the {} package isn't implemented for Open CL - falling \
+                back to the native framework", operation, package);
+        }
+
+        Some(E::upcast(&self.fallback))
+    }
 }
 
 impl<P> ContextCtor<P> for OpenCLContext<P>
     where P: 'static + ExtensionPackage + ExtensionPackageCtor<OpenCLContext<()>>, 
-          OpenCLContext<P>: Unsize<P::Extension> {
+          P::Extension: Upcast<OpenCLContext<P>> {
             
     type F = OpenCL<P>;
 
     fn new(framework: &Self::F, selection: &[Hardware]) -> Result<Self> {
 
-        let props = ocl::builders::ContextProperties::new().platform(framework.implementation);
-        let s = ocl::builders::DeviceSpecifier::Indices(selection.iter().map(|h| h.id).collect());
+        let platform = match selection.first() {
+            Some(hardware) => hardware.platform,
+            _ => return Err(Error::new(ErrorKind::InvalidSelection, "no hardware was selected")),
+        };
+
+        if let Some(h) = selection.iter().find(|h| h.platform != platform) {
+            let message = format!(
+                "`{}` belongs to the Open CL platform {}, but `{}` belongs to the platform {} - a \
+                context can only hold the devices of a single platform", 
+                h.name, h.platform, selection[0].name, platform);
+            return Err(Error::new(ErrorKind::InvalidSelection, message));
+        }
+
+        let implementation = framework.implementations.get(platform).cloned()
+            .ok_or_else(|| Error::new(ErrorKind::InvalidSelection, "unknown Open CL platform"))?;
+
+        let device_ids: Vec<_> = selection.iter().map(|h| framework.device(h)).collect();
+
+        let props = ocl::builders::ContextProperties::new().platform(implementation);
+        let s = ocl::builders::DeviceSpecifier::List(device_ids.clone());
         let ctx = ocl::Context::new(Some(props), Some(s), None, None)?;
 
+        let program_cache = framework.program_cache.clone().map(ProgramCache::new);
+
+        let builtins = build(
+            &ctx, 
+            vec![CString::new(include_str!("source/random.cl")).unwrap()], 
+            &device_ids, 
+            &framework.compiler_options,
+            program_cache.as_ref()
+        )?;
+
+        let profiler = Rc::new(Profiler::default());
+        let stats = Rc::new(RefCell::new(TransferStats::default()));
+        let alive = Rc::new(());
         let mut devices = vec![];
 
-        for hardware in selection.iter() {
-            let d = ocl::Device::by_idx_wrap(framework.implementation, hardware.id);
+        for (index, &d) in device_ids.iter().enumerate() {
             let queue = ocl::Queue::new(&ctx, d, Some(ocl::flags::QUEUE_PROFILING_ENABLE))?;
 
             devices.push(OpenCLDevice {
                 device: d,
                 context: ctx.clone(),
-                queue,
+                queue: queue.clone(),
+                streams: Rc::new(Streams::new(queue)),
+                builtins: builtins.clone(),
+                index,
+                profiler: profiler.clone(),
+                stats: stats.clone(),
+                alive: Rc::downgrade(&alive),
             });
         }
 
@@ -124,6 +429,15 @@ impl<P> ContextCtor<P> for OpenCLContext<P>
             active: 0, 
             selected_devices: devices, 
             selected_hardware: selection.to_vec(),
+            data_parallel: false,
+            compiler_options: framework.compiler_options.clone(),
+            program_cache,
+            profiler,
+            stats,
+            alive,
+            fallback: NativeContext::host()?,
+            fallen_back: RefCell::new(HashSet::new()),
+            kernels: HashMap::new(),
             extension_package: (),
         };
 
@@ -134,7 +448,64 @@ impl<P> ContextCtor<P> for OpenCLContext<P>
             active: unpackaged.active,
             selected_devices: unpackaged.selected_devices,
             selected_hardware: unpackaged.selected_hardware,
+            data_parallel: unpackaged.data_parallel,
+            compiler_options: unpackaged.compiler_options,
+            program_cache: unpackaged.program_cache,
+            profiler: unpackaged.profiler,
+            stats: unpackaged.stats,
+            alive: unpackaged.alive,
+            fallback: unpackaged.fallback.with_package(),
+            fallen_back: unpackaged.fallen_back,
+            kernels: unpackaged.kernels,
             extension_package: package,
         })
     }
-}
\ No newline at end of file
+}
+
+/// Builds a program from the `src_strings` for the `devices`, collecting the build log of each 
+/// device if the build fails.
+///
+/// If a `cache` is provided, the program is created from the cached binaries when possible, and
+/// the binaries of a program built from source are added to it.
+fn build(
+    context: &ocl::Context, 
+    src_strings: Vec<CString>, 
+    devices: &[ocl::Device], 
+    options: &str,
+    cache: Option<&ProgramCache>) -> Result<ocl::core::Program> {
+
+    let cmplr_opts = CString::new(options)
+        .map_err(|e| Error::new(ErrorKind::InvalidConfiguration, e))?;
+
+    let keys: Vec<_> = match cache {
+        Some(_) => devices.iter().map(|d| ProgramCache::key(&src_strings, d, options)).collect(),
+        _ => vec![],
+    };
+
+    if let Some(cache) = cache {
+        if let Some(program) = cache.load(context, devices, &keys, &cmplr_opts) {
+            return Ok(program);
+        }
+    }
+
+    let program = ocl::core::create_program_with_source(context.core(), &src_strings)?;
+
+    if let Err(e) = ocl::core::build_program(&program, Some(devices), &cmplr_opts, None, None) {
+        let logs: Vec<_> = devices.iter()
+            .map(|d| {
+                let info = ProgramBuildInfo::BuildLog;
+                (d.name(), ocl::core::get_program_build_info(&program, d, info).to_string())
+            })
+            .collect();
+
+        let error = ProgramBuildError { logs, error: e.into() };
+
+        return Err(Error::new(ErrorKind::ProgramBuild, error));
+    }
+
+    if let Some(cache) = cache {
+        cache.store(&program, devices, &keys);
+    }
+
+    Ok(program)
+}
@@ -1,17 +1,26 @@
 use ocl;
+use ocl::enums::DeviceInfo;
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
 
-use super::{OpenCLBuf, OpenCLMemory};
-use super::super::super::compute_device::{Allocate, ComputeDevice};
-use super::super::super::error::Result;
+use super::{OpenCLAccess, OpenCLBuf, OpenCLKernel, OpenCLKernels, OpenCLMemory, OpenCLStream};
+use super::profiler::Profiler;
+use super::stream::Streams;
+use super::super::super::compute_device::{Allocate, ComputeDevice, Initialize};
+use super::super::super::error::{Error, ErrorKind, KernelLaunchError, Result};
 use super::super::super::memory::Memory;
-use super::super::super::tensor::{TensorShape, TensorType};
+use super::super::super::profile::EventCategory;
+use super::super::super::stats::TransferStats;
+use super::super::super::tensor::{Distribution, TensorShape, TensorType};
 
 /// Represents an Open CL device.
 #[derive(Clone, Debug)]
 pub struct OpenCLDevice {
     pub(in frameworks::open_cl) device: ocl::Device,
     pub(in frameworks::open_cl) context: ocl::Context,
-    /// A command queue
+    /// The command queue of the default stream.
+    pub(in frameworks::open_cl) queue: ocl::Queue,
+    /// The command queues (streams)
     ///
     /// A command queue is the mechanism for interaction with the device. The queue is used for 
     /// operations such as kernel launches and memory copies. At least one command queue per device
@@ -29,16 +38,146 @@ pub struct OpenCLDevice {
     /// - in-order
     /// - out-of-order
     ///
-    /// ## TODO
-    ///
-    /// * Use events to synchronize
-    pub(in frameworks::open_cl) queue: ocl::Queue,
+    /// Commands are enqueued on the active stream, which is the default (in-order) stream unless
+    /// another one is set.
+    pub(in frameworks::open_cl) streams: Rc<Streams>,
+    /// The program containing the kernels used by the framework itself (e.g., random 
+    /// initialization), built once for all of the devices of the context.
+    pub(in frameworks::open_cl) builtins: ocl::core::Program,
+    /// The index of the device within the context's selection.
+    pub(in frameworks::open_cl) index: usize,
+    /// The profiler of the context.
+    pub(in frameworks::open_cl) profiler: Rc<Profiler>,
+    /// The transfer statistics of the context.
+    pub(in frameworks::open_cl) stats: Rc<RefCell<TransferStats>>,
+    /// Dangles once the context is dropped, retiring the memory allocated on the device.
+    pub(in frameworks::open_cl) alive: Weak<()>,
 }
 
 impl OpenCLDevice {
+    /// Returns the command queue of the default stream.
     pub fn queue(&self) -> &ocl::Queue {
         &self.queue
     }
+
+    /// Returns the stream the commands are enqueued on.
+    pub fn stream(&self) -> OpenCLStream {
+        self.streams.active()
+    }
+
+    /// Returns the streams of the device, starting with the default stream.
+    pub fn streams(&self) -> Vec<OpenCLStream> {
+        self.streams.streams.borrow().clone()
+    }
+
+    /// Creates a stream, i.e., an additional command queue.
+    ///
+    /// The commands of an `out_of_order` stream only wait for the commands they depend on, if the
+    /// device supports it.
+    pub fn create_stream(&self, out_of_order: bool) -> Result<OpenCLStream> {
+        let mut flags = ocl::flags::QUEUE_PROFILING_ENABLE;
+
+        if out_of_order {
+            flags = flags | ocl::flags::QUEUE_OUT_OF_ORDER_EXEC_MODE_ENABLE;
+        }
+
+        let queue = ocl::Queue::new(&self.context, self.device, Some(flags))?;
+        let mut streams = self.streams.streams.borrow_mut();
+        let stream = OpenCLStream { queue, id: streams.len(), out_of_order };
+        streams.push(stream.clone());
+
+        Ok(stream)
+    }
+
+    /// Enqueues the following commands (kernel launches and transfers) on the `stream`.
+    pub fn set_stream(&self, stream: &OpenCLStream) -> Result {
+        let known = self.streams.streams.borrow().get(stream.id)
+            .map_or(false, |s| s.queue.as_ptr() == stream.queue.as_ptr());
+
+        if !known {
+            let message = format!("stream {} doesn't belong to {}", stream.id, self.device.name());
+            return Err(Error::new(ErrorKind::InvalidSelection, message));
+        }
+
+        self.streams.active.set(stream.id);
+
+        Ok(())
+    }
+
+    /// Blocks until the commands enqueued on each of the device's streams have completed.
+    pub fn synchronize(&self) -> Result {
+        for stream in self.streams.streams.borrow().iter() {
+            stream.synchronize()?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the index of the device within the context's selection.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns a description of the device and its driver (e.g., 
+    /// `GeForce GTX 1080 (NVIDIA Corporation, driver 390.48)`).
+    ///
+    /// Results that depend on the device and its compiler, such as tuned kernel parameters, are 
+    /// stored under this identifier.
+    pub fn identifier(&self) -> String {
+        let vendor = self.device.info(DeviceInfo::Vendor).to_string();
+        let driver = self.device.info(DeviceInfo::DriverVersion).to_string();
+
+        format!("{} ({}, driver {})", self.device.name(), vendor, driver)
+    }
+
+    /// Enqueues the `kernel` on the active stream.
+    ///
+    /// The kernel waits for the commands of other streams accessing the memory it `accesses`, 
+    /// which should list each of the kernel's buffer arguments.
+    ///
+    /// If profiling is enabled, the kernel's timings are recorded under the operation name `op`.
+    /// A failed launch returns an `ErrorKind::KernelLaunch` error naming `op`.
+    pub unsafe fn enqueue(&self, op: &str, kernel: &OpenCLKernel, accesses: &[OpenCLAccess]) 
+        -> Result {
+
+        let launch_error = |e: ocl::Error| {
+            let error = KernelLaunchError { kernel: op.to_string(), error: e.into() };
+            Error::new(ErrorKind::KernelLaunch, error)
+        };
+
+        let stream = self.streams.active();
+
+        if accesses.is_empty() && !self.profiler.enabled() {
+            return kernel.enq(&stream.queue, None, None).map_err(launch_error);
+        }
+
+        let wait_list: ocl::EventList = accesses.iter()
+            .flat_map(|access| access.wait_list(&stream))
+            .collect::<Vec<_>>()
+            .into();
+
+        let mut event = ocl::Event::empty();
+        let wait_list = if wait_list.is_empty() { None } else { Some(&wait_list) };
+        kernel.enq(&stream.queue, wait_list, Some(&mut event)).map_err(launch_error)?;
+
+        for access in accesses {
+            access.record(&stream, &event);
+        }
+
+        if self.profiler.enabled() {
+            self.profiler.record(op, EventCategory::Kernel, self.index, event);
+        }
+
+        Ok(())
+    }
+
+    /// Records the `event` of a transfer issued by a synchronization for the `reason`, if 
+    /// profiling is enabled.
+    pub(in frameworks::open_cl) fn record_transfer(&self, reason: &str, event: ocl::Event) {
+        if self.profiler.enabled() {
+            self.profiler.record(reason, EventCategory::Transfer, self.index, event);
+        }
+    }
 }
 
 impl ComputeDevice for OpenCLDevice { }
@@ -56,8 +195,52 @@ impl<T> Allocate<T> for OpenCLDevice  where T: TensorType + 'static {
         let memory = Box::new(OpenCLMemory {
             buf,
             device,
+            dependencies: Default::default(),
+            staging: None,
         });
 
         return Ok(memory);
     }
-}
\ No newline at end of file
+}
+
+macro_rules! initialize {
+    ($t:ty, $name:expr) => {
+        impl Initialize<$t> for OpenCLDevice {
+            fn initialize(&self, memory: &mut Memory<$t>, distribution: &Distribution, seed: u64) 
+                -> Result {
+
+                let memory = memory.downcast_mut::<OpenCLMemory<$t>>()
+                    .ok_or(ErrorKind::MemoryDowncasting)?;
+                let n = memory.buf.buf.len();
+                let (offset, scale) = distribution.parameters();
+
+                let kernel_name = match *distribution {
+                    Distribution::Uniform { .. } => concat!("uniform_", $name, "_fill"),
+                    Distribution::Normal { .. } => concat!("normal_", $name, "_fill"),
+                    Distribution::TruncatedNormal { .. } => 
+                        concat!("truncated_normal_", $name, "_fill"),
+                };
+
+                unsafe {
+                    let kernels = OpenCLKernels::new(&self.builtins, Some(self), &[kernel_name])?;
+                    let kernel = kernels.get(self, kernel_name)?
+                        .arg_buf(&*memory)
+                        .arg_scl(n as u32)
+                        .arg_scl(seed as u32)
+                        .arg_scl((seed >> 32) as u32)
+                        .arg_scl(offset as $t)
+                        .arg_scl(scale as $t)
+
+                        .gws([n]);
+
+                    self.enqueue(kernel_name, &kernel, &[OpenCLAccess::write(memory)])?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+initialize!(f32, "float");
+initialize!(f64, "double");
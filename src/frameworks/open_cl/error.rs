@@ -4,6 +4,6 @@ use error::{Error, ErrorKind};
 impl From<OpenCLError> for Error {
     /// Creates a new error from a known kind of error
     fn from(e: OpenCLError) -> Error {
-        Error::new(ErrorKind::Framework(super::OpenCL::<()>::ID), ::std::error::Error::description(&e)) 
+        Error::new(ErrorKind::Framework(super::OpenCL::<()>::ID), e.to_string())
     }
 }
\ No newline at end of file
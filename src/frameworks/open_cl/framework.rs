@@ -1,11 +1,14 @@
 use ocl;
 use ocl::Platform as Implementation;
+use ocl::core::ffi;
 use ocl::enums::{DeviceInfo, DeviceInfoResult};
 use ocl::flags::{DEVICE_TYPE_ACCELERATOR, DEVICE_TYPE_CPU, DEVICE_TYPE_GPU};
 use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::ptr;
 
 use super::OpenCLContext;
-use super::super::super::error::Result;
+use super::super::super::error::{Error, ErrorKind, Result};
 use super::super::super::framework::{Framework, FrameworkCtor};
 use super::super::super::hardware::{Hardware, HardwareKind};
 
@@ -13,11 +16,12 @@ use super::super::super::hardware::{Hardware, HardwareKind};
 ///
 /// # Flow
 ///
-/// Since multiple platforms can exist, the first available platform is selected during 
-/// the initialization. A list of available devices are then provided for your choosing. Then,
+/// Since multiple platforms can exist, all of them are enumerated during the initialization and
+/// a list of available devices across the platforms is then provided for your choosing. Then,
 /// the provided selection of devices are used to create a context, with a command queue for each
-/// device. At this stage, a program(s) is compiled. A (host) program is essentially a collection 
-/// of kernels. A kernel is the smallest unit of execution.
+/// device. A context is limited to the devices of a single platform. At this stage, a program(s)
+/// is compiled. A (host) program is essentially a collection of kernels. A kernel is the smallest
+/// unit of execution.
 ///
 /// In OpenCL, the host code can read in a kernel binary (i.e., compiled off-line) or a kernel 
 /// source file (i.e., compile on-line). More information on on-line/off-line compilation can be
@@ -39,18 +43,136 @@ use super::super::super::hardware::{Hardware, HardwareKind};
 /// [3]: https://www.khronos.org/registry/OpenCL/sdk/1.0/docs/man/xhtml/mathFunctions.html
 #[derive(Debug)]
 pub struct OpenCL<P> {
-    /// A list of available devices for the first platform found.
+    /// A list of available devices across all of the platforms found.
     available_hardware: Vec<Hardware>,
-    /// The specific Open CL implementation (e.g., AMD APP, NVIDIA or Intel Open CL)
+    /// The specific Open CL implementations (e.g., AMD APP, NVIDIA or Intel Open CL)
     ///
     /// Platforms are defined by the implementation. Platforms enables the host to interact with 
-    /// OpenCL-capable devices.
-    pub(in frameworks::open_cl) implementation: Implementation,
+    /// OpenCL-capable devices. The `platform` of a `Hardware` is an index into this list.
+    pub(in frameworks::open_cl) implementations: Vec<Implementation>,
+    /// The options passed to the compiler when building the programs of a context.
+    pub(in frameworks::open_cl) compiler_options: String,
+    /// The directory the binaries of the programs built from source are cached in, if any.
+    pub(in frameworks::open_cl) program_cache: Option<PathBuf>,
+    /// The sub-devices created by partitioning devices, along with their hardware.
+    sub_devices: Vec<(Hardware, ocl::Device)>,
     package: PhantomData<P>,
 }
 
 impl<P> OpenCL<P> {
-    pub(in frameworks::open_cl) const ID: &'static str = "Open CL";
+    /// The name of the framework.
+    pub const ID: &'static str = "Open CL";
+
+    /// Returns all of the platforms found.
+    pub fn platforms(&self) -> &[Implementation] {
+        &self.implementations
+    }
+
+    /// Returns the available hardware provided by the platform at index `platform`.
+    pub fn platform_hardware(&self, platform: usize) -> Vec<Hardware> {
+        self.available_hardware.iter().filter(|h| h.platform == platform).cloned().collect()
+    }
+
+    /// Returns the options passed to the compiler when building programs.
+    pub fn compiler_options(&self) -> &str {
+        &self.compiler_options
+    }
+
+    /// Sets the options passed to the compiler when building the programs of the contexts created
+    /// afterwards (e.g., `-cl-fast-relaxed-math`).
+    pub fn set_compiler_options<S>(&mut self, options: S) where S: Into<String> {
+        self.compiler_options = options.into();
+    }
+
+    /// Returns the directory the program binaries are cached in.
+    pub fn program_cache(&self) -> Option<&Path> {
+        self.program_cache.as_ref().map(|p| p.as_path())
+    }
+
+    /// Sets the directory the program binaries of the contexts created afterwards are cached in,
+    /// or disables the cache (the default).
+    ///
+    /// Programs are otherwise built from source every time a context is created. Cached entries 
+    /// are keyed by the source, the compiler options, the device and its driver version.
+    pub fn set_program_cache(&mut self, directory: Option<PathBuf>) {
+        self.program_cache = directory;
+    }
+
+    /// Partitions the device of the `hardware` into as many sub-devices of `compute_units`
+    /// compute units as it can hold, and returns their hardware.
+    ///
+    /// The sub-devices are selected like any other hardware of the platform, e.g., to split
+    /// data-parallel operations across the cores of a single CPU. They aren't part of the
+    /// `hardware` of the framework, so the default selection doesn't change. Partitioning requires
+    /// an Open CL 1.2 device supporting `CL_DEVICE_PARTITION_EQUALLY`, which excludes most GPUs.
+    pub fn partition_equally(
+        &mut self, 
+        hardware: &Hardware, 
+        compute_units: usize) -> Result<Vec<Hardware>> {
+
+        let implementation = self.implementations.get(hardware.platform).cloned()
+            .ok_or_else(|| Error::new(ErrorKind::InvalidSelection, "unknown Open CL platform"))?;
+        let device = self.device(hardware);
+
+        let properties = [
+            ffi::CL_DEVICE_PARTITION_EQUALLY as ffi::cl_device_partition_property,
+            compute_units as ffi::cl_device_partition_property,
+            0,
+        ];
+
+        let ids = unsafe {
+            let mut n = 0;
+            let status = ffi::clCreateSubDevices(
+                device.as_raw(), properties.as_ptr(), 0, ptr::null_mut(), &mut n);
+            ocl::core::Error::eval_errcode(status, (), "clCreateSubDevices", "")?;
+
+            let mut ids = vec![ptr::null_mut(); n as usize];
+            let status = ffi::clCreateSubDevices(
+                device.as_raw(), properties.as_ptr(), n, ids.as_mut_ptr(), ptr::null_mut());
+            ocl::core::Error::eval_errcode(status, (), "clCreateSubDevices", "")?;
+            ids
+        };
+
+        // the sub-devices are numbered after the devices of the platform
+        let first_id = ocl::Device::list_all(implementation)?.len() + 
+            self.sub_devices.iter().filter(|&&(ref h, _)| h.platform == hardware.platform).count();
+
+        let mut partition = vec![];
+
+        for (i, id) in ids.into_iter().enumerate() {
+            let sub_device = ocl::Device::from(unsafe { ocl::core::DeviceId::from_raw(id) });
+            let sub_hardware = self::hardware::<P>(hardware.platform, first_id + i, &sub_device);
+            self.sub_devices.push((sub_hardware.clone(), sub_device));
+            partition.push(sub_hardware);
+        }
+
+        info!(
+            "[PARENCHYMA] Partitioned `{}` into {} sub-devices", hardware.name, partition.len());
+
+        Ok(partition)
+    }
+
+    /// Returns the device of the `hardware`, which belongs to a known platform.
+    pub(in frameworks::open_cl) fn device(&self, hardware: &Hardware) -> ocl::Device {
+        let sub_device = self.sub_devices.iter()
+            .find(|&&(ref h, _)| h.platform == hardware.platform && h.id == hardware.id);
+
+        match sub_device {
+            Some(&(_, device)) => device,
+            _ => ocl::Device::by_idx_wrap(self.implementations[hardware.platform], hardware.id),
+        }
+    }
+}
+
+impl<P> Drop for OpenCL<P> {
+    fn drop(&mut self) {
+        // a sub-device is only deleted once the command queues created for it are released
+        for &(ref hardware, device) in &self.sub_devices {
+            if let Err(e) = unsafe { ocl::core::release_device(&device, None) } {
+                warn!("[PARENCHYMA] Failed to release the sub-device `{}`: {}", hardware.name, e);
+            }
+        }
+    }
 }
 
 impl<P> Framework for OpenCL<P> where P: 'static {
@@ -61,65 +183,113 @@ impl<P> Framework for OpenCL<P> where P: 'static {
     fn hardware(&self) -> &[Hardware] {
         &self.available_hardware
     }
+
+    /// Returns the hardware of the first platform providing any.
+    ///
+    /// A context can't span multiple platforms, so the hardware of the other platforms has to be 
+    /// selected explicitly (see `platform_hardware`).
+    fn default_selection(&self) -> Vec<Hardware> {
+        match self.available_hardware.first() {
+            Some(hardware) => self.platform_hardware(hardware.platform),
+            _ => vec![],
+        }
+    }
 }
 
 impl<P> FrameworkCtor for OpenCL<P> where P: 'static {
     type Context = OpenCLContext<P>;
 
     fn new() -> Result<Self> {
-        let ignore_env_var = false;
-        let implementation = Implementation::first(ignore_env_var)?;
-        let devices = ocl::Device::list_all(implementation)?;
-
-        let available_hardware = {
-
-            devices.iter().enumerate()
-
-            .filter(|&(_, d)| {
-                use ocl::enums::DeviceInfo::{MaxComputeUnits, Type};
-                use ocl::enums::DeviceInfoResult::Error;
-
-                let _1 = d.is_available().unwrap_or(false);
-                // let _2 = match d.info(Type) { Error(_) => false, _ => true };
-                // let _3 = match d.info(MaxComputeUnits) { Error(_) => false, _ => true };
-                // TODO
-
-                _1
-            })
-
-            .map(|(i, d)| {
-                
-                let kind = {
-                    match d.info(DeviceInfo::Type) {
-                        DeviceInfoResult::Type(t) => match t {
-                            DEVICE_TYPE_ACCELERATOR => HardwareKind::Accelerator,
-                            DEVICE_TYPE_CPU => HardwareKind::CPU,
-                            DEVICE_TYPE_GPU => HardwareKind::GPU,
-                            _ => HardwareKind::Unknown,
-                        },
-                        _ => unreachable!(),
-                    }
-                };
-
-                let compute_units = {
-                    match d.info(DeviceInfo::MaxComputeUnits) {
-                        DeviceInfoResult::MaxComputeUnits(n) => n as usize,
-                        _ => unreachable!(),
-                    }
-                };
-
-                Hardware {
-                    id: i,
-                    framework: OpenCL::<P>::ID,
-                    kind,
-                    name: d.name(),
-                    compute_units,
+        let implementations = Implementation::list();
+
+        if implementations.is_empty() {
+            let message = "no Open CL platform was found";
+            return Err(Error::new(ErrorKind::Framework(OpenCL::<P>::ID), message));
+        }
+
+        let mut available_hardware = vec![];
+
+        for (platform, &implementation) in implementations.iter().enumerate() {
+            let devices = match ocl::Device::list_all(implementation) {
+                Ok(devices) => devices,
+                Err(e) => {
+                    warn!("[PARENCHYMA] Skipping the Open CL platform {}: {}", platform, e);
+                    continue;
                 }
-            })
+            };
 
-            .collect::<Vec<Hardware>>()
-        };
+            available_hardware.extend(
+                devices.iter().enumerate()
+                    .filter(|&(_, d)| d.is_available().unwrap_or(false))
+                    .map(|(i, d)| hardware::<P>(platform, i, d))
+            );
+        }
+
+        Ok(OpenCL { 
+            available_hardware, 
+            implementations, 
+            compiler_options: String::new(), 
+            program_cache: None,
+            sub_devices: vec![],
+            package: PhantomData,
+        })
+    }
+}
+
+/// Describes the device `d`, the `id`th device of the `platform`.
+fn hardware<P>(platform: usize, id: usize, d: &ocl::Device) -> Hardware {
+    let kind = {
+        match d.info(DeviceInfo::Type) {
+            DeviceInfoResult::Type(t) => match t {
+                DEVICE_TYPE_ACCELERATOR => HardwareKind::Accelerator,
+                DEVICE_TYPE_CPU => HardwareKind::CPU,
+                DEVICE_TYPE_GPU => HardwareKind::GPU,
+                _ => HardwareKind::Unknown,
+            },
+            _ => unreachable!(),
+        }
+    };
 
-        Ok(OpenCL {  available_hardware, implementation, package: PhantomData })
+    let compute_units = {
+        match d.info(DeviceInfo::MaxComputeUnits) {
+            DeviceInfoResult::MaxComputeUnits(n) => n as usize,
+            _ => unreachable!(),
+        }
+    };
+
+    let global_memory = match d.info(DeviceInfo::GlobalMemSize) {
+        DeviceInfoResult::GlobalMemSize(n) => n,
+        _ => 0,
+    };
+
+    let local_memory = match d.info(DeviceInfo::LocalMemSize) {
+        DeviceInfoResult::LocalMemSize(n) => n,
+        _ => 0,
+    };
+
+    let max_work_group_size = match d.info(DeviceInfo::MaxWorkGroupSize) {
+        DeviceInfoResult::MaxWorkGroupSize(n) => n,
+        _ => 1,
+    };
+
+    let extensions: Vec<String> = d.info(DeviceInfo::Extensions).to_string()
+        .split_whitespace()
+        .map(String::from)
+        .collect();
+
+    Hardware {
+        id,
+        framework: OpenCL::<P>::ID,
+        platform,
+        kind,
+        name: d.name(),
+        compute_units,
+        global_memory,
+        local_memory,
+        max_work_group_size,
+        version: d.info(DeviceInfo::Version).to_string(),
+        fp64: extensions.iter().any(|e| e == "cl_khr_fp64"),
+        fp16: extensions.iter().any(|e| e == "cl_khr_fp16"),
+        extensions,
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,177 @@
+use ocl;
+use ocl::core::{AsMem, OclPrm};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+use super::OpenCLDevice;
+use super::super::super::error::{Error, ErrorKind, Result};
+
+/// Holds the program of a package along with a pool of kernel objects per device.
+///
+/// Creating a kernel is as expensive as a call of a small operation, so the kernels are created
+/// when the package is compiled and reused afterwards. ocl 0.16 kernels can't be cloned before
+/// their arguments are set, and clones share their arguments
+/// ([ocl#97](https://github.com/cogciprocate/ocl/issues/97)), so the pools hold the raw kernel
+/// objects instead: each call takes one out of the pool, sets its arguments and returns it when
+/// it's dropped. A new kernel object is only created while another call holds the pooled ones.
+#[derive(Debug)]
+pub struct OpenCLKernels {
+    program: ocl::core::Program,
+    /// The kernels of each device, by name.
+    pools: Vec<HashMap<String, Pool>>,
+}
+
+/// The kernel objects of a device sharing the same name.
+#[derive(Debug)]
+struct Pool {
+    /// The kernels that aren't used by a call.
+    idle: RefCell<Vec<ocl::core::Kernel>>,
+    /// The number of kernels created.
+    created: Cell<usize>,
+}
+
+impl OpenCLKernels {
+    /// Creates the kernels named `names` for each of the `devices` (the devices the `program` was
+    /// built for).
+    pub fn new<'d, I>(
+        program: &ocl::core::Program,
+        devices: I,
+        names: &[&str]) -> Result<OpenCLKernels> where I: IntoIterator<Item=&'d OpenCLDevice> {
+
+        let mut pools = vec![];
+
+        for device in devices {
+            while pools.len() <= device.index {
+                pools.push(HashMap::new());
+            }
+
+            for &name in names {
+                let kernel = ocl::core::create_kernel(program, name)?;
+                let pool = Pool { idle: RefCell::new(vec![kernel]), created: Cell::new(1) };
+                pools[device.index].insert(name.to_string(), pool);
+            }
+        }
+
+        Ok(OpenCLKernels { program: program.clone(), pools })
+    }
+
+    /// Takes the kernel named `name` of the `device` out of the pool, for a single call.
+    ///
+    /// The arguments of the kernel are left over from its previous call, so all of them have to
+    /// be set again.
+    pub fn get(&self, device: &OpenCLDevice, name: &str) -> Result<OpenCLKernel> {
+        let pool = match self.pools.get(device.index).and_then(|pools| pools.get(name)) {
+            Some(pool) => pool,
+            _ => {
+                let message = format!("no kernel named `{}` was created for the device", name);
+                return Err(Error::new(ErrorKind::Other, message));
+            }
+        };
+
+        let kernel = match pool.idle.borrow_mut().pop() {
+            Some(kernel) => kernel,
+            _ => {
+                let kernel = ocl::core::create_kernel(&self.program, name)?;
+                pool.created.set(pool.created.get() + 1);
+                kernel
+            }
+        };
+
+        Ok(OpenCLKernel {
+            kernel: Some(kernel),
+            pool: &pool.idle,
+            arguments: 0,
+            error: None,
+            gws: ocl::SpatialDims::Unspecified,
+            lws: ocl::SpatialDims::Unspecified,
+        })
+    }
+
+    /// Returns the number of kernel objects named `name` created for the `device`, which only
+    /// grows beyond one when calls overlap.
+    pub fn created(&self, device: &OpenCLDevice, name: &str) -> usize {
+        self.pools.get(device.index)
+            .and_then(|pools| pools.get(name))
+            .map_or(0, |pool| pool.created.get())
+    }
+}
+
+/// A kernel taken out of the pool of an `OpenCLKernels` for a single call (see
+/// `OpenCLDevice::enqueue`).
+///
+/// The arguments are set in order (builder-style). The kernel returns to the pool when it's
+/// dropped.
+#[derive(Debug)]
+pub struct OpenCLKernel<'a> {
+    kernel: Option<ocl::core::Kernel>,
+    pool: &'a RefCell<Vec<ocl::core::Kernel>>,
+    /// The number of arguments set.
+    arguments: u32,
+    /// The first error returned while setting the arguments, reported by the launch.
+    error: Option<ocl::Error>,
+    gws: ocl::SpatialDims,
+    lws: ocl::SpatialDims,
+}
+
+impl<'a> OpenCLKernel<'a> {
+    /// Sets the next argument to the `buffer`.
+    pub fn arg_buf<T, M>(self, buffer: M) -> Self where T: OclPrm, M: AsMem<T> {
+        self.arg(ocl::core::KernelArg::Mem::<T>(buffer.as_mem()))
+    }
+
+    /// Sets the next argument to the `scalar`.
+    pub fn arg_scl<T>(self, scalar: T) -> Self where T: OclPrm {
+        self.arg(ocl::core::KernelArg::Scalar(scalar))
+    }
+
+    /// Sets the global work size.
+    pub fn gws<D>(mut self, gws: D) -> Self where D: Into<ocl::SpatialDims> {
+        self.gws = gws.into();
+        self
+    }
+
+    /// Sets the local work size (chosen by the implementation if unspecified).
+    pub fn lws<D>(mut self, lws: D) -> Self where D: Into<ocl::SpatialDims> {
+        self.lws = lws.into();
+        self
+    }
+
+    fn arg<T>(mut self, argument: ocl::core::KernelArg<T>) -> Self where T: OclPrm {
+        if self.error.is_none() {
+            let kernel = self.kernel.as_ref().expect("the kernel was returned to the pool");
+
+            if let Err(e) = ocl::core::set_kernel_arg(kernel, self.arguments, argument) {
+                self.error = Some(e);
+            }
+        }
+
+        self.arguments += 1;
+        self
+    }
+
+    /// Enqueues the kernel on the `queue`, after the `wait_list`.
+    pub(in frameworks::open_cl) unsafe fn enq(
+        &self,
+        queue: &ocl::Queue,
+        wait_list: Option<&ocl::EventList>,
+        event: Option<&mut ocl::Event>) -> ocl::Result<()> {
+
+        if let Some(ref e) = self.error {
+            return Err(e.to_string().into());
+        }
+
+        let kernel = self.kernel.as_ref().expect("the kernel was returned to the pool");
+        let lws = if self.lws.is_unspecified() { None } else { Some(self.lws.to_lens()?) };
+
+        ocl::core::enqueue_kernel(
+            queue, kernel, self.gws.dim_count(), None, &self.gws.to_lens()?, lws, wait_list, event)
+    }
+}
+
+impl<'a> Drop for OpenCLKernel<'a> {
+    fn drop(&mut self) {
+        if let Some(kernel) = self.kernel.take() {
+            self.pool.borrow_mut().push(kernel);
+        }
+    }
+}
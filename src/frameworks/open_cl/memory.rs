@@ -1,9 +1,13 @@
 use ocl;
+use std::cell::RefCell;
+use std::rc::Rc;
 use super::OpenCLDevice;
+use super::stream::Dependencies;
 use super::super::NativeMemory;
 use super::super::super::compute_device::ComputeDevice;
 use super::super::super::error::{ErrorKind, Result};
 use super::super::super::memory::{Memory, TransferDirection};
+use super::super::super::stats::TransferStats;
 use super::super::super::tensor::TensorType;
 
 /// A `Memory` wraps around an OpenCL buffer id that manages its deallocation, named 
@@ -27,6 +31,10 @@ pub struct OpenCLBuf<T> where T: TensorType {
 pub struct OpenCLMemory<T> where T: TensorType {
     pub(in super) buf: OpenCLBuf<T>,
     pub(in super) device: OpenCLDevice,
+    /// The commands accessing the memory, waited for by the commands of other streams.
+    pub(in super) dependencies: Rc<Dependencies>,
+    /// The copy of the host data being uploaded asynchronously, and the event of the upload.
+    pub(in super) staging: Option<(Vec<T>, ocl::Event)>,
 }
 
 impl<T> Memory<T> for OpenCLMemory<T> where T: TensorType + 'static {
@@ -38,21 +46,71 @@ impl<T> Memory<T> for OpenCLMemory<T> where T: TensorType + 'static {
         }
     }
 
-    fn transfer(&mut self, dir: TransferDirection, m: &mut Memory<T>) -> Result {
+    fn location(&self) -> String {
+        format!("{} #{}", self.device.device.name(), self.device.index)
+    }
+
+    fn transfer_stats(&self) -> Option<&RefCell<TransferStats>> {
+        Some(&self.device.stats)
+    }
+
+    fn retired(&self) -> bool {
+        self.device.alive.upgrade().is_none()
+    }
+
+    /// Transfers the data on the active stream, after the commands of other streams accessing 
+    /// the memory.
+    ///
+    /// Uploads on the default stream block, whereas uploads on other streams upload a copy of the
+    /// host data, so that they can overlap with the commands of other streams (e.g., uploading the
+    /// next batch while computing the current one).
+    ///
+    /// The data of another Open CL memory is copied directly if both belong to the same context,
+    /// or staged through the source memory mapped on the host otherwise.
+    fn transfer(&mut self, dir: TransferDirection, m: &mut Memory<T>, reason: &str) -> Result {
+        if let Some(other) = m.downcast_mut::<OpenCLMemory<T>>() {
+            return match dir {
+                TransferDirection::TransferIn => other.copy_to(self, reason),
+                TransferDirection::TransferOut => self.copy_to(other, reason),
+            };
+        }
+
+        let stream = self.device.stream();
+
         match dir {
             TransferDirection::TransferIn => {
                 if let Some(na) = m.downcast_ref::<NativeMemory<T>>() {
-                    let buffer_write_cmd = unsafe {
-                        self.buf.buf.write(
-                            na.0.as_slice_memory_order()
-                                .expect("the array's data is not contiguous") // TODO
-                        )
-                        .queue(&self.device.queue)
-                        .block(true) // TODO
-                        .len(na.0.len())
-                    };
+                    let wait_list = self.dependencies.wait_list(&stream, true);
+                    let data = na.0.as_slice_memory_order()
+                        .expect("the array's data is not contiguous"); // TODO
+                    let blocking = stream.id == 0;
+                    let mut event = ocl::Event::empty();
+
+                    self.finish_staging()?;
+
+                    let staging = if blocking { None } else { Some(data.to_vec()) };
+
+                    unsafe {
+                        let source = staging.as_ref().map_or(data, |staging| &staging[..]);
+
+                        let mut buffer_write_cmd = self.buf.buf.write(source)
+                            .queue(&stream.queue)
+                            .block(blocking)
+                            .len(na.0.len())
+                            .enew(&mut event);
 
-                    Ok(buffer_write_cmd.enq()?)
+                        if !wait_list.is_empty() {
+                            buffer_write_cmd = buffer_write_cmd.ewait(&wait_list[..]);
+                        }
+
+                        buffer_write_cmd.enq()?;
+                    }
+
+                    self.staging = staging.map(|staging| (staging, event.clone()));
+                    self.dependencies.record(&stream, &event, true);
+                    self.device.record_transfer(reason, event);
+
+                    Ok(())
                 } else {
                     Err(ErrorKind::NoAvailableSynchronizationRouteFound.into())
                 }
@@ -60,25 +118,236 @@ impl<T> Memory<T> for OpenCLMemory<T> where T: TensorType + 'static {
 
             TransferDirection::TransferOut => {
                 if let Some(na) = m.downcast_mut::<NativeMemory<T>>() {
+                    let wait_list = self.dependencies.wait_list(&stream, false);
                     let length = na.0.len();
+                    let mut event = ocl::Event::empty();
 
-                    let buffer_read_cmd = unsafe {
-                        self.buf.buf.read(
+                    unsafe {
+                        let mut buffer_read_cmd = self.buf.buf.read(
                             na.0.as_slice_memory_order_mut()
                                 .expect("the array's data is not contiguous") // TODO
                         )
-                        .queue(&self.device.queue)
-                        .block(true) // TODO
+                        .queue(&stream.queue)
+                        .block(true)
                         .len(length)
-                    };
+                        .enew(&mut event);
+
+                        if !wait_list.is_empty() {
+                            buffer_read_cmd = buffer_read_cmd.ewait(&wait_list[..]);
+                        }
+
+                        buffer_read_cmd.enq()?;
+                    }
 
-                    Ok(buffer_read_cmd.enq()?)
+                    self.dependencies.record(&stream, &event, false);
+                    self.device.record_transfer(reason, event);
+
+                    Ok(())
                 } else {
                     Err(ErrorKind::NoAvailableSynchronizationRouteFound.into())
                 }
             }
         }
     }
+
+    /// Transfers the range on the active stream (see `transfer`), after the commands of other
+    /// streams accessing the memory.
+    ///
+    /// Ranges of other Open CL memory are only copied within a context.
+    fn transfer_range(
+        &mut self,
+        dir: TransferDirection,
+        m: &mut Memory<T>,
+        offset: usize,
+        m_offset: usize,
+        length: usize,
+        reason: &str) -> Result {
+
+        if let Some(other) = m.downcast_mut::<OpenCLMemory<T>>() {
+            if self.device.context.core() != other.device.context.core() {
+                return Err(ErrorKind::NoAvailableSynchronizationRouteFound.into());
+            }
+
+            return match dir {
+                TransferDirection::TransferIn => 
+                    other.copy_range_within_context(m_offset, self, offset, length, reason),
+                TransferDirection::TransferOut => 
+                    self.copy_range_within_context(offset, other, m_offset, length, reason),
+            };
+        }
+
+        let native = match m.downcast_mut::<NativeMemory<T>>() {
+            Some(native) => native.as_slice_memory_order_mut()
+                .expect("the array's data is not contiguous"), // TODO
+            _ => return Err(ErrorKind::NoAvailableSynchronizationRouteFound.into()),
+        };
+
+        let data = &mut native[m_offset..m_offset + length];
+        let stream = self.device.stream();
+        let write = match dir { TransferDirection::TransferIn => true, _ => false };
+        let wait_list = self.dependencies.wait_list(&stream, write);
+        let mut event = ocl::Event::empty();
+
+        self.finish_staging()?;
+
+        unsafe {
+            match dir {
+                TransferDirection::TransferIn => {
+                    let mut buffer_write_cmd = self.buf.buf.write(&data[..])
+                        .queue(&stream.queue)
+                        .block(true)
+                        .offset(offset)
+                        .len(length)
+                        .enew(&mut event);
+
+                    if !wait_list.is_empty() {
+                        buffer_write_cmd = buffer_write_cmd.ewait(&wait_list[..]);
+                    }
+
+                    buffer_write_cmd.enq()?;
+                },
+                TransferDirection::TransferOut => {
+                    let mut buffer_read_cmd = self.buf.buf.read(data)
+                        .queue(&stream.queue)
+                        .block(true)
+                        .offset(offset)
+                        .len(length)
+                        .enew(&mut event);
+
+                    if !wait_list.is_empty() {
+                        buffer_read_cmd = buffer_read_cmd.ewait(&wait_list[..]);
+                    }
+
+                    buffer_read_cmd.enq()?;
+                },
+            }
+        }
+
+        self.dependencies.record(&stream, &event, write);
+        self.device.record_transfer(reason, event);
+
+        Ok(())
+    }
+}
+
+impl<T> OpenCLMemory<T> where T: TensorType {
+    /// Copies the data to the `destination`, on the destination's active stream.
+    fn copy_to(&self, destination: &mut OpenCLMemory<T>, reason: &str) -> Result {
+        destination.finish_staging()?;
+
+        if self.device.context.core() == destination.device.context.core() {
+            self.copy_range_within_context(0, destination, 0, self.buf.buf.len(), reason)
+        } else {
+            self.copy_across_contexts(destination, reason)
+        }
+    }
+
+    /// Enqueues a copy of `length` elements between the buffers (`clEnqueueCopyBuffer`), from
+    /// `offset` to the `destination_offset`, on the destination's active stream. The buffers are
+    /// both accessible by the devices of the context.
+    fn copy_range_within_context(
+        &self,
+        offset: usize,
+        destination: &mut OpenCLMemory<T>,
+        destination_offset: usize,
+        length: usize,
+        reason: &str) -> Result {
+
+        destination.finish_staging()?;
+
+        let stream = destination.device.stream();
+        let mut wait_list = destination.dependencies.wait_list(&stream, true);
+        wait_list.extend(self.dependencies.foreign_wait_list(false));
+        let mut event = ocl::Event::empty();
+
+        let mut buffer_copy_cmd = self.buf.buf.cmd()
+            .copy(&destination.buf.buf, Some(destination_offset), Some(length))
+            .offset(offset)
+            .queue(&stream.queue)
+            .enew(&mut event);
+
+        if !wait_list.is_empty() {
+            buffer_copy_cmd = buffer_copy_cmd.ewait(&wait_list[..]);
+        }
+
+        buffer_copy_cmd.enq()?;
+
+        // the copy runs on the destination's queue, so the source's streams have to wait for it
+        self.dependencies.record_foreign(&event, false);
+        destination.dependencies.record(&stream, &event, true);
+        destination.device.record_transfer(reason, event);
+
+        Ok(())
+    }
+
+    /// Maps the source buffer on the host and writes the mapped data to the destination buffer,
+    /// since buffers can't be shared by contexts.
+    fn copy_across_contexts(&self, destination: &mut OpenCLMemory<T>, reason: &str) -> Result {
+        let source_stream = self.device.stream();
+        let stream = destination.device.stream();
+        let read_wait_list = self.dependencies.wait_list(&source_stream, false);
+        let write_wait_list = destination.dependencies.wait_list(&stream, true);
+        let mut map_event = ocl::Event::empty();
+        let mut unmap_event = ocl::Event::empty();
+        let mut event = ocl::Event::empty();
+
+        unsafe {
+            let mut buffer_map_cmd = self.buf.buf.map()
+                .read()
+                .queue(&source_stream.queue)
+                .enew(&mut map_event);
+
+            if !read_wait_list.is_empty() {
+                buffer_map_cmd = buffer_map_cmd.ewait(&read_wait_list[..]);
+            }
+
+            let mut mapped = buffer_map_cmd.enq()?;
+
+            let written = {
+                let mut buffer_write_cmd = destination.buf.buf.write(&mapped[..])
+                    .queue(&stream.queue)
+                    .block(true)
+                    .enew(&mut event);
+
+                if !write_wait_list.is_empty() {
+                    buffer_write_cmd = buffer_write_cmd.ewait(&write_wait_list[..]);
+                }
+
+                buffer_write_cmd.enq()
+            };
+
+            // the source has to be unmapped even if the write failed
+            mapped.unmap().queue(&source_stream.queue).enew(&mut unmap_event).enq()
+                .map_err(ocl::Error::from)?;
+            written?;
+        }
+
+        self.dependencies.record(&source_stream, &unmap_event, false);
+        self.device.record_transfer(reason, map_event);
+        destination.dependencies.record(&stream, &event, true);
+        destination.device.record_transfer(reason, event);
+
+        Ok(())
+    }
+
+    /// Waits for the asynchronous upload of the staging copy, before the copy is dropped or the
+    /// memory is overwritten.
+    fn finish_staging(&mut self) -> Result {
+        if let Some((_, previous)) = self.staging.take() {
+            previous.wait_for()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T> Drop for OpenCLMemory<T> where T: TensorType {
+    fn drop(&mut self) {
+        // an asynchronous upload may still be reading the staging copy
+        if let Some((_, ref event)) = self.staging {
+            let _ = event.wait_for();
+        }
+    }
 }
 
 impl<T: TensorType> ::ocl::core::AsMem<T> for OpenCLMemory<T> {
@@ -1,10 +1,18 @@
 pub use self::context::OpenCLContext;
 pub use self::device::OpenCLDevice;
+pub use self::kernels::{OpenCLKernel, OpenCLKernels};
 pub use self::framework::OpenCL;
 pub use self::memory::{OpenCLBuf, OpenCLMemory};
+pub use self::program::OpenCLProgramBuilder;
+pub use self::stream::{OpenCLAccess, OpenCLStream};
 
+mod cache;
 mod context;
 mod device;
 mod error;
 mod framework;
-mod memory;
\ No newline at end of file
+mod kernels;
+mod memory;
+mod profiler;
+mod program;
+mod stream;
\ No newline at end of file
@@ -0,0 +1,65 @@
+use ocl;
+use ocl::enums::ProfilingInfo;
+use std::cell::{Cell, RefCell};
+
+use super::super::super::error::Result;
+use super::super::super::profile::{EventCategory, ProfileEvent};
+
+/// Collects the events of the commands enqueued while profiling is enabled.
+///
+/// The timings are only read once requested, since reading them blocks until the command has
+/// completed. A profiler is shared by all of the devices of a context.
+#[derive(Debug, Default)]
+pub(in frameworks::open_cl) struct Profiler {
+    enabled: Cell<bool>,
+    pending: RefCell<Vec<Pending>>,
+}
+
+#[derive(Debug)]
+struct Pending {
+    op: String,
+    category: EventCategory,
+    device: usize,
+    event: ocl::Event,
+}
+
+impl Profiler {
+    pub fn enabled(&self) -> bool {
+        self.enabled.get()
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.set(enabled);
+    }
+
+    /// Records the `event` of a command issued by the `op` on the `device`th device.
+    pub fn record(&self, op: &str, category: EventCategory, device: usize, event: ocl::Event) {
+        self.pending.borrow_mut().push(Pending { op: op.to_string(), category, device, event });
+    }
+
+    /// Waits for the recorded commands to complete and reads their timings.
+    pub fn events(&self) -> Result<Vec<ProfileEvent>> {
+        let pending = self.pending.borrow();
+        let mut events = Vec::with_capacity(pending.len());
+
+        for p in pending.iter() {
+            p.event.wait_for()?;
+
+            events.push(ProfileEvent {
+                op: p.op.clone(),
+                category: p.category,
+                device: p.device,
+                queued: p.event.profiling_info(ProfilingInfo::Queued).time()?,
+                submit: p.event.profiling_info(ProfilingInfo::Submit).time()?,
+                start: p.event.profiling_info(ProfilingInfo::Start).time()?,
+                end: p.event.profiling_info(ProfilingInfo::End).time()?,
+            });
+        }
+
+        Ok(events)
+    }
+
+    pub fn reset(&self) {
+        self.pending.borrow_mut().clear();
+    }
+}
@@ -0,0 +1,110 @@
+use ocl;
+use std::ffi::CString;
+
+use super::{OpenCLContext, OpenCLDevice};
+use super::super::super::error::{Error, ErrorKind, Result};
+
+/// Builds a program of an Open CL context with preprocessor defines and compiler options.
+///
+/// The options are appended to the compiler options of the framework, so a package can tune its
+/// kernels without overriding the flags chosen by the user.
+///
+/// ```ignore
+/// let program = context.program_builder()
+///     .src(include_str!("source/level1/xaxpy.cl"))
+///     .define("WGS", 64)
+///     .option("-cl-mad-enable")
+///     .build()?;
+/// ```
+pub struct OpenCLProgramBuilder<'a, P: 'a> {
+    context: &'a OpenCLContext<P>,
+    devices: Vec<ocl::Device>,
+    src_strings: Vec<CString>,
+    defines: Vec<(String, String)>,
+    options: Vec<String>,
+}
+
+impl<'a, P> OpenCLProgramBuilder<'a, P> {
+    pub(in frameworks::open_cl) fn new(context: &'a OpenCLContext<P>) -> Self {
+        let devices = context.devices().iter().map(|d| d.device.clone()).collect();
+
+        OpenCLProgramBuilder { 
+            context, devices, src_strings: vec![], defines: vec![], options: vec![] 
+        }
+    }
+
+    /// Builds the program for the `devices` only, rather than for all of the devices of the 
+    /// context (e.g., to compile kernels with the parameters tuned for some of them).
+    pub fn devices<'d, I>(mut self, devices: I) -> Self 
+        where I: IntoIterator<Item=&'d OpenCLDevice> {
+
+        self.devices = devices.into_iter().map(|d| d.device.clone()).collect();
+        self
+    }
+
+    /// Adds a source string.
+    ///
+    /// Panics if the source contains a nul byte.
+    pub fn src<S>(mut self, src: S) -> Self where S: Into<Vec<u8>> {
+        self.src_strings.push(CString::new(src).expect("a source string contains a nul byte"));
+        self
+    }
+
+    /// Adds the source strings.
+    pub fn srcs<I>(mut self, src_strings: I) -> Self where I: IntoIterator<Item=CString> {
+        self.src_strings.extend(src_strings);
+        self
+    }
+
+    /// Defines the macro `name` as `value` (i.e., `-D name=value`).
+    ///
+    /// Redefining a macro replaces its value.
+    pub fn define<V>(mut self, name: &str, value: V) -> Self where V: ToString {
+        self.defines.retain(|&(ref n, _)| n != name);
+        self.defines.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Adds a compiler option (e.g., `-cl-fast-relaxed-math`).
+    pub fn option(mut self, option: &str) -> Self {
+        self.options.push(option.to_string());
+        self
+    }
+
+    /// Returns the options passed to the compiler: the options of the framework, followed by the
+    /// defines and the options of the builder.
+    pub fn compiler_options(&self) -> String {
+        let defines = self.defines.iter().map(|&(ref n, ref v)| format!("-D {}={}", n, v));
+
+        Some(self.context.compiler_options().to_string()).into_iter()
+            .chain(defines)
+            .chain(self.options.iter().cloned())
+            .filter(|option| !option.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Builds the program.
+    ///
+    /// Fails with `ErrorKind::InvalidConfiguration` if a macro name isn't an identifier, and with
+    /// `ErrorKind::ProgramBuild` if the sources don't compile.
+    pub fn build(self) -> Result<ocl::core::Program> {
+        if let Some(&(ref name, _)) = self.defines.iter().find(|&&(ref n, _)| !is_identifier(n)) {
+            let message = format!("`{}` isn't a valid macro name", name);
+            return Err(Error::new(ErrorKind::InvalidConfiguration, message));
+        }
+
+        let options = self.compiler_options();
+        self.context.build_program(self.src_strings, &self.devices, &options)
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    match chars.next() {
+        Some(c) if c == '_' || c.is_ascii_alphabetic() => 
+            chars.all(|c| c == '_' || c.is_ascii_alphanumeric()),
+        _ => false,
+    }
+}
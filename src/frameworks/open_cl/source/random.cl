@@ -0,0 +1,165 @@
+// =================================================================================================
+// Counter-based random number generation (Philox4x32-10).
+//
+// Mirrors `tensor/initializer.rs` - every element is computed from its index and the seed, so the
+// values written here are the same as the ones produced by the native framework.
+// =================================================================================================
+
+#define PHILOX_M0 0xD2511F53u
+#define PHILOX_M1 0xCD9E8D57u
+#define PHILOX_W0 0x9E3779B9u
+#define PHILOX_W1 0xBB67AE85u
+
+// The number of draws a truncated normal sample may be rejected before falling back to the mean.
+#define TRUNCATION_ROUNDS 32
+
+#define TWO_PI 6.283185307179586
+
+// `offset + scale * x` must not be fused, otherwise the results would differ from the host's.
+#pragma OPENCL FP_CONTRACT OFF
+
+uint4 philox4x32(uint4 counter, uint2 key) {
+    for (int i = 0; i < 10; i++) {
+        const uint hi0 = mul_hi(PHILOX_M0, counter.x);
+        const uint lo0 = PHILOX_M0 * counter.x;
+        const uint hi1 = mul_hi(PHILOX_M1, counter.z);
+        const uint lo1 = PHILOX_M1 * counter.z;
+
+        counter = (uint4)(hi1 ^ counter.y ^ key.x, lo1, hi0 ^ counter.w ^ key.y, lo0);
+        key += (uint2)(PHILOX_W0, PHILOX_W1);
+    }
+
+    return counter;
+}
+
+uint4 block(const uint2 seed, const ulong index, const uint round) {
+    return philox4x32((uint4)((uint) index, (uint)(index >> 32), round, 0u), seed);
+}
+
+// === float
+
+float uniform_float(const uint4 b) {
+    return (float)(b.x >> 8) * (1.0f / 16777216.0f);
+}
+
+float normal_float(const uint4 b) {
+    const float u1 = 1.0f - (float)(b.x >> 8) * (1.0f / 16777216.0f);
+    const float u2 = (float)(b.y >> 8) * (1.0f / 16777216.0f);
+
+    return sqrt(-2.0f * log(u1)) * cos((float) TWO_PI * u2);
+}
+
+kernel void uniform_float_fill(
+    global float* out, const uint len, const uint seed_lo, const uint seed_hi,
+    const float offset, const float scale) {
+
+    const uint i = get_global_id(0);
+    const uint2 seed = (uint2)(seed_lo, seed_hi);
+
+    if (i < len) {
+        out[i] = offset + scale * uniform_float(block(seed, i, 0u));
+    }
+}
+
+kernel void normal_float_fill(
+    global float* out, const uint len, const uint seed_lo, const uint seed_hi,
+    const float offset, const float scale) {
+
+    const uint i = get_global_id(0);
+    const uint2 seed = (uint2)(seed_lo, seed_hi);
+
+    if (i < len) {
+        out[i] = offset + scale * normal_float(block(seed, i, 0u));
+    }
+}
+
+kernel void truncated_normal_float_fill(
+    global float* out, const uint len, const uint seed_lo, const uint seed_hi,
+    const float offset, const float scale) {
+
+    const uint i = get_global_id(0);
+    const uint2 seed = (uint2)(seed_lo, seed_hi);
+
+    if (i < len) {
+        float z = normal_float(block(seed, i, 0u));
+        float s = scale;
+
+        for (uint round = 1u; fabs(z) > 2.0f; round++) {
+            if (round == TRUNCATION_ROUNDS) {
+                s = 0.0f;
+                break;
+            }
+
+            z = normal_float(block(seed, i, round));
+        }
+
+        out[i] = offset + s * z;
+    }
+}
+
+// === double
+
+#if defined(cl_khr_fp64)
+#pragma OPENCL EXTENSION cl_khr_fp64 : enable
+
+double uniform_double(const uint4 b) {
+    const ulong bits = ((ulong) b.x << 21) | (ulong)(b.y >> 11);
+    return (double) bits * (1.0 / 9007199254740992.0);
+}
+
+double normal_double(const uint4 b) {
+    const double u1 = 1.0 - uniform_double(b);
+    const double u2 = uniform_double((uint4)(b.z, b.w, 0u, 0u));
+
+    return sqrt(-2.0 * log(u1)) * cos(TWO_PI * u2);
+}
+
+kernel void uniform_double_fill(
+    global double* out, const uint len, const uint seed_lo, const uint seed_hi,
+    const double offset, const double scale) {
+
+    const uint i = get_global_id(0);
+    const uint2 seed = (uint2)(seed_lo, seed_hi);
+
+    if (i < len) {
+        out[i] = offset + scale * uniform_double(block(seed, i, 0u));
+    }
+}
+
+kernel void normal_double_fill(
+    global double* out, const uint len, const uint seed_lo, const uint seed_hi,
+    const double offset, const double scale) {
+
+    const uint i = get_global_id(0);
+    const uint2 seed = (uint2)(seed_lo, seed_hi);
+
+    if (i < len) {
+        out[i] = offset + scale * normal_double(block(seed, i, 0u));
+    }
+}
+
+kernel void truncated_normal_double_fill(
+    global double* out, const uint len, const uint seed_lo, const uint seed_hi,
+    const double offset, const double scale) {
+
+    const uint i = get_global_id(0);
+    const uint2 seed = (uint2)(seed_lo, seed_hi);
+
+    if (i < len) {
+        double z = normal_double(block(seed, i, 0u));
+        double s = scale;
+
+        for (uint round = 1u; fabs(z) > 2.0; round++) {
+            if (round == TRUNCATION_ROUNDS) {
+                s = 0.0;
+                break;
+            }
+
+            z = normal_double(block(seed, i, round));
+        }
+
+        out[i] = offset + s * z;
+    }
+}
+
+#endif
@@ -0,0 +1,166 @@
+use ocl;
+use std::cell::{Cell, RefCell};
+
+use super::OpenCLMemory;
+use super::super::super::error::Result;
+use super::super::super::tensor::TensorType;
+
+/// A command queue of a device.
+///
+/// Commands enqueued on different streams may run concurrently - e.g., the upload of the next
+/// batch on one stream alongside the computation of the current batch on another. Commands on an
+/// in-order stream run in the order they're enqueued, whereas those on an out-of-order stream
+/// only wait for the commands they depend on.
+///
+/// The dependencies are tracked by the memory of the shared tensors: a command waits for the
+/// commands of other streams (or of the same out-of-order stream) that last wrote the memory it
+/// reads, or that read or wrote the memory it writes.
+#[derive(Clone, Debug)]
+pub struct OpenCLStream {
+    pub(in frameworks::open_cl) queue: ocl::Queue,
+    pub(in frameworks::open_cl) id: usize,
+    pub(in frameworks::open_cl) out_of_order: bool,
+}
+
+impl OpenCLStream {
+    /// Returns the index of the stream within its device's streams (`0` for the default stream).
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn queue(&self) -> &ocl::Queue {
+        &self.queue
+    }
+
+    /// Returns `true` if the commands of the stream may run out of order.
+    pub fn is_out_of_order(&self) -> bool {
+        self.out_of_order
+    }
+
+    /// Blocks until the commands enqueued on the stream have completed.
+    pub fn synchronize(&self) -> Result {
+        Ok(self.queue.finish()?)
+    }
+}
+
+/// A memory accessed by an enqueued command (see `OpenCLDevice::enqueue`).
+///
+/// The command waits for the commands of other streams that last wrote the memory, or, if it
+/// writes the memory, that read it since.
+#[derive(Clone, Copy, Debug)]
+pub struct OpenCLAccess<'a> {
+    dependencies: &'a Dependencies,
+    write: bool,
+}
+
+impl<'a> OpenCLAccess<'a> {
+    /// The command reads the `memory`.
+    pub fn read<T>(memory: &'a OpenCLMemory<T>) -> OpenCLAccess<'a> where T: TensorType {
+        OpenCLAccess { dependencies: &memory.dependencies, write: false }
+    }
+
+    /// The command may overwrite the `memory`.
+    pub fn write<T>(memory: &'a OpenCLMemory<T>) -> OpenCLAccess<'a> where T: TensorType {
+        OpenCLAccess { dependencies: &memory.dependencies, write: true }
+    }
+
+    pub(in frameworks::open_cl) fn wait_list(&self, stream: &OpenCLStream) -> Vec<ocl::Event> {
+        self.dependencies.wait_list(stream, self.write)
+    }
+
+    pub(in frameworks::open_cl) fn record(&self, stream: &OpenCLStream, event: &ocl::Event) {
+        self.dependencies.record(stream, event, self.write)
+    }
+}
+
+/// The streams of a device, shared by the clones of the device (such as those held by its
+/// memory).
+#[derive(Debug)]
+pub(in frameworks::open_cl) struct Streams {
+    pub streams: RefCell<Vec<OpenCLStream>>,
+    /// The index of the stream the commands are enqueued on.
+    pub active: Cell<usize>,
+}
+
+impl Streams {
+    pub fn new(default: ocl::Queue) -> Streams {
+        let stream = OpenCLStream { queue: default, id: 0, out_of_order: false };
+
+        Streams {
+            streams: RefCell::new(vec![stream]),
+            active: Cell::new(0),
+        }
+    }
+
+    pub fn active(&self) -> OpenCLStream {
+        self.streams.borrow()[self.active.get()].clone()
+    }
+}
+
+/// The stream index recorded for the commands enqueued on the queue of another device (e.g., a 
+/// copy between two devices of a context), which every stream waits for.
+const FOREIGN: usize = ::std::usize::MAX;
+
+/// Tracks the commands accessing a memory object.
+#[derive(Debug, Default)]
+pub(in frameworks::open_cl) struct Dependencies {
+    /// The stream and event of the last command writing the memory.
+    write: RefCell<Option<(usize, ocl::Event)>>,
+    /// The streams and events of the commands reading the memory since it was last written.
+    reads: RefCell<Vec<(usize, ocl::Event)>>,
+}
+
+impl Dependencies {
+    /// Returns the events a command enqueued on the `stream` has to wait for before accessing
+    /// the memory.
+    pub fn wait_list(&self, stream: &OpenCLStream, write: bool) -> Vec<ocl::Event> {
+        // commands of the same in-order stream already run one after the other
+        self.events(write, |id| stream.out_of_order || id != stream.id)
+    }
+
+    /// Returns the events a command enqueued on the queue of another device has to wait for 
+    /// before accessing the memory.
+    pub fn foreign_wait_list(&self, write: bool) -> Vec<ocl::Event> {
+        self.events(write, |_| true)
+    }
+
+    fn events<F>(&self, write: bool, waits: F) -> Vec<ocl::Event> where F: Fn(usize) -> bool {
+        let mut events: Vec<_> = self.write.borrow().iter()
+            .filter(|&&(id, _)| waits(id))
+            .map(|&(_, ref event)| event.clone())
+            .collect();
+
+        if write {
+            events.extend(self.reads.borrow().iter()
+                .filter(|&&(id, _)| waits(id))
+                .map(|&(_, ref event)| event.clone()));
+        }
+
+        events
+    }
+
+    /// Records the `event` of a command enqueued on the `stream` accessing the memory.
+    pub fn record(&self, stream: &OpenCLStream, event: &ocl::Event, write: bool) {
+        self.record_as(stream.id, event, write)
+    }
+
+    /// Records the `event` of a command enqueued on the queue of another device accessing the 
+    /// memory.
+    pub fn record_foreign(&self, event: &ocl::Event, write: bool) {
+        self.record_as(FOREIGN, event, write)
+    }
+
+    fn record_as(&self, id: usize, event: &ocl::Event, write: bool) {
+        let mut reads = self.reads.borrow_mut();
+
+        if write {
+            *self.write.borrow_mut() = Some((id, event.clone()));
+            reads.clear();
+        } else {
+            // forget the reads that have completed, so that memory read over and over again
+            // (e.g., weights) doesn't accumulate events
+            reads.retain(|&(_, ref event)| !event.is_complete().unwrap_or(true));
+            reads.push((id, event.clone()));
+        }
+    }
+}
@@ -4,13 +4,22 @@
 //!
 //! [`Device`]: [device]: ./compute_device/struct.Device.html
 
+use std::str::FromStr;
+use super::error::{Error, ErrorKind};
+
 /// Representation for hardware across frameworks.
 #[derive(Clone, Debug)]
 pub struct Hardware {
-    /// The unique ID of the hardware.
+    /// The unique ID of the hardware within its platform.
     pub id: usize,
     /// Framework marker
     pub framework: &'static str,
+    /// The index of the platform providing the hardware.
+    ///
+    /// Frameworks such as Open CL can expose several implementations (platforms) on the same 
+    /// machine, e.g., POCL alongside a vendor runtime. Frameworks without such a notion always
+    /// use `0`.
+    pub platform: usize,
     /// The type of compute device, such as a CPU or a GPU.
     pub kind: HardwareKind,
     /// The name.
@@ -18,8 +27,42 @@ pub struct Hardware {
     /// The number of compute units.
     ///
     /// A compute unit is the fundamental unit of computation. A compute device usually has 
-    /// multiple compute units.
+    /// multiple compute units. For the host CPU, this is the number of physical cores.
     pub compute_units: usize,
+    /// The size of the global memory in bytes (`0` if unknown).
+    pub global_memory: u64,
+    /// The size of the local memory in bytes, i.e., the memory shared by the work-items of a
+    /// work-group (`0` if unknown or not applicable).
+    pub local_memory: u64,
+    /// The maximum number of work-items in a work-group.
+    pub max_work_group_size: usize,
+    /// The version of the framework supported by the hardware (e.g., "OpenCL 1.2 pocl 1.1").
+    pub version: String,
+    /// Whether or not double precision floating point arithmetic is supported.
+    pub fp64: bool,
+    /// Whether or not half precision floating point arithmetic is supported.
+    pub fp16: bool,
+    /// The supported extensions (e.g., `cl_khr_fp64`) or, for the host CPU, the SIMD instruction 
+    /// sets detected at runtime (e.g., `avx2`).
+    pub extensions: Vec<String>,
+}
+
+impl Hardware {
+    /// Returns `true` if the hardware supports the provided `extension`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use parenchyma::frameworks::Native;
+    /// use parenchyma::prelude::*;
+    ///
+    /// let framework: Native<()> = Native::new().unwrap();
+    /// let cpu = &framework.hardware()[0];
+    /// println!("AVX2 support: {}", cpu.supports("avx2"));
+    /// ```
+    pub fn supports(&self, extension: &str) -> bool {
+        self.extensions.iter().any(|e| e == extension)
+    }
 }
 
 /// General classes for devices, used to identify the type of a device.
@@ -39,4 +82,24 @@ pub enum HardwareKind {
     GPU,
     /// Used for anything else.
     Unknown,
-}
\ No newline at end of file
+}
+
+impl FromStr for HardwareKind {
+    type Err = Error;
+
+    /// Parses a kind case-insensitively (e.g., `gpu`, `CPU` or `accelerator`).
+    fn from_str(s: &str) -> Result<HardwareKind, Error> {
+        match &s.to_lowercase()[..] {
+            "accelerator" => Ok(HardwareKind::Accelerator),
+            "cell" => Ok(HardwareKind::Cell),
+            "cpu" => Ok(HardwareKind::CPU),
+            "dsp" => Ok(HardwareKind::DSP),
+            "gpu" => Ok(HardwareKind::GPU),
+            "unknown" => Ok(HardwareKind::Unknown),
+            _ => {
+                let message = format!("`{}` isn't a hardware kind", s);
+                Err(Error::new(ErrorKind::InvalidConfiguration, message))
+            }
+        }
+    }
+}
@@ -0,0 +1,250 @@
+//! Provides custom kernels - one-off operations registered with a backend without writing an
+//! extension package.
+//!
+//! A custom kernel pairs an Open CL C source with a native closure under a single name. Once
+//! registered, it's launched on shared tensors and scalars: the tensors are synchronized with the
+//! active device (or the host), and the work size is picked from the output.
+//!
+//! # Example Usage
+//!
+//! ```ignore
+//! let saxpy = CustomKernel::new("saxpy")
+//!     .opencl("
+//!         __kernel void saxpy(float a, __global const float* x, __global float* y) {
+//!             const size_t i = get_global_id(0);
+//!             y[i] += a * x[i];
+//!         }")
+//!     .native(|args| {
+//!         let a = args.scalar(0)?.to_f32();
+//!         let x = args.input(0)?;
+//!
+//!         for (y, x) in args.output(0)?.iter_mut().zip(x) {
+//!             *y += a * x;
+//!         }
+//!
+//!         Ok(())
+//!     });
+//!
+//! backend.register_kernel(saxpy)?;
+//! backend.launch("saxpy", &mut [2.0f32.into(), x.into(), y.into()])?;
+//! ```
+
+use rayon::ThreadPool;
+use std::fmt;
+use std::sync::Arc;
+
+use super::error::{Error, ErrorKind, Result};
+use super::tensor::SharedTensor;
+
+/// The name under which the frameworks without custom kernels report them as unsupported.
+pub const NAME: &'static str = "parenchyma/kernel";
+
+/// The native implementation of a custom kernel.
+pub type NativeKernel = Fn(&mut NativeArgs) -> Result + Send + Sync;
+
+/// A kernel defined by an Open CL C source and a native closure.
+///
+/// The Open CL source has to define a kernel function with the same name as the custom kernel,
+/// taking the arguments in the order they're passed to `Backend::launch`. Either implementation
+/// can be omitted - a kernel without an Open CL source runs on the host when launched on an
+/// Open CL backend.
+#[derive(Clone)]
+pub struct CustomKernel {
+    name: String,
+    opencl: Option<String>,
+    native: Option<Arc<NativeKernel>>,
+}
+
+impl CustomKernel {
+    /// Creates a kernel named `name`, without any implementation.
+    pub fn new<S>(name: S) -> CustomKernel where S: Into<String> {
+        CustomKernel { name: name.into(), opencl: None, native: None }
+    }
+
+    /// Sets the Open CL C source defining the kernel.
+    pub fn opencl<S>(mut self, source: S) -> Self where S: Into<String> {
+        self.opencl = Some(source.into());
+        self
+    }
+
+    /// Sets the native implementation.
+    pub fn native<F>(mut self, f: F) -> Self 
+        where F: Fn(&mut NativeArgs) -> Result + Send + Sync + 'static {
+
+        self.native = Some(Arc::new(f));
+        self
+    }
+
+    /// Returns the name of the kernel.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the Open CL C source defining the kernel, if any.
+    pub fn opencl_source(&self) -> Option<&str> {
+        self.opencl.as_ref().map(|source| &source[..])
+    }
+
+    /// Returns the native implementation, if any.
+    pub fn native_kernel(&self) -> Option<&Arc<NativeKernel>> {
+        self.native.as_ref()
+    }
+}
+
+impl fmt::Debug for CustomKernel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CustomKernel")
+            .field("name", &self.name)
+            .field("opencl", &self.opencl.is_some())
+            .field("native", &self.native.is_some())
+            .finish()
+    }
+}
+
+/// A scalar argument.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Scalar {
+    /// A `float`.
+    F32(f32),
+    /// A `double`.
+    F64(f64),
+    /// An `int`.
+    I32(i32),
+    /// A `uint`.
+    U32(u32),
+}
+
+impl Scalar {
+    /// Converts the scalar to an `f32`, rounding it if needed.
+    pub fn to_f32(&self) -> f32 {
+        self.to_f64() as f32
+    }
+
+    /// Converts the scalar to an `f64`.
+    pub fn to_f64(&self) -> f64 {
+        match *self {
+            Scalar::F32(value) => value as f64,
+            Scalar::F64(value) => value,
+            Scalar::I32(value) => value as f64,
+            Scalar::U32(value) => value as f64,
+        }
+    }
+}
+
+/// An argument of a custom kernel.
+///
+/// The tensors are synchronized with the device the kernel runs on - for reading only, or for
+/// reading and writing.
+pub enum KernelArg<'a> {
+    /// A scalar, passed by value.
+    Scalar(Scalar),
+    /// A tensor the kernel only reads (an input).
+    Tensor(&'a SharedTensor),
+    /// A tensor the kernel writes (an output), which it may also read.
+    TensorMut(&'a mut SharedTensor),
+}
+
+impl<'a> KernelArg<'a> {
+    /// Returns the number of work-items a kernel is launched with: the capacity of the first
+    /// mutable tensor, or of the first tensor if none is mutable.
+    pub fn work_size(args: &[KernelArg]) -> usize {
+        let capacity = |only_mutable: bool| args.iter().filter_map(|arg| match *arg {
+            KernelArg::TensorMut(ref tensor) => Some(tensor.shape().capacity()),
+            KernelArg::Tensor(tensor) if !only_mutable => Some(tensor.shape().capacity()),
+            _ => None,
+        }).next();
+
+        capacity(true).or_else(|| capacity(false)).unwrap_or(1)
+    }
+}
+
+macro_rules! scalar_arg {
+    ($t:ty, $variant:ident) => {
+        impl<'a> From<$t> for KernelArg<'a> {
+            fn from(value: $t) -> KernelArg<'a> {
+                KernelArg::Scalar(Scalar::$variant(value))
+            }
+        }
+    }
+}
+
+scalar_arg!(f32, F32);
+scalar_arg!(f64, F64);
+scalar_arg!(i32, I32);
+scalar_arg!(u32, U32);
+
+impl<'a> From<&'a SharedTensor> for KernelArg<'a> {
+    fn from(tensor: &'a SharedTensor) -> KernelArg<'a> {
+        KernelArg::Tensor(tensor)
+    }
+}
+
+impl<'a> From<&'a mut SharedTensor> for KernelArg<'a> {
+    fn from(tensor: &'a mut SharedTensor) -> KernelArg<'a> {
+        KernelArg::TensorMut(tensor)
+    }
+}
+
+/// The arguments of a custom kernel running natively, synchronized with the host.
+///
+/// The scalars, the tensors that are only read (the inputs), and the mutable tensors (the outputs)
+/// are each indexed in the order they were passed, e.g., `[a, x, y]` makes `a` scalar `0`, `x`
+/// input `0`, and `y` output `0`.
+pub struct NativeArgs<'a> {
+    pub(crate) scalars: Vec<Scalar>,
+    pub(crate) inputs: Vec<&'a [f32]>,
+    pub(crate) outputs: Vec<&'a mut [f32]>,
+    pool: &'a ThreadPool,
+}
+
+impl<'a> NativeArgs<'a> {
+    pub(crate) fn new(args: &'a mut [KernelArg], pool: &'a ThreadPool) -> Result<NativeArgs<'a>> {
+        let mut native = NativeArgs { scalars: vec![], inputs: vec![], outputs: vec![], pool };
+
+        for arg in args.iter_mut() {
+            match *arg {
+                KernelArg::Scalar(scalar) => native.scalars.push(scalar),
+                KernelArg::Tensor(tensor) => native.inputs.push(tensor.as_slice()?),
+                KernelArg::TensorMut(ref mut tensor) => 
+                    native.outputs.push(tensor.as_mut_slice()?),
+            }
+        }
+
+        Ok(native)
+    }
+
+    /// Returns the scalar at `i`.
+    pub fn scalar(&self, i: usize) -> Result<Scalar> {
+        self.scalars.get(i).cloned().ok_or_else(|| missing("scalar", i))
+    }
+
+    /// Returns the input at `i`.
+    ///
+    /// The input isn't borrowed from the arguments, so that it can be read while writing the
+    /// outputs.
+    pub fn input(&self, i: usize) -> Result<&'a [f32]> {
+        self.inputs.get(i).cloned().ok_or_else(|| missing("input", i))
+    }
+
+    /// Returns the output at `i`.
+    pub fn output(&mut self, i: usize) -> Result<&mut [f32]> {
+        match self.outputs.get_mut(i) {
+            Some(output) => Ok(&mut **output),
+            _ => Err(missing("output", i)),
+        }
+    }
+
+    /// Returns all of the outputs, for kernels writing several at once.
+    pub fn outputs(&mut self) -> &mut [&'a mut [f32]] {
+        &mut self.outputs
+    }
+
+    /// Returns the thread pool of the native context, for kernels running in parallel.
+    pub fn pool(&self) -> &ThreadPool {
+        self.pool
+    }
+}
+
+fn missing(kind: &str, i: usize) -> Error {
+    Error::new(ErrorKind::Other, format!("the kernel wasn't passed a {} #{}", kind, i))
+}
@@ -94,31 +94,41 @@
 //!
 //! [Collenchyma]: https://github.com/autumnai/collenchyma
 //! [Autumn]: https://github.com/autumnai
-#![feature(box_syntax, crate_in_paths, get_type_id, non_modrs_mods, unsize, use_extern_macros)]
 
+extern crate libloading;
 #[macro_use]
 extern crate log;
 #[macro_use(array)]
 extern crate ndarray;
 extern crate num;
+extern crate num_cpus;
 extern crate ocl;
+extern crate rayon;
+extern crate regex;
+extern crate toml;
 
 pub use self::ndarray::array;
 
 pub mod backend;
 pub mod changelog;
 pub mod compute_device;
+pub mod config;
 pub mod context;
 pub mod error;
 pub mod extension_package;
 pub mod framework;
 pub mod frameworks;
 pub mod hardware;
+pub mod kernel;
 pub mod memory;
+pub mod plugin;
+pub mod profile;
+pub mod stats;
 pub mod tensor;
 
 pub mod prelude {
     pub use super::backend::Backend;
+    pub use super::config::BackendConfig;
     pub use super::framework::{Framework, FrameworkCtor};
     pub use super::tensor::{IntoTensor, SharedTensor, TensorShape};
 }
\ No newline at end of file
@@ -1,8 +1,10 @@
 //! Provides a unified representation of memory across different frameworks.
 
 use std::any::{Any, TypeId};
+use std::cell::RefCell;
 use super::compute_device::ComputeDevice;
 use super::error::{ErrorKind, Result};
+use super::stats::TransferStats;
 
 // TODO
 // pub struct Stacked<'p, T> { data: T, marker: PhantomData<&'p mut &'a ()> }
@@ -37,7 +39,8 @@ pub trait Memory<T>: Any {
     /// avoid confusion.
     ///
     /// The `transfer` method handles the asynchronous data transfer behavior across 
-    /// frameworks and contexts.
+    /// frameworks and contexts. The `reason` of the synchronization names the transfer in the 
+    /// profiles of the frameworks recording them.
     ///
     // # TODO: Transfer Matrix/Routes
     //
@@ -51,7 +54,25 @@ pub trait Memory<T>: Any {
     // native/host -> native/host = true
     // native/host -> cuda/opencl = false
     // ```
-    fn transfer(&mut self, TransferDirection, &mut Memory<T>) -> Result {
+    #[allow(unused_variables)]
+    fn transfer(&mut self, dir: TransferDirection, m: &mut Memory<T>, reason: &str) -> Result {
+        Err(ErrorKind::NoAvailableSynchronizationRouteFound.into())
+    }
+    /// Transfers the `length` elements of the memory starting at `offset` to or from those of `m`
+    /// starting at `m_offset` (e.g., a share of a batch split across devices).
+    ///
+    /// Like `transfer`, a framework only needs to handle the routes it knows about. Copies
+    /// between the devices of a framework shouldn't go through the host.
+    #[allow(unused_variables)]
+    fn transfer_range(
+        &mut self,
+        dir: TransferDirection,
+        m: &mut Memory<T>,
+        offset: usize,
+        m_offset: usize,
+        length: usize,
+        reason: &str) -> Result {
+
         Err(ErrorKind::NoAvailableSynchronizationRouteFound.into())
     }
     /// Determines whether or not the memory is allocated or pinned on the `backend`'s active device.
@@ -69,6 +90,23 @@ pub trait Memory<T>: Any {
     fn synchronized(&self, compute_device: &ComputeDevice) -> bool {
         return false;
     }
+    /// Describes the location of the memory (e.g., the name of the device) in the transfer 
+    /// statistics and logs.
+    fn location(&self) -> String {
+        String::from("unknown")
+    }
+    /// Returns the transfer statistics of the context owning the memory, if it keeps any.
+    fn transfer_stats(&self) -> Option<&RefCell<TransferStats>> {
+        None
+    }
+    /// Returns `true` if the context owning the memory was dropped (e.g., when an `AnyBackend`
+    /// switched to another framework).
+    ///
+    /// A retired memory can still be transferred from, but shared tensors move off of it the next
+    /// time they're accessed.
+    fn retired(&self) -> bool {
+        false
+    }
 }
 
 impl<T: 'static> Memory<T> {
@@ -79,7 +117,7 @@ impl<T: 'static> Memory<T> {
         let t = TypeId::of::<M>();
 
         // Get TypeId of the type in the trait object
-        let boxed = self.get_type_id();
+        let boxed = self.type_id();
 
         // Compare both TypeIds on equality
         t == boxed
@@ -0,0 +1,434 @@
+//! Provides plugins - extension packages loaded from shared libraries at runtime.
+//!
+//! A plugin registers custom kernels (see the `kernel` module) through a stable C ABI, so
+//! proprietary kernels can be shipped without recompiling the application. The shared library
+//! exports a `parenchyma_plugin_declaration` function returning a `PluginDeclaration`, which
+//! names the plugin, records the versions of the ABI and of Parenchyma it was built against, and
+//! points to the function registering its kernels.
+//!
+//! A plugin is only loaded if it was built against the same ABI version and a semver-compatible
+//! version of Parenchyma.
+//!
+//! # Writing a Plugin
+//!
+//! A Rust plugin is a `cdylib` crate declaring itself with the `declare_plugin!` macro:
+//!
+//! ```ignore
+//! #[macro_use]
+//! extern crate parenchyma;
+//!
+//! use parenchyma::plugin::{PluginArgs, PluginKernel, PluginRegistrar, FAILURE, SUCCESS};
+//!
+//! declare_plugin!("saxpy", register);
+//!
+//! const SAXPY: &'static str = "
+//!     __kernel void saxpy(float a, __global const float* x, __global float* y) {
+//!         y[get_global_id(0)] += a * x[get_global_id(0)];
+//!     }\0";
+//!
+//! unsafe extern "C" fn register(registrar: *mut PluginRegistrar) -> i32 {
+//!     let kernel = PluginKernel {
+//!         name: b"saxpy\0".as_ptr() as *const _,
+//!         opencl: SAXPY.as_ptr() as *const _,
+//!         native: Some(saxpy),
+//!     };
+//!
+//!     (*registrar).register(&kernel)
+//! }
+//!
+//! unsafe extern "C" fn saxpy(args: *mut PluginArgs) -> i32 {
+//!     let args = &mut *args;
+//!
+//!     match (args.scalar(0), args.input(0), args.output(0)) {
+//!         (Some(a), Some(x), Some(y)) => {
+//!             for (y, x) in y.iter_mut().zip(x) {
+//!                 *y += a as f32 * x;
+//!             }
+//!
+//!             SUCCESS
+//!         },
+//!         _ => FAILURE,
+//!     }
+//! }
+//! ```
+//!
+//! Plugins written in other languages mirror the `#[repr(C)]` structures of this module.
+//!
+//! # Loading a Plugin
+//!
+//! ```ignore
+//! let plugin = Plugin::load("libsaxpy.so")?;
+//! backend.register_plugin(&plugin)?;
+//! backend.launch("saxpy", &mut [2.0f32.into(), x.into(), y.into()])?;
+//! ```
+
+use libloading::Library;
+use std::ffi::{CStr, OsStr};
+use std::os::raw::{c_char, c_void};
+use std::sync::Arc;
+use std::slice;
+
+use super::error::{Error, ErrorKind, Result};
+use super::kernel::{CustomKernel, NativeArgs, Scalar};
+
+/// The version of the plugin ABI, bumped whenever the structures of this module change.
+pub const ABI_VERSION: u32 = 1;
+
+/// The version of Parenchyma, as a NUL-terminated string.
+pub const CORE_VERSION: &'static str = concat!(env!("CARGO_PKG_VERSION"), "\0");
+
+/// The symbol of the function returning the declaration of a plugin.
+pub const DECLARATION_SYMBOL: &'static [u8] = b"parenchyma_plugin_declaration\0";
+
+/// The status returned by the functions of a plugin on success.
+pub const SUCCESS: i32 = 0;
+
+/// The status returned by the functions of a plugin on failure.
+pub const FAILURE: i32 = -1;
+
+/// The native implementation of a plugin kernel, returning `SUCCESS` or an error code.
+pub type PluginNativeKernel = unsafe extern "C" fn(args: *mut PluginArgs) -> i32;
+
+/// Declares a plugin, exporting the function returning its declaration.
+///
+/// The `register` function is an `unsafe extern "C" fn(*mut PluginRegistrar) -> i32`.
+#[macro_export]
+macro_rules! declare_plugin {
+    ($name:expr, $register:path) => {
+        #[no_mangle]
+        pub extern "C" fn parenchyma_plugin_declaration() -> $crate::plugin::PluginDeclaration {
+            $crate::plugin::PluginDeclaration {
+                abi_version: $crate::plugin::ABI_VERSION,
+                core_version: $crate::plugin::CORE_VERSION.as_ptr() as *const _,
+                name: concat!($name, "\0").as_ptr() as *const _,
+                register: $register,
+            }
+        }
+    }
+}
+
+/// The declaration of a plugin.
+#[repr(C)]
+pub struct PluginDeclaration {
+    /// The version of the ABI the plugin was built against (`ABI_VERSION`).
+    pub abi_version: u32,
+    /// The version of Parenchyma the plugin was built against (`CORE_VERSION`).
+    pub core_version: *const c_char,
+    /// The NUL-terminated name of the plugin.
+    pub name: *const c_char,
+    /// Registers the kernels of the plugin, returning `SUCCESS` or an error code.
+    pub register: unsafe extern "C" fn(registrar: *mut PluginRegistrar) -> i32,
+}
+
+/// A kernel registered by a plugin.
+#[repr(C)]
+pub struct PluginKernel {
+    /// The NUL-terminated name of the kernel.
+    pub name: *const c_char,
+    /// The NUL-terminated Open CL C source defining the kernel, or null.
+    pub opencl: *const c_char,
+    /// The native implementation of the kernel, if any.
+    pub native: Option<PluginNativeKernel>,
+}
+
+/// Collects the kernels registered by a plugin.
+#[repr(C)]
+pub struct PluginRegistrar {
+    /// Registers a kernel, returning `SUCCESS` or `FAILURE`. The kernel is copied - its strings
+    /// don't have to outlive the call.
+    pub register_kernel:
+        unsafe extern "C" fn(registrar: *mut PluginRegistrar, kernel: *const PluginKernel) -> i32,
+    registration: *mut c_void,
+}
+
+impl PluginRegistrar {
+    /// Registers a kernel, returning `SUCCESS` or `FAILURE`.
+    pub unsafe fn register(&mut self, kernel: &PluginKernel) -> i32 {
+        (self.register_kernel)(self, kernel)
+    }
+}
+
+/// A slice of an input tensor.
+#[repr(C)]
+pub struct PluginSlice {
+    pub data: *const f32,
+    pub len: usize,
+}
+
+/// A slice of an output tensor.
+#[repr(C)]
+pub struct PluginSliceMut {
+    pub data: *mut f32,
+    pub len: usize,
+}
+
+/// The arguments of a native plugin kernel, indexed like those of `NativeArgs`.
+///
+/// The scalars are all passed as doubles.
+#[repr(C)]
+pub struct PluginArgs {
+    pub scalars: *const f64,
+    pub num_scalars: usize,
+    pub inputs: *const PluginSlice,
+    pub num_inputs: usize,
+    pub outputs: *const PluginSliceMut,
+    pub num_outputs: usize,
+}
+
+impl PluginArgs {
+    /// Returns the scalar at `i`, if any.
+    pub unsafe fn scalar(&self, i: usize) -> Option<f64> {
+        parts(self.scalars, self.num_scalars).get(i).cloned()
+    }
+
+    /// Returns the input at `i`, if any.
+    ///
+    /// The inputs aren't borrowed from the arguments, so that they can be read while writing the
+    /// outputs - they're valid until the kernel returns.
+    pub unsafe fn input<'a>(&self, i: usize) -> Option<&'a [f32]> {
+        parts(self.inputs, self.num_inputs).get(i)
+            .map(|input| parts(input.data, input.len))
+    }
+
+    /// Returns the output at `i`, if any.
+    ///
+    /// The output borrows the arguments mutably, so an output can't be aliased by another call.
+    pub unsafe fn output(&mut self, i: usize) -> Option<&mut [f32]> {
+        parts(self.outputs, self.num_outputs).get(i)
+            .map(|output| if output.len == 0 {
+                &mut []
+            } else {
+                slice::from_raw_parts_mut(output.data, output.len)
+            })
+    }
+}
+
+/// A plugin loaded from a shared library.
+///
+/// The library stays loaded as long as the plugin or any of its native kernels (e.g., registered
+/// with a backend) are alive.
+pub struct Plugin {
+    name: String,
+    kernels: Vec<CustomKernel>,
+    _library: Arc<Library>,
+}
+
+/// The state of a registration, behind the opaque pointer of the registrar.
+struct Registration {
+    library: Arc<Library>,
+    plugin: String,
+    kernels: Vec<CustomKernel>,
+    error: Option<Error>,
+}
+
+impl Plugin {
+    /// Loads the plugin from the shared library at the `path`, checking that it was built against
+    /// a compatible version of Parenchyma and collecting its kernels.
+    pub fn load<P>(path: P) -> Result<Plugin> where P: AsRef<OsStr> {
+        let path = path.as_ref();
+
+        let library = Library::new(path).map_err(|e| {
+            let message = format!("failed to load the plugin `{}`: {}", path.to_string_lossy(), e);
+            Error::new(ErrorKind::Plugin, message)
+        })?;
+
+        let library = Arc::new(library);
+
+        let declaration = unsafe {
+            let declare = library.get::<extern "C" fn() -> PluginDeclaration>(DECLARATION_SYMBOL)
+                .map_err(|e| {
+                    let message = format!("`{}` isn't a plugin: {}", path.to_string_lossy(), e);
+                    Error::new(ErrorKind::Plugin, message)
+                })?;
+
+            declare()
+        };
+
+        let name = unsafe { string(declaration.name) }
+            .ok_or_else(|| Error::new(ErrorKind::Plugin, "the plugin has an invalid name"))?;
+
+        Self::check(&name, &declaration)?;
+
+        let mut registration = Registration {
+            library: library.clone(),
+            plugin: name.clone(),
+            kernels: vec![],
+            error: None,
+        };
+
+        let status = unsafe {
+            let mut registrar = PluginRegistrar {
+                register_kernel,
+                registration: &mut registration as *mut Registration as *mut c_void,
+            };
+
+            (declaration.register)(&mut registrar)
+        };
+
+        if let Some(error) = registration.error {
+            return Err(error);
+        }
+
+        if status != SUCCESS {
+            let message = format!("the plugin `{}` failed to register (code {})", name, status);
+            return Err(Error::new(ErrorKind::Plugin, message));
+        }
+
+        info!("[PARENCHYMA] Loaded the plugin `{}` ({} kernels)", name, registration.kernels.len());
+
+        Ok(Plugin { name, kernels: registration.kernels, _library: library })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the kernels registered by the plugin.
+    pub fn kernels(&self) -> &[CustomKernel] {
+        &self.kernels
+    }
+
+    fn check(name: &str, declaration: &PluginDeclaration) -> Result {
+        if declaration.abi_version != ABI_VERSION {
+            let message = format!(
+                "the plugin `{}` uses the ABI version {}, expected {}",
+                name, declaration.abi_version, ABI_VERSION);
+
+            return Err(Error::new(ErrorKind::Plugin, message));
+        }
+
+        let core_version = &CORE_VERSION[..CORE_VERSION.len() - 1];
+        let plugin_version = unsafe { string(declaration.core_version) }
+            .unwrap_or_else(|| "an unknown version".to_string());
+
+        if !is_compatible(core_version, &plugin_version) {
+            let message = format!(
+                "the plugin `{}` was built against Parenchyma {}, which is incompatible with {}",
+                name, plugin_version, core_version);
+
+            return Err(Error::new(ErrorKind::Plugin, message));
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns `true` if a plugin built against the `plugin` version of Parenchyma can be loaded by
+/// the `core` version, following the semver rules of Cargo: the versions have to share their
+/// leftmost non-zero component (and any components before it).
+pub fn is_compatible(core: &str, plugin: &str) -> bool {
+    fn parse(version: &str) -> Option<[u64; 3]> {
+        // ignore the pre-release and build metadata
+        let version = version.split(|c| c == '-' || c == '+').next()?;
+        let mut components = version.split('.').map(|component| component.parse().ok());
+        let parsed = [components.next()??, components.next()??, components.next()??];
+
+        match components.next() {
+            Some(_) => None,
+            None => Some(parsed),
+        }
+    }
+
+    match (parse(core), parse(plugin)) {
+        (Some(core), Some(plugin)) => {
+            let significant = core.iter().position(|&component| component != 0).unwrap_or(2);
+            core[..significant + 1] == plugin[..significant + 1]
+        },
+        _ => false,
+    }
+}
+
+unsafe extern "C" fn register_kernel(
+    registrar: *mut PluginRegistrar,
+    kernel: *const PluginKernel) -> i32 {
+
+    if registrar.is_null() || kernel.is_null() {
+        return FAILURE;
+    }
+
+    let registration = &mut *((*registrar).registration as *mut Registration);
+    let kernel = &*kernel;
+
+    let name = match string(kernel.name) {
+        Some(name) => name,
+        _ => {
+            let message = format!("the plugin `{}` registered a kernel with an invalid name",
+                registration.plugin);
+            registration.error = Some(Error::new(ErrorKind::Plugin, message));
+            return FAILURE;
+        }
+    };
+
+    let mut custom = CustomKernel::new(name.clone());
+
+    if !kernel.opencl.is_null() {
+        match string(kernel.opencl) {
+            Some(source) => custom = custom.opencl(source),
+            _ => {
+                let message = format!("the Open CL source of the kernel `{}` isn't UTF-8", name);
+                registration.error = Some(Error::new(ErrorKind::Plugin, message));
+                return FAILURE;
+            }
+        }
+    }
+
+    if let Some(native) = kernel.native {
+        // keeps the library loaded for as long as the kernel is registered somewhere
+        let library = registration.library.clone();
+
+        custom = custom.native(move |args| {
+            let _ = &library;
+            launch(&name, native, args)
+        });
+    }
+
+    registration.kernels.push(custom);
+    SUCCESS
+}
+
+fn launch(name: &str, native: PluginNativeKernel, args: &mut NativeArgs) -> Result {
+    let scalars: Vec<f64> = args.scalars.iter().map(Scalar::to_f64).collect();
+
+    let inputs: Vec<PluginSlice> = args.inputs.iter()
+        .map(|input| PluginSlice { data: input.as_ptr(), len: input.len() })
+        .collect();
+
+    let outputs: Vec<PluginSliceMut> = args.outputs.iter_mut()
+        .map(|output| PluginSliceMut { data: output.as_mut_ptr(), len: output.len() })
+        .collect();
+
+    let mut plugin_args = PluginArgs {
+        scalars: scalars.as_ptr(),
+        num_scalars: scalars.len(),
+        inputs: inputs.as_ptr(),
+        num_inputs: inputs.len(),
+        outputs: outputs.as_ptr(),
+        num_outputs: outputs.len(),
+    };
+
+    match unsafe { native(&mut plugin_args) } {
+        SUCCESS => Ok(()),
+        code => {
+            let message = format!("the plugin kernel `{}` failed (code {})", name, code);
+            Err(Error::new(ErrorKind::Other, message))
+        }
+    }
+}
+
+/// Copies a NUL-terminated UTF-8 string, returning `None` if it's null or invalid.
+unsafe fn string(pointer: *const c_char) -> Option<String> {
+    if pointer.is_null() {
+        return None;
+    }
+
+    CStr::from_ptr(pointer).to_str().ok().map(|string| string.to_string())
+}
+
+/// Returns the slice of `len` elements at `data`, accepting a null pointer for an empty slice.
+unsafe fn parts<'a, T>(data: *const T, len: usize) -> &'a [T] {
+    if len == 0 {
+        return &[];
+    }
+
+    slice::from_raw_parts(data, len)
+}
@@ -0,0 +1,195 @@
+//! Profiles of the operations executed by a backend.
+//!
+//! Profiling is opt-in (see `Backend::set_profiling`). Once enabled, the frameworks supporting it
+//! record the timings of every kernel, named after the operation that launched it, and of every
+//! transfer, named after the reason of the synchronization that issued it.
+//! A `Profile` can be printed as a summary table or exported to the [Chrome trace event][1]
+//! format, which can be loaded in `chrome://tracing` or [Perfetto][2].
+//!
+//! # Example
+//!
+//! ```ignore
+//! backend.set_profiling(true)?;
+//! backend.sigmoid(x, result)?;
+//!
+//! let profile = backend.profile()?;
+//! println!("{}", profile);
+//! ::std::fs::write("trace.json", profile.to_chrome_trace())?;
+//! ```
+//!
+//! [1]: https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU
+//! [2]: https://ui.perfetto.dev
+
+use std::collections::HashMap;
+use std::fmt;
+
+/// The kind of work an event represents.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum EventCategory {
+    /// A kernel execution.
+    Kernel,
+    /// A memory transfer between the host and a device.
+    Transfer,
+}
+
+impl EventCategory {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            EventCategory::Kernel => "kernel",
+            EventCategory::Transfer => "transfer",
+        }
+    }
+}
+
+/// The timings of a command, in nanoseconds on the device's clock.
+#[derive(Clone, Debug)]
+pub struct ProfileEvent {
+    /// The name of the operation that issued the command (e.g., the name of a kernel, `read` or `write`).
+    pub op: String,
+    /// The kind of command.
+    pub category: EventCategory,
+    /// The index of the device within the backend's selection.
+    pub device: usize,
+    /// When the command was enqueued by the host.
+    pub queued: u64,
+    /// When the command was submitted to the device.
+    pub submit: u64,
+    /// When the command started executing.
+    pub start: u64,
+    /// When the command finished executing.
+    pub end: u64,
+}
+
+impl ProfileEvent {
+    /// Returns the execution time.
+    pub fn duration(&self) -> u64 {
+        self.end.saturating_sub(self.start)
+    }
+
+    /// Returns the time between enqueueing and executing the command.
+    pub fn latency(&self) -> u64 {
+        self.start.saturating_sub(self.queued)
+    }
+}
+
+/// The aggregated timings of an operation.
+#[derive(Clone, Debug)]
+pub struct OpSummary {
+    pub op: String,
+    pub category: EventCategory,
+    /// The number of commands.
+    pub calls: usize,
+    /// The total execution time in nanoseconds.
+    pub total: u64,
+    /// The shortest execution time in nanoseconds.
+    pub min: u64,
+    /// The longest execution time in nanoseconds.
+    pub max: u64,
+    /// The total time spent between enqueueing and executing the commands, in nanoseconds.
+    pub latency: u64,
+}
+
+/// The events recorded by a backend.
+#[derive(Clone, Debug, Default)]
+pub struct Profile {
+    pub events: Vec<ProfileEvent>,
+}
+
+impl Profile {
+    /// Aggregates the events per operation, sorted by decreasing total execution time.
+    pub fn summary(&self) -> Vec<OpSummary> {
+        let mut summaries: HashMap<(&str, EventCategory), OpSummary> = HashMap::new();
+
+        for event in self.events.iter() {
+            let duration = event.duration();
+            let summary = summaries.entry((&event.op[..], event.category)).or_insert(OpSummary {
+                op: event.op.clone(),
+                category: event.category,
+                calls: 0,
+                total: 0,
+                min: ::std::u64::MAX,
+                max: 0,
+                latency: 0,
+            });
+
+            summary.calls += 1;
+            summary.total += duration;
+            summary.min = summary.min.min(duration);
+            summary.max = summary.max.max(duration);
+            summary.latency += event.latency();
+        }
+
+        let mut summaries: Vec<_> = summaries.into_iter().map(|(_, summary)| summary).collect();
+        summaries.sort_by(|a, b| b.total.cmp(&a.total).then_with(|| a.op.cmp(&b.op)));
+        summaries
+    }
+
+    /// Returns the total execution time of the events of the `category`, in nanoseconds.
+    pub fn total(&self, category: EventCategory) -> u64 {
+        self.events.iter().filter(|e| e.category == category).map(|e| e.duration()).sum()
+    }
+
+    /// Exports the events to the Chrome trace event format (JSON).
+    ///
+    /// Each device is represented by a thread of its own, and the timestamps are relative to the
+    /// earliest enqueued command.
+    pub fn to_chrome_trace(&self) -> String {
+        let origin = self.events.iter().map(|e| e.queued).min().unwrap_or(0);
+        let micros = |t: u64| t.saturating_sub(origin) as f64 / 1000.0;
+
+        let events: Vec<String> = self.events.iter().map(|e| {
+            format!(
+                "{{\"name\":{},\"cat\":\"{}\",\"ph\":\"X\",\"pid\":0,\"tid\":{},\"ts\":{:.3},\
+                \"dur\":{:.3},\"args\":{{\"queued\":{:.3},\"submit\":{:.3}}}}}",
+                escape(&e.op), e.category.as_str(), e.device, micros(e.start),
+                e.duration() as f64 / 1000.0, micros(e.queued), micros(e.submit))
+        })
+        .collect();
+
+        format!("{{\"traceEvents\":[{}],\"displayTimeUnit\":\"ns\"}}", events.join(","))
+    }
+}
+
+impl fmt::Display for Profile {
+    /// Formats the summary as a table (times in microseconds).
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let total = (self.total(EventCategory::Kernel) + self.total(EventCategory::Transfer)).max(1);
+
+        writeln!(f, "{:<32} {:>8} {:>6} {:>12} {:>10} {:>10} {:>10} {:>12}",
+            "op", "kind", "calls", "total (us)", "mean", "min", "max", "latency")?;
+
+        for s in self.summary() {
+            writeln!(f, "{:<32} {:>8} {:>6} {:>12.1} {:>10.1} {:>10.1} {:>10.1} {:>12.1}",
+                s.op, s.category.as_str(), s.calls, s.total as f64 / 1000.0,
+                s.total as f64 / s.calls as f64 / 1000.0, s.min as f64 / 1000.0,
+                s.max as f64 / 1000.0, s.latency as f64 / s.calls as f64 / 1000.0)?;
+        }
+
+        for &category in [EventCategory::Kernel, EventCategory::Transfer].iter() {
+            let t = self.total(category);
+            writeln!(f, "{:<32} {:>12.1} us ({:.1}%)",
+                format!("total ({})", category.as_str()), t as f64 / 1000.0,
+                100.0 * t as f64 / total as f64)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns `s` as a JSON string literal.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len() + 2);
+    escaped.push('"');
+
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped.push('"');
+    escaped
+}
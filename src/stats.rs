@@ -0,0 +1,146 @@
+//! Statistics of the transfers made to synchronize shared tensors.
+//!
+//! Every time a `SharedTensor` copies its latest data to another device, the transfer is counted
+//! by the context owning the memory it was copied to (or, for copies to the host, the memory it was
+//! copied from). The statistics are read and reset through `Backend::transfer_stats` and
+//! `Backend::reset_transfer_stats`, which makes it easy to notice tensors bouncing between the
+//! host and a device:
+//!
+//! ```ignore
+//! backend.reset_transfer_stats();
+//! train_step(&backend)?;
+//! println!("{}", backend.transfer_stats());
+//! ```
+//!
+//! Each synchronization is also logged at the `debug` level, with the shape of the tensor and the
+//! reason of the synchronization.
+
+use std::collections::HashMap;
+use std::fmt;
+
+/// The direction of a transfer.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Route {
+    /// From the host to a device.
+    HostToDevice,
+    /// From a device to the host.
+    DeviceToHost,
+    /// Between two devices, without staging through the host.
+    DeviceToDevice,
+}
+
+impl fmt::Display for Route {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match *self {
+            Route::HostToDevice => "host -> device",
+            Route::DeviceToHost => "device -> host",
+            Route::DeviceToDevice => "device -> device",
+        };
+
+        f.pad(s)
+    }
+}
+
+/// The source and destination of transfers.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct DevicePair {
+    /// The location of the copy the data was read from (see `Memory::location`).
+    pub source: String,
+    /// The location of the copy the data was written to.
+    pub destination: String,
+    /// The direction of the transfers.
+    pub route: Route,
+}
+
+/// The number and size of transfers.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct TransferCount {
+    /// The number of transfers.
+    pub transfers: usize,
+    /// The number of bytes transferred, in total.
+    pub bytes: usize,
+}
+
+impl TransferCount {
+    fn add(&mut self, other: TransferCount) {
+        self.transfers += other.transfers;
+        self.bytes += other.bytes;
+    }
+}
+
+/// Transfer counters, split by device pair.
+#[derive(Clone, Debug, Default)]
+pub struct TransferStats {
+    pairs: HashMap<DevicePair, TransferCount>,
+}
+
+impl TransferStats {
+    /// Counts a transfer of `bytes` between the `pair` of devices.
+    pub fn record(&mut self, pair: DevicePair, bytes: usize) {
+        self.pairs.entry(pair).or_insert_with(TransferCount::default)
+            .add(TransferCount { transfers: 1, bytes });
+    }
+
+    /// Returns the counters of each pair of devices.
+    pub fn pairs(&self) -> &HashMap<DevicePair, TransferCount> {
+        &self.pairs
+    }
+
+    /// Returns the counters of the transfers taking the `route`.
+    pub fn route(&self, route: Route) -> TransferCount {
+        let mut count = TransferCount::default();
+
+        for (_, &c) in self.pairs.iter().filter(|&(pair, _)| pair.route == route) {
+            count.add(c);
+        }
+
+        count
+    }
+
+    /// Returns the counters of all of the transfers.
+    pub fn total(&self) -> TransferCount {
+        let mut count = TransferCount::default();
+
+        for &c in self.pairs.values() {
+            count.add(c);
+        }
+
+        count
+    }
+
+    /// Adds the counters of `other` to the counters.
+    pub fn merge(&mut self, other: &TransferStats) {
+        for (pair, &count) in other.pairs.iter() {
+            self.pairs.entry(pair.clone()).or_insert_with(TransferCount::default).add(count);
+        }
+    }
+
+    /// Resets the counters.
+    pub fn clear(&mut self) {
+        self.pairs.clear();
+    }
+
+    /// Returns `true` if no transfer was counted.
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+}
+
+impl fmt::Display for TransferStats {
+    /// Formats the counters as a table, the busiest pairs first.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut pairs: Vec<_> = self.pairs.iter().collect();
+        pairs.sort_by(|a, b| b.1.bytes.cmp(&a.1.bytes).then_with(|| a.0.source.cmp(&b.0.source)));
+
+        writeln!(f, "{:<24} {:<24} {:<16} {:>10} {:>14}",
+            "source", "destination", "route", "transfers", "bytes")?;
+
+        for (pair, count) in pairs {
+            writeln!(f, "{:<24} {:<24} {:<16} {:>10} {:>14}",
+                pair.source, pair.destination, pair.route, count.transfers, count.bytes)?;
+        }
+
+        let total = self.total();
+        writeln!(f, "{:<66} {:>10} {:>14}", "total", total.transfers, total.bytes)
+    }
+}
@@ -0,0 +1,256 @@
+//! Random initialization of tensor memory.
+//!
+//! Values are produced by a counter-based generator ([Philox4x32-10][1]). Rather than advancing
+//! a hidden state, every element is computed from its own index and the seed, so a device can fill
+//! its memory in parallel and produce the very same stream as the host. The same algorithm is
+//! implemented in Rust for the native framework and in OpenCL C for the OpenCL framework.
+//!
+//! Uniform samples are bitwise identical across frameworks. Normal samples go through
+//! `log`/`sqrt`/`cos`, so they can differ by a few ULPs depending on the device's math library.
+//!
+//! [1]: http://www.thesalmons.org/john/random123/papers/random123sc11.pdf
+
+use super::TensorShape;
+
+/// Multiplier of the first Philox lane.
+const PHILOX_M0: u32 = 0xD251_1F53;
+/// Multiplier of the second Philox lane.
+const PHILOX_M1: u32 = 0xCD9E_8D57;
+/// Weyl sequence constant bumping the first key word.
+const PHILOX_W0: u32 = 0x9E37_79B9;
+/// Weyl sequence constant bumping the second key word.
+const PHILOX_W1: u32 = 0xBB67_AE85;
+
+/// The number of draws a truncated normal sample may be rejected before falling back to the mean.
+const TRUNCATION_ROUNDS: u32 = 32;
+
+/// Computes the Philox4x32-10 block for the provided `counter` and `key`.
+pub(crate) fn philox4x32(mut counter: [u32; 4], mut key: [u32; 2]) -> [u32; 4] {
+    for _ in 0..10 {
+        let p0 = PHILOX_M0 as u64 * counter[0] as u64;
+        let p1 = PHILOX_M1 as u64 * counter[2] as u64;
+
+        counter = [
+            ((p1 >> 32) as u32) ^ counter[1] ^ key[0],
+            p1 as u32,
+            ((p0 >> 32) as u32) ^ counter[3] ^ key[1],
+            p0 as u32,
+        ];
+
+        key = [key[0].wrapping_add(PHILOX_W0), key[1].wrapping_add(PHILOX_W1)];
+    }
+
+    counter
+}
+
+/// Returns the block for the element at `index` of the stream identified by `seed`.
+///
+/// The `round` is only used by the truncated normal distribution for redrawing rejected samples.
+fn block(seed: u64, index: u64, round: u32) -> [u32; 4] {
+    philox4x32([index as u32, (index >> 32) as u32, round, 0], [seed as u32, (seed >> 32) as u32])
+}
+
+/// Implemented by the element types that can be randomly initialized.
+pub trait Sample: Copy + 'static {
+    /// Maps a generator block to a uniform sample in `[0, 1)`.
+    fn uniform(block: [u32; 4]) -> Self;
+    /// Maps a generator block to a standard normal sample (Box-Muller transform).
+    fn normal(block: [u32; 4]) -> Self;
+    /// Computes `offset + scale * x` with the parameters rounded to `Self`.
+    fn affine(offset: f64, scale: f64, x: Self) -> Self;
+    /// Returns `true` if the absolute value of the standard sample `x` is within `bound`.
+    fn within(x: Self, bound: f64) -> bool;
+}
+
+impl Sample for f32 {
+    fn uniform(block: [u32; 4]) -> f32 {
+        (block[0] >> 8) as f32 * (1.0 / 16_777_216.0)
+    }
+
+    fn normal(block: [u32; 4]) -> f32 {
+        let u1 = 1.0 - (block[0] >> 8) as f32 * (1.0 / 16_777_216.0);
+        let u2 = (block[1] >> 8) as f32 * (1.0 / 16_777_216.0);
+
+        (-2.0 * u1.ln()).sqrt() * (2.0 * ::std::f32::consts::PI * u2).cos()
+    }
+
+    fn affine(offset: f64, scale: f64, x: f32) -> f32 {
+        offset as f32 + scale as f32 * x
+    }
+
+    fn within(x: f32, bound: f64) -> bool {
+        x.abs() <= bound as f32
+    }
+}
+
+impl Sample for f64 {
+    fn uniform(block: [u32; 4]) -> f64 {
+        let bits = ((block[0] as u64) << 21) | (block[1] >> 11) as u64;
+        bits as f64 * (1.0 / 9_007_199_254_740_992.0)
+    }
+
+    fn normal(block: [u32; 4]) -> f64 {
+        let u1 = 1.0 - f64::uniform(block);
+        let u2 = f64::uniform([block[2], block[3], 0, 0]);
+
+        (-2.0 * u1.ln()).sqrt() * (2.0 * ::std::f64::consts::PI * u2).cos()
+    }
+
+    fn affine(offset: f64, scale: f64, x: f64) -> f64 {
+        offset + scale * x
+    }
+
+    fn within(x: f64, bound: f64) -> bool {
+        x.abs() <= bound
+    }
+}
+
+/// A probability distribution with its parameters resolved.
+///
+/// `Initializer`s are turned into distributions once the shape of the tensor is known.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Distribution {
+    /// Uniform over `[low, high)`.
+    Uniform { low: f64, high: f64 },
+    /// Normal with the provided mean and standard deviation.
+    Normal { mean: f64, std: f64 },
+    /// Normal with the provided mean and standard deviation, redrawn if the sample is more than
+    /// two standard deviations away from the mean.
+    TruncatedNormal { mean: f64, std: f64 },
+}
+
+impl Distribution {
+    /// Returns the `(offset, scale)` pair applied to the standard sample (i.e., `low` and
+    /// `high - low` for uniform distributions, `mean` and `std` for normal ones).
+    ///
+    /// Devices should receive these instead of the raw parameters so that every framework 
+    /// performs the exact same floating point operations.
+    pub fn parameters(&self) -> (f64, f64) {
+        match *self {
+            Distribution::Uniform { low, high } => (low, high - low),
+            Distribution::Normal { mean, std } | 
+            Distribution::TruncatedNormal { mean, std } => (mean, std),
+        }
+    }
+
+    /// Returns the value of the element at `index` of the stream identified by `seed`.
+    pub fn sample<T>(&self, seed: u64, index: u64) -> T where T: Sample {
+        let (offset, scale) = self.parameters();
+
+        match *self {
+            Distribution::Uniform { .. } =>
+                T::affine(offset, scale, T::uniform(block(seed, index, 0))),
+
+            Distribution::Normal { .. } =>
+                T::affine(offset, scale, T::normal(block(seed, index, 0))),
+
+            Distribution::TruncatedNormal { .. } => {
+                let mut z = T::normal(block(seed, index, 0));
+                let mut round = 1;
+
+                while !T::within(z, 2.0) {
+                    if round == TRUNCATION_ROUNDS {
+                        // practically unreachable (p = 0.0455^32); the mean is used instead
+                        return T::affine(offset, 0.0, z);
+                    }
+
+                    z = T::normal(block(seed, index, round));
+                    round += 1;
+                }
+
+                T::affine(offset, scale, z)
+            }
+        }
+    }
+}
+
+/// Weight initialization schemes.
+///
+/// The fan-in and fan-out of the Xavier/Glorot and He schemes are inferred from the shape of the
+/// tensor, which is expected to be laid out as `[outputs, inputs, receptive field..]` (e.g.,
+/// `[out_features, in_features]` for a linear layer or `[filters, channels, height, width]` for a
+/// convolution). A rank 1 tensor uses its length as both the fan-in and the fan-out.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Initializer {
+    /// Uniform over `[low, high)`.
+    Uniform { low: f64, high: f64 },
+    /// Normal with the provided mean and standard deviation.
+    Normal { mean: f64, std: f64 },
+    /// Normal truncated to two standard deviations around the mean.
+    TruncatedNormal { mean: f64, std: f64 },
+    /// [Xavier/Glorot][1] uniform: `U(-l, l)` where `l = sqrt(6 / (fan_in + fan_out))`.
+    ///
+    /// [1]: http://proceedings.mlr.press/v9/glorot10a/glorot10a.pdf
+    XavierUniform,
+    /// [Xavier/Glorot][1] normal: `N(0, s)` where `s = sqrt(2 / (fan_in + fan_out))`.
+    ///
+    /// [1]: http://proceedings.mlr.press/v9/glorot10a/glorot10a.pdf
+    XavierNormal,
+    /// [He][1] uniform: `U(-l, l)` where `l = sqrt(6 / fan_in)`.
+    ///
+    /// [1]: https://arxiv.org/abs/1502.01852
+    HeUniform,
+    /// [He][1] normal: `N(0, s)` where `s = sqrt(2 / fan_in)`.
+    ///
+    /// [1]: https://arxiv.org/abs/1502.01852
+    HeNormal,
+}
+
+impl Initializer {
+    /// Resolves the initializer into a distribution for a tensor of the provided `shape`.
+    pub fn distribution(&self, shape: &TensorShape) -> Distribution {
+        let (fan_in, fan_out) = fans(shape);
+
+        match *self {
+            Initializer::Uniform { low, high } => Distribution::Uniform { low, high },
+            Initializer::Normal { mean, std } => Distribution::Normal { mean, std },
+            Initializer::TruncatedNormal { mean, std } => Distribution::TruncatedNormal { mean, std },
+            Initializer::XavierUniform => {
+                let limit = (6.0 / (fan_in + fan_out)).sqrt();
+                Distribution::Uniform { low: -limit, high: limit }
+            },
+            Initializer::XavierNormal => {
+                Distribution::Normal { mean: 0.0, std: (2.0 / (fan_in + fan_out)).sqrt() }
+            },
+            Initializer::HeUniform => {
+                let limit = (6.0 / fan_in).sqrt();
+                Distribution::Uniform { low: -limit, high: limit }
+            },
+            Initializer::HeNormal => {
+                Distribution::Normal { mean: 0.0, std: (2.0 / fan_in).sqrt() }
+            },
+        }
+    }
+}
+
+/// Returns the fan-in and fan-out of a tensor with the provided `shape`.
+fn fans(shape: &TensorShape) -> (f64, f64) {
+    let dims = shape.dimensions();
+
+    match dims.len() {
+        0 => (1.0, 1.0),
+        1 => (dims[0] as f64, dims[0] as f64),
+        _ => {
+            let receptive_field = dims[2..].iter().fold(1, |acc, &d| acc * d);
+            ((dims[1] * receptive_field) as f64, (dims[0] * receptive_field) as f64)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::philox4x32;
+
+    #[test]
+    fn philox_matches_known_answers() {
+        assert_eq!(
+            philox4x32([0; 4], [0; 2]),
+            [0x6627e8d5, 0xe169c58d, 0xbc57ac4c, 0x9b00dbd8]);
+        assert_eq!(
+            philox4x32([!0; 4], [!0; 2]),
+            [0x408f276d, 0x41c83b0e, 0xa20bc7c6, 0x6d5451fd]);
+        assert_eq!(
+            philox4x32([0x243f6a88, 0x85a308d3, 0x13198a2e, 0x03707344], [0xa4093822, 0x299f31d0]),
+            [0xd16cfe09, 0x94fdcceb, 0x5001e420, 0x24126ea1]);
+    }
+}
@@ -45,10 +45,12 @@
 //! that the memory will be overwritten, so the other memory locations are immediately considered 
 //! outdated.
 
+pub use self::initializer::{Distribution, Initializer, Sample};
 pub use self::into_tensor::IntoTensor;
 pub use self::tensor_shape::TensorShape;
 pub use self::tensor_type::TensorType;
 
+mod initializer;
 mod into_tensor;
 mod tensor_map;
 mod tensor_memories;
@@ -58,15 +60,16 @@ mod utility;
 
 use num::traits::{NumCast, cast};
 use std::cell::RefCell;
-use std::fmt;
+use std::{fmt, mem};
 use std::ops::{Deref, DerefMut};
 
 use self::tensor_map::TensorMap;
 use self::tensor_memories::TensorMemories;
 
-use super::compute_device::{Allocate, ComputeDevice};
-use super::error::{Error, ErrorKind, Result};
+use super::compute_device::{Allocate, ComputeDevice, Initialize};
+use super::error::{Error, ErrorKind, Result, ShapeMismatch};
 use super::memory::{Memory, TransferDirection};
+use super::stats::{DevicePair, Route};
 
 /// A shared tensor for framework-agnostic, memory-aware, n-dimensional storage.
 pub struct SharedTensor<T = f32> {
@@ -119,6 +122,11 @@ impl<T> SharedTensor<T> {
     pub fn shape(&self) -> &TensorShape {
         &self.shape
     }
+
+    /// Returns `true` if a copy of the tensor holds data.
+    pub fn is_initialized(&self) -> bool {
+        !self.synch_map.empty()
+    }
 }
 
 impl<T> SharedTensor<T> where T: 'static, ComputeDevice: Allocate<T> {
@@ -138,7 +146,7 @@ impl<T> SharedTensor<T> where T: 'static, ComputeDevice: Allocate<T> {
             ArrayBase::from_shape_vec(shape.dimensions(), data.into())
                 .map_err(|e| Error::new(ErrorKind::IncompatibleShape, e))?
         );
-        let memories = RefCell::new(vec![box memory as Box<Memory<T>>]);
+        let memories = RefCell::new(vec![Box::new(memory) as Box<Memory<T>>]);
         let synch_map = TensorMap::with(1 << 0);
 
         Ok(SharedTensor { memories, shape, synch_map })
@@ -153,8 +161,8 @@ impl<T> SharedTensor<T> where T: 'static, ComputeDevice: Allocate<T> {
         let shape = shape.into();
 
         if shape.capacity != self.shape.capacity {
-            let e: Error = ErrorKind::InvalidReshapedTensorSize.into();
-            return Err(e);
+            let mismatch = ShapeMismatch { expected: self.shape.clone(), actual: shape };
+            return Err(Error::new(ErrorKind::InvalidReshapedTensorSize, mismatch));
         }
 
         Ok(self.shape = shape)
@@ -371,6 +379,218 @@ impl<T> SharedTensor<T> where T: 'static, ComputeDevice: Allocate<T> {
     }
 }
 
+impl<T> SharedTensor<T> where T: 'static, ComputeDevice: Allocate<T> {
+    /// Splits the tensor along its first (batch) axis into tensors of `sizes[i]` samples located
+    /// on `devices[i]`.
+    ///
+    /// The sum of the `sizes` must be equal to the batch size. Each share is copied from the 
+    /// latest copy of the tensor - directly if the frameworks allow it (e.g., between the devices
+    /// of an Open CL context), or from a copy synchronized with the share's device otherwise.
+    pub fn split_batch(&self, sizes: &[usize], devices: &[&ComputeDevice]) 
+        -> Result<Vec<SharedTensor<T>>> where T: Copy {
+
+        let dimensions = self.shape.dimensions();
+        let batch_size = sizes.iter().fold(0, |acc, n| acc + n);
+
+        if dimensions.is_empty() || dimensions[0] != batch_size {
+            let message = format!(
+                "the batch sizes {:?} don't add up to the shape {:?}", sizes, dimensions);
+            return Err(Error::new(ErrorKind::IncompatibleShape, message));
+        }
+
+        if sizes.len() != devices.len() {
+            let message = format!("{} batch sizes for {} devices", sizes.len(), devices.len());
+            return Err(Error::new(ErrorKind::InvalidSelection, message));
+        }
+
+        let sample_size = if batch_size == 0 { 0 } else { self.shape.capacity / batch_size };
+        let mut offset = 0;
+
+        sizes.iter().zip(devices).map(|(&n, &codev)| {
+            let mut dimsizes = dimensions.to_vec();
+            dimsizes[0] = n;
+            let share = unsafe { SharedTensor::uninitialized(dimsizes) };
+
+            if n > 0 {
+                let i = share.fetchsert(codev)?;
+                self.transfer_range(offset * sample_size, &share, i, 0, codev, "split")?;
+                share.synch_map.set(1 << i);
+            }
+
+            offset += n;
+            Ok(share)
+        })
+        .collect()
+    }
+
+    /// Overwrites the tensor on the `codev` with the concatenation of the `parts` along the first
+    /// (batch) axis.
+    ///
+    /// This is the inverse of `split_batch`. The parts are copied like the shares of a split.
+    pub fn join_batch(&mut self, parts: &[SharedTensor<T>], codev: &ComputeDevice) -> Result 
+        where T: Copy {
+
+        let capacity = parts.iter().fold(0, |acc, part| acc + part.shape.capacity);
+
+        if capacity != self.shape.capacity {
+            let message = format!(
+                "the parts hold {} elements, but the shape {:?} holds {}", 
+                capacity, self.shape.dimensions(), self.shape.capacity);
+            return Err(Error::new(ErrorKind::IncompatibleShape, message));
+        }
+
+        let i = self.fetchsert(codev)?;
+        let mut offset = 0;
+
+        for part in parts.iter().filter(|part| part.shape.capacity > 0) {
+            part.transfer_range(0, self, i, offset, codev, "join")?;
+            offset += part.shape.capacity;
+        }
+
+        self.synch_map.set(1 << i);
+
+        Ok(())
+    }
+
+    /// Copies the elements of the latest copy starting at `offset` to the memory of the 
+    /// `destination` at `index`, starting at `destination_offset`.
+    ///
+    /// The copy falls back to a copy synchronized with the destination's `codev` if neither
+    /// framework knows the route between the memories.
+    fn transfer_range(
+        &self,
+        offset: usize,
+        destination: &SharedTensor<T>,
+        index: usize,
+        destination_offset: usize,
+        codev: &ComputeDevice,
+        reason: &str) -> Result where T: Copy {
+
+        use super::frameworks::NativeMemory;
+
+        if self.synch_map.empty() {
+            return Err(ErrorKind::UninitializedMemory.into());
+        }
+
+        let length = ::std::cmp::min(
+            self.shape.capacity - offset, destination.shape.capacity - destination_offset);
+
+        let mut copies = self.memories.borrow_mut();
+        let mut destination_copies = destination.memories.borrow_mut();
+        let target = destination_copies[index].deref_mut();
+        let mut source_index = self.synch_map.latest() as usize;
+
+        // the host copies are handled here, as native memory can't clone its elements
+        let host = |source: &mut Memory<T>, target: &mut Memory<T>| {
+            match (source.downcast_ref::<NativeMemory<T>>(), 
+                   target.downcast_mut::<NativeMemory<T>>()) {
+                (Some(source), Some(target)) => {
+                    let source = source.as_slice_memory_order()
+                        .expect("the array's data is not contiguous");
+                    let target = target.as_slice_memory_order_mut()
+                        .expect("the array's data is not contiguous");
+                    target[destination_offset..destination_offset + length]
+                        .copy_from_slice(&source[offset..offset + length]);
+                    true
+                },
+                _ => false,
+            }
+        };
+
+        let copy = |source: &mut Memory<T>, target: &mut Memory<T>| {
+            if host(&mut *source, &mut *target) {
+                return Ok(());
+            }
+
+            let dir = TransferDirection::TransferOut;
+
+            match source.transfer_range(dir, target, offset, destination_offset, length, reason) {
+                Err(ref e) if e.kind() == ErrorKind::NoAvailableSynchronizationRouteFound => {
+                    let dir = TransferDirection::TransferIn;
+                    target.transfer_range(dir, source, destination_offset, offset, length, reason)
+                },
+
+                r @ _ => r,
+            }
+        };
+
+        let direct = copy(copies[source_index].deref_mut(), &mut *target);
+
+        match direct {
+            Err(ref e) if e.kind() == ErrorKind::NoAvailableSynchronizationRouteFound => {
+                drop(copies);
+                source_index = self.autosync(codev, false)?;
+                copies = self.memories.borrow_mut();
+                copy(copies[source_index].deref_mut(), &mut *target)?;
+            },
+
+            r @ _ => r?,
+        }
+
+        let bytes = length * mem::size_of::<T>();
+        record_transfer(copies[source_index].deref(), &*target, &self.shape, bytes, reason);
+
+        Ok(())
+    }
+}
+
+impl<T> SharedTensor<T> where T: 'static, ComputeDevice: Allocate<T> {
+    /// Synchronizes the tensor with the host and drops all of the other copies.
+    ///
+    /// This detaches the tensor from the devices it was used on, e.g., before the backend owning 
+    /// them is replaced by one of another framework. Uninitialized tensors simply lose their 
+    /// allocations.
+    pub fn retain_host(&mut self) -> Result {
+        use super::frameworks::HOST;
+
+        if self.synch_map.empty() {
+            self.memories.borrow_mut().clear();
+            return Ok(());
+        }
+
+        let i = self.autosync(&HOST, false)?;
+        let mut borrowed_copies = self.memories.borrow_mut();
+        let host = borrowed_copies.swap_remove(i);
+        borrowed_copies.clear();
+        borrowed_copies.push(host);
+        self.synch_map.set(1 << 0);
+
+        Ok(())
+    }
+}
+
+impl<T> SharedTensor<T> where T: 'static, ComputeDevice: Allocate<T> + Initialize<T> {
+    /// Fills the tensor with random values generated directly on the device `codev`.
+    ///
+    /// The memory on `codev` becomes the latest copy, no data is uploaded from the host. Since the
+    /// values are produced by a counter-based generator, the same `seed` results in the same
+    /// tensor regardless of the device it was initialized on.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use parenchyma::frameworks::HOST;
+    /// use parenchyma::prelude::*;
+    /// use parenchyma::tensor::Initializer;
+    ///
+    /// let mut weights: SharedTensor = SharedTensor::from([64, 128]);
+    /// weights.initialize(&HOST, Initializer::XavierUniform, 42).unwrap();
+    /// ```
+    pub fn initialize(&mut self, codev: &ComputeDevice, initializer: Initializer, seed: u64) -> Result {
+        let distribution = initializer.distribution(&self.shape);
+        let i = self.fetchsert(codev)?;
+
+        {
+            let mut borrowed_copies = self.memories.borrow_mut();
+            codev.initialize(borrowed_copies[i].deref_mut(), &distribution, seed)?;
+        }
+
+        self.synch_map.set(1 << i);
+
+        Ok(())
+    }
+}
+
 impl<T> SharedTensor<T> where T: 'static, ComputeDevice: Allocate<T> {
     /// `autosync` synchronizes data only if necessary.
     ///
@@ -387,11 +607,13 @@ impl<T> SharedTensor<T> where T: 'static, ComputeDevice: Allocate<T> {
             // TODO auto initialize
             Err(ErrorKind::UninitializedMemory.into())
         } else {
+            let reason = if overwritable { "read-write access" } else { "read access" };
+
             let i = self.fetchsert(codev).and_then(|i| 
                 if self.synchronized(i) {
                     Ok(i)
                 } else {
-                    self.synchronize(i).map(|_| i)
+                    self.synchronize(i, reason).map(|_| i)
                 }
             )?;
 
@@ -408,7 +630,10 @@ impl<T> SharedTensor<T> where T: 'static, ComputeDevice: Allocate<T> {
         }
     }
     /// Synchronizes the memory at the provided index.
-    fn synchronize<'a>(&'a self, destination_index: usize) -> Result {
+    ///
+    /// The transfer is counted in the statistics of the destination's context (or the source's, if
+    /// the destination doesn't keep any) and logged along with the `reason` of the synchronization.
+    fn synchronize<'a>(&'a self, destination_index: usize, reason: &str) -> Result {
         let source_index = self.synch_map.latest() as usize;
         assert_ne!(source_index, TensorMap::CAPACITY);
 
@@ -439,14 +664,19 @@ impl<T> SharedTensor<T> where T: 'static, ComputeDevice: Allocate<T> {
         // from Native backend, while Native may know nothing about CUDA at all. So if first 
         // attempt fails we change order and try again.
 
-        match source.transfer(TransferDirection::TransferOut, destination.deref_mut()) {
+        match source.transfer(TransferDirection::TransferOut, destination.deref_mut(), reason) {
             Err(ref e) if e.kind() == ErrorKind::NoAvailableSynchronizationRouteFound => {
-                destination.transfer(TransferDirection::TransferIn, source.deref_mut())
+                destination.transfer(TransferDirection::TransferIn, source.deref_mut(), reason)?
             }
 
-            r @ _ => r
+            r @ _ => r?
         }
 
+        let bytes = self.shape.capacity * mem::size_of::<T>();
+        record_transfer(&**source, &**destination, &self.shape, bytes, reason);
+
+        Ok(())
+
         // TODO refactor
 
         // TODO: try transfer indirectly via Native backend
@@ -472,6 +702,8 @@ impl<T> SharedTensor<T> where T: 'static, ComputeDevice: Allocate<T> {
     ///
     /// **note**: A copy is created if a matching one is not found.
     fn fetchsert(&self, codev: &ComputeDevice) -> Result<usize> {
+        self.rehome()?;
+
         if let Some(i) = self.position(codev) {
             Ok(i)
         } else {
@@ -489,6 +721,96 @@ impl<T> SharedTensor<T> where T: 'static, ComputeDevice: Allocate<T> {
     }
 }
 
+impl<T> SharedTensor<T> where T: 'static, ComputeDevice: Allocate<T> {
+    /// Drops the retired memory copies (see `Memory::retired`).
+    ///
+    /// If none of the remaining copies is up-to-date, the latest copy is synchronized with the
+    /// host first. That way, tensors follow an `AnyBackend` to another framework when they're
+    /// next accessed.
+    fn rehome(&self) -> Result {
+        use super::frameworks::HOST;
+
+        if !self.memories.borrow().iter().any(|memory| memory.retired()) {
+            return Ok(());
+        }
+
+        let stranded = !self.synch_map.empty() && self.memories.borrow().iter()
+            .enumerate()
+            .all(|(i, memory)| memory.retired() || !self.synchronized(i));
+
+        if stranded {
+            let host: &ComputeDevice = &HOST;
+
+            let i = match self.position(host) {
+                Some(i) => i,
+                None => {
+                    if self.memories.borrow().len() == TensorMap::CAPACITY {
+                        return Err(ErrorKind::CapacityExceeded.into());
+                    }
+
+                    let m = host.allocate(&self.shape)?;
+                    self.memories.borrow_mut().push(m);
+                    self.memories.borrow().len() - 1
+                }
+            };
+
+            self.synchronize(i, "rehoming")?;
+            self.synch_map.insert(i);
+        }
+
+        let mut borrowed_copies = self.memories.borrow_mut();
+        let copies = mem::replace(&mut *borrowed_copies, vec![]);
+        let mut versions = 0;
+
+        for (i, memory) in copies.into_iter().enumerate() {
+            if !memory.retired() {
+                if self.synchronized(i) {
+                    versions |= 1 << borrowed_copies.len();
+                }
+
+                borrowed_copies.push(memory);
+            }
+        }
+
+        self.synch_map.set(versions);
+
+        Ok(())
+    }
+}
+
+/// Logs a transfer between the memories and counts it in the statistics of the destination's
+/// context (or the source's, if the destination doesn't keep any).
+fn record_transfer<T>(
+    source: &Memory<T>,
+    destination: &Memory<T>,
+    shape: &TensorShape,
+    bytes: usize,
+    reason: &str) where T: 'static {
+
+    use super::frameworks::NativeMemory;
+
+    let route = if source.is::<NativeMemory<T>>() {
+        Route::HostToDevice
+    } else if destination.is::<NativeMemory<T>>() {
+        Route::DeviceToHost
+    } else {
+        Route::DeviceToDevice
+    };
+
+    let pair = DevicePair { 
+        source: source.location(), 
+        destination: destination.location(), 
+        route,
+    };
+
+    debug!("[PARENCHYMA] Synchronized a tensor of shape {:?} from {} to {} ({} bytes) for {}",
+        shape.dimensions(), pair.source, pair.destination, bytes, reason);
+
+    if let Some(stats) = destination.transfer_stats().or(source.transfer_stats()) {
+        stats.borrow_mut().record(pair, bytes);
+    }
+}
+
 impl<T> fmt::Debug for SharedTensor<T> where T: fmt::Debug + 'static, ComputeDevice: Allocate<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         use super::frameworks::{HOST, NativeMemory};
@@ -1,4 +1,4 @@
-use super::super::error::{Error, ErrorKind, Result};
+use super::super::error::{Error, ErrorKind, Result, ShapeMismatch};
 
 /// Describes the shape of a tensor.
 ///
@@ -33,14 +33,8 @@ impl TensorShape {
     /// Checks that the shape of the provided `data` is compatible.
     pub fn check<T>(&self, data: &[T]) -> Result {
         if self.capacity != data.len() {
-            let message = format!(
-                "TODO: incompatible shape. Capacity = {}, Length = {}", 
-                self.capacity, 
-                data.len());
-            let kind = ErrorKind::IncompatibleShape;
-            let e = Error::new(kind, message);
-
-            return Err(e);
+            let mismatch = ShapeMismatch { expected: self.clone(), actual: data.len().into() };
+            return Err(Error::new(ErrorKind::IncompatibleShape, mismatch));
         }
 
         Ok(())
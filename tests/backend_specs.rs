@@ -9,13 +9,19 @@ mod backend_spec {
 
         #[test]
         fn it_can_create_default_backend() {
-            let backend: Result<Backend, _> = Backend::new::<Native>();
+            let backend: Result<Backend, _> = Backend::new::<Native<()>>();
             assert!(backend.is_ok());
         }
 
+        #[test]
+        fn it_can_create_backend_with_automatic_framework_selection() {
+            let backend: Backend = Backend::default();
+            assert!(!backend.selection().is_empty());
+        }
+
         #[test]
         fn it_can_use_ibackend_trait_object() {
-            let backend: Rc<Backend> = Rc::new(Backend::new::<Native>().unwrap());
+            let backend: Rc<Backend> = Rc::new(Backend::new::<Native<()>>().unwrap());
             use_ibackend(backend);
         }
 
@@ -24,6 +30,77 @@ mod backend_spec {
         }
     }
 
+    mod any_backend {
+        use parenchyma::backend::AnyBackend;
+        use parenchyma::frameworks::OpenCLMemory;
+        use parenchyma::prelude::*;
+        use parenchyma::tensor;
+
+        #[test]
+        fn it_switches_frameworks_at_runtime_keeping_tensors() {
+            let mut backend: AnyBackend = AnyBackend::default();
+            let x: SharedTensor = SharedTensor::with([3], vec![1., 2., 3.]).unwrap();
+            let y: SharedTensor<f64> = SharedTensor::with([1], vec![4.]).unwrap();
+
+            let native = BackendConfig::new().framework("native").unwrap();
+            backend.switch(&native).unwrap();
+
+            assert_eq!(backend.framework().name(), "native/host");
+            assert_eq!(x.as_slice().unwrap(), &[1., 2., 3.]);
+            assert_eq!(y.as_slice().unwrap(), &[4.]);
+        }
+
+        #[test]
+        fn it_moves_tensors_off_the_devices_of_the_previous_backend() {
+            let opencl = BackendConfig::new().framework("opencl").unwrap();
+            let mut backend: AnyBackend = AnyBackend::new(&opencl).unwrap();
+            let mut x: SharedTensor = SharedTensor::with([3], vec![1., 2., 3.]).unwrap();
+
+            // the copy on the Open CL device becomes the only up-to-date one
+            let _: &mut OpenCLMemory<f32> =
+                tensor::mut_reference(&mut x, backend.active_device()).unwrap();
+
+            let native = BackendConfig::new().framework("native").unwrap();
+            backend.switch(&native).unwrap();
+
+            assert_eq!(x.as_slice().unwrap(), &[1., 2., 3.]);
+        }
+
+        #[test]
+        fn it_keeps_the_backend_if_the_new_one_fails() {
+            let mut backend: AnyBackend = AnyBackend::default();
+            let name = backend.framework().name();
+
+            let invalid = BackendConfig::new().framework("native").unwrap().platform(7);
+            assert!(backend.switch(&invalid).is_err());
+            assert_eq!(backend.framework().name(), name);
+        }
+    }
+
+    mod open_cl_platforms {
+        use parenchyma::backend::Backend;
+        use parenchyma::error::ErrorKind;
+        use parenchyma::frameworks::OpenCL;
+        use parenchyma::prelude::*;
+
+        #[test]
+        fn it_selects_hardware_of_a_single_platform_by_default() {
+            let framework: OpenCL<()> = OpenCL::new().unwrap();
+            let selection = framework.default_selection();
+            assert!(selection.iter().all(|h| h.platform == selection[0].platform));
+        }
+
+        #[test]
+        fn it_rejects_a_selection_mixing_platforms() {
+            let framework: OpenCL<()> = OpenCL::new().unwrap();
+            let mut other = framework.hardware()[0].clone();
+            other.platform += 1;
+            let selection = vec![framework.hardware()[0].clone(), other];
+            let error = Backend::<()>::with(framework, selection).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidSelection);
+        }
+    }
+
     // #[cfg(feature = "cuda")]
     // mod cuda {
     //     use co::*;
@@ -0,0 +1,65 @@
+extern crate parenchyma;
+
+#[cfg(test)]
+mod batch_spec {
+    use parenchyma::compute_device::partition;
+    use parenchyma::error::ErrorKind;
+    use parenchyma::frameworks::{HOST, MockDevice, MockMemory};
+    use parenchyma::prelude::*;
+    use parenchyma::tensor;
+
+    #[test]
+    fn it_partitions_evenly() {
+        assert_eq!(partition(10, 3), vec![4, 3, 3]);
+        assert_eq!(partition(2, 4), vec![1, 1, 0, 0]);
+        assert_eq!(partition(8, 2), vec![4, 4]);
+    }
+
+    #[test]
+    fn it_splits_and_joins_along_the_batch_axis() {
+        let data: Vec<f32> = (0..12).map(|i| i as f32).collect();
+        let x = SharedTensor::with([4, 3], data.clone()).unwrap();
+
+        let parts = x.split_batch(&[3, 1], &[&HOST, &HOST]).unwrap();
+        assert_eq!(parts[0].shape().dimensions(), &[3, 3]);
+        assert_eq!(parts[1].shape().dimensions(), &[1, 3]);
+        assert_eq!(parts[1].as_slice().unwrap(), &[9., 10., 11.]);
+
+        let mut y: SharedTensor = unsafe { SharedTensor::uninitialized([4, 3]) };
+        y.join_batch(&parts, &HOST).unwrap();
+        assert_eq!(y.as_slice().unwrap(), &data[..]);
+    }
+
+    #[test]
+    fn it_splits_and_joins_across_devices_without_the_host() {
+        let (a, b) = (MockDevice::new(0), MockDevice::new(1));
+        let data: Vec<f32> = (0..12).map(|i| i as f32).collect();
+        let mut x = SharedTensor::with([4, 3], data.clone()).unwrap();
+
+        // make the copy of `a` the only up-to-date one
+        let _: &mut MockMemory<f32> = tensor::mut_reference(&mut x, &a).unwrap();
+        a.reset();
+
+        let parts = x.split_batch(&[3, 1], &[&a, &b]).unwrap();
+        let share: &MockMemory<f32> = tensor::reference(&parts[1], &b).unwrap();
+        assert_eq!(share.as_slice(), &[9., 10., 11.]);
+        assert_eq!(a.peer_transfers(), 2);
+
+        let mut y: SharedTensor = unsafe { SharedTensor::uninitialized([4, 3]) };
+        y.join_batch(&parts, &b).unwrap();
+        let joined: &MockMemory<f32> = tensor::reference(&y, &b).unwrap();
+        assert_eq!(joined.as_slice(), &data[..]);
+        assert_eq!((a.peer_transfers(), b.peer_transfers()), (3, 1));
+
+        for device in [&a, &b].iter() {
+            assert_eq!((device.transfers_in(), device.transfers_out()), (0, 0));
+        }
+    }
+
+    #[test]
+    fn it_fails_to_split_into_mismatched_sizes() {
+        let x: SharedTensor = SharedTensor::from([4, 3]);
+        let e = x.split_batch(&[2, 1], &[&HOST, &HOST]).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::IncompatibleShape);
+    }
+}
@@ -0,0 +1,65 @@
+extern crate parenchyma;
+
+#[cfg(test)]
+mod config_spec {
+    use parenchyma::error::ErrorKind;
+    use parenchyma::frameworks::Native;
+    use parenchyma::hardware::HardwareKind;
+    use parenchyma::prelude::*;
+    use std::env;
+
+    #[test]
+    fn it_parses_a_toml_configuration() {
+        let config: BackendConfig = BackendConfig::from_toml(r#"
+            framework = "native"
+            device_kind = "cpu"
+            device = "^Host"
+        "#).unwrap();
+
+        let backend = config.backend().unwrap();
+        assert_eq!(backend.selection()[0].kind, HardwareKind::CPU);
+    }
+
+    #[test]
+    fn it_reads_the_program_cache_directory() {
+        let config = BackendConfig::<()>::from_toml(r#"program_cache = "/tmp/parenchyma""#);
+        assert_eq!(config.unwrap(), BackendConfig::new().program_cache("/tmp/parenchyma"));
+    }
+
+    #[test]
+    fn it_rejects_unknown_keys_and_frameworks() {
+        let unknown_key = BackendConfig::<()>::from_toml("gpu = true").unwrap_err();
+        assert_eq!(unknown_key.kind(), ErrorKind::InvalidConfiguration);
+
+        let unknown_framework = BackendConfig::<()>::new().framework("metal").unwrap_err();
+        assert_eq!(unknown_framework.kind(), ErrorKind::InvalidConfiguration);
+
+        let invalid_regex = BackendConfig::<()>::new().device("(").unwrap_err();
+        assert_eq!(invalid_regex.kind(), ErrorKind::InvalidConfiguration);
+    }
+
+    #[test]
+    fn it_rejects_zero_threads() {
+        let toml = BackendConfig::<()>::from_toml("num_threads = 0").unwrap_err();
+        assert_eq!(toml.kind(), ErrorKind::InvalidConfiguration);
+
+        env::set_var("PARENCHYMA_NUM_THREADS", "0");
+        let env = BackendConfig::<()>::from_env().unwrap_err();
+        env::remove_var("PARENCHYMA_NUM_THREADS");
+        assert_eq!(env.kind(), ErrorKind::InvalidConfiguration);
+    }
+
+    #[test]
+    fn it_validates_preferences_against_the_hardware() {
+        let framework: Native<()> = Native::new().unwrap();
+
+        let gpu = BackendConfig::<()>::new().kind(HardwareKind::GPU);
+        assert_eq!(gpu.selection(&framework).unwrap_err().kind(), ErrorKind::InvalidSelection);
+
+        let platform = BackendConfig::<()>::new().platform(1);
+        assert_eq!(platform.selection(&framework).unwrap_err().kind(), ErrorKind::InvalidSelection);
+
+        let cpu = BackendConfig::<()>::new().kind(HardwareKind::CPU);
+        assert_eq!(cpu.selection(&framework).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,62 @@
+extern crate parenchyma;
+
+#[cfg(test)]
+mod error_spec {
+    use parenchyma::error::{ErrorKind, ShapeMismatch};
+    use parenchyma::prelude::*;
+    use std::error::Error;
+
+    #[test]
+    fn it_carries_the_expected_and_actual_shapes() {
+        let mut x: SharedTensor = SharedTensor::from([2, 3]);
+        let e = x.reshape([4, 2]).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidReshapedTensorSize);
+
+        let mismatch = e.get_ref().unwrap().downcast_ref::<ShapeMismatch>().unwrap();
+        assert_eq!(mismatch.expected, TensorShape::from([2, 3]));
+        assert_eq!(mismatch.actual, TensorShape::from([4, 2]));
+    }
+
+    #[test]
+    fn it_chains_the_inner_error_as_the_source() {
+        let e = TensorShape::from([2, 2]).check(&[1., 2., 3.]).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::IncompatibleShape);
+        assert!(e.source().unwrap().is::<ShapeMismatch>());
+        assert_eq!(e.to_string(), "the tensor shape is incompatible with the shape of the data");
+        assert_eq!(
+            e.source().unwrap().to_string(), 
+            "expected [2, 2] (4 elements), got [3] (3 elements)");
+    }
+
+    #[test]
+    fn it_has_no_source_without_an_inner_error() {
+        let e: parenchyma::error::Error = ErrorKind::UninitializedMemory.into();
+        assert!(e.source().is_none());
+        assert_eq!(e.to_string(), "uninitialized memory");
+    }
+
+    mod open_cl {
+        use parenchyma::context::ContextCtor;
+        use parenchyma::error::{ErrorKind, ProgramBuildError};
+        use parenchyma::frameworks::{OpenCL, OpenCLContext};
+        use parenchyma::prelude::*;
+        use std::error::Error;
+        use std::ffi::CString;
+
+        #[test]
+        fn it_returns_the_build_log_of_each_device() {
+            let framework: OpenCL<()> = OpenCL::new().unwrap();
+            let selection = framework.default_selection();
+            let context = OpenCLContext::new(&framework, &selection).unwrap();
+
+            let source = CString::new("__kernel void broken(__global float* x) { x[0] = y; }");
+            let e = context.program(vec![source.unwrap()]).unwrap_err();
+            assert_eq!(e.kind(), ErrorKind::ProgramBuild);
+
+            let build_error = e.get_ref().unwrap().downcast_ref::<ProgramBuildError>().unwrap();
+            assert_eq!(build_error.logs.len(), selection.len());
+            assert!(build_error.logs.iter().all(|&(_, ref log)| !log.is_empty()));
+            assert!(build_error.source().is_some());
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! A plugin registering a `saxpy` kernel and a `fail` kernel returning an error code.
+
+#[macro_use]
+extern crate parenchyma;
+
+use parenchyma::plugin::{PluginArgs, PluginKernel, PluginRegistrar, FAILURE, SUCCESS};
+use std::ptr;
+
+declare_plugin!("test-plugin", register);
+
+const SAXPY: &'static str = "
+    __kernel void saxpy(float a, __global const float* x, __global float* y) {
+        y[get_global_id(0)] += a * x[get_global_id(0)];
+    }\0";
+
+/// The error code returned by the `fail` kernel.
+const FAIL_CODE: i32 = 7;
+
+unsafe extern "C" fn register(registrar: *mut PluginRegistrar) -> i32 {
+    let kernels = [
+        PluginKernel {
+            name: b"saxpy\0".as_ptr() as *const _,
+            opencl: SAXPY.as_ptr() as *const _,
+            native: Some(saxpy),
+        },
+        PluginKernel {
+            name: b"fail\0".as_ptr() as *const _,
+            opencl: ptr::null(),
+            native: Some(fail),
+        },
+    ];
+
+    for kernel in kernels.iter() {
+        if (*registrar).register(kernel) != SUCCESS {
+            return FAILURE;
+        }
+    }
+
+    SUCCESS
+}
+
+unsafe extern "C" fn saxpy(args: *mut PluginArgs) -> i32 {
+    let args = &mut *args;
+
+    match (args.scalar(0), args.input(0), args.output(0)) {
+        (Some(a), Some(x), Some(y)) => {
+            for (y, x) in y.iter_mut().zip(x) {
+                *y += a as f32 * x;
+            }
+
+            SUCCESS
+        },
+        _ => FAILURE,
+    }
+}
+
+unsafe extern "C" fn fail(_: *mut PluginArgs) -> i32 {
+    FAIL_CODE
+}
@@ -7,7 +7,17 @@ mod framework_native_spec {
 
     #[test]
     fn it_works() {
-        let framework: Native = Native::new().unwrap();
+        let framework: Native<()> = Native::new().unwrap();
         assert_eq!(framework.hardware().len(), 1);
     }
+
+    #[test]
+    fn it_describes_the_host_cpu() {
+        let framework: Native<()> = Native::new().unwrap();
+        let cpu = &framework.hardware()[0];
+        assert!(cpu.compute_units >= 1);
+        assert!(cpu.fp64);
+        #[cfg(target_arch = "x86_64")]
+        assert!(cpu.supports("sse2"));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,92 @@
+extern crate parenchyma;
+
+#[cfg(test)]
+mod initializer_spec {
+    mod native {
+        use parenchyma::frameworks::HOST;
+        use parenchyma::prelude::*;
+        use parenchyma::tensor::Initializer;
+
+        fn initialized(shape: [usize; 2], initializer: Initializer, seed: u64) -> Vec<f32> {
+            let mut tensor: SharedTensor = SharedTensor::from(shape);
+            tensor.initialize(&HOST, initializer, seed).unwrap();
+            tensor.as_slice().unwrap().to_vec()
+        }
+
+        #[test]
+        fn it_is_deterministic_for_a_seed() {
+            let initializer = Initializer::Normal { mean: 0.0, std: 1.0 };
+            assert_eq!(initialized([16, 16], initializer, 7), initialized([16, 16], initializer, 7));
+            assert!(initialized([16, 16], initializer, 7) != initialized([16, 16], initializer, 8));
+        }
+
+        #[test]
+        fn it_does_not_depend_on_the_shape() {
+            let initializer = Initializer::Uniform { low: 0.0, high: 1.0 };
+            let a = initialized([4, 64], initializer, 1);
+            let b = initialized([64, 4], initializer, 1);
+            assert_eq!(a, b);
+        }
+
+        #[test]
+        fn it_samples_uniform_values_within_bounds() {
+            let values = initialized([32, 32], Initializer::Uniform { low: -2.0, high: 3.0 }, 3);
+            assert!(values.iter().all(|&v| v >= -2.0 && v < 3.0));
+        }
+
+        #[test]
+        fn it_truncates_normal_values() {
+            let initializer = Initializer::TruncatedNormal { mean: 1.0, std: 0.5 };
+            let values = initialized([64, 64], initializer, 11);
+            assert!(values.iter().all(|&v| v >= 0.0 && v <= 2.0));
+        }
+
+        #[test]
+        fn it_scales_xavier_uniform_by_the_fans() {
+            let limit = (6.0f32 / (100.0 + 50.0)).sqrt();
+            let values = initialized([50, 100], Initializer::XavierUniform, 5);
+            assert!(values.iter().all(|&v| v.abs() <= limit));
+        }
+
+        #[test]
+        fn it_scales_he_normal_by_the_fan_in() {
+            let values = initialized([256, 128], Initializer::HeNormal, 5);
+            let n = values.len() as f32;
+            let mean = values.iter().sum::<f32>() / n;
+            let std = (values.iter().map(|v| (v - mean) * (v - mean)).sum::<f32>() / n).sqrt();
+            assert!(mean.abs() < 0.01);
+            assert!((std - (2.0f32 / 128.0).sqrt()).abs() < 0.01);
+        }
+    }
+
+    mod opencl {
+        use parenchyma::frameworks::{HOST, OpenCL};
+        use parenchyma::prelude::*;
+        use parenchyma::tensor::Initializer;
+
+        fn compare(initializer: Initializer, tolerance: f32) {
+            let backend: Backend = Backend::new::<OpenCL<_>>().unwrap();
+
+            let mut device: SharedTensor = SharedTensor::from([32, 32]);
+            device.initialize(backend.active_device(), initializer, 42).unwrap();
+
+            let mut host: SharedTensor = SharedTensor::from([32, 32]);
+            host.initialize(&HOST, initializer, 42).unwrap();
+
+            for (a, b) in device.as_slice().unwrap().iter().zip(host.as_slice().unwrap()) {
+                assert!((a - b).abs() <= tolerance, "{} != {}", a, b);
+            }
+        }
+
+        #[test]
+        fn it_matches_native_uniform_values() {
+            compare(Initializer::Uniform { low: -1.0, high: 1.0 }, 0.0);
+        }
+
+        #[test]
+        fn it_matches_native_normal_values() {
+            compare(Initializer::Normal { mean: 0.0, std: 1.0 }, 1e-5);
+            compare(Initializer::TruncatedNormal { mean: 0.0, std: 1.0 }, 1e-5);
+        }
+    }
+}
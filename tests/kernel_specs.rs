@@ -0,0 +1,88 @@
+extern crate parenchyma;
+
+#[cfg(test)]
+mod kernel_spec {
+    use parenchyma::error::ErrorKind;
+    use parenchyma::frameworks::{Mock, Native, OpenCL};
+    use parenchyma::kernel::CustomKernel;
+    use parenchyma::prelude::*;
+
+    fn saxpy() -> CustomKernel {
+        CustomKernel::new("saxpy")
+            .opencl("
+                __kernel void saxpy(float a, __global const float* x, __global float* y) {
+                    const size_t i = get_global_id(0);
+                    y[i] += a * x[i];
+                }")
+            .native(|args| {
+                let a = args.scalar(0)?.to_f32();
+                let x = args.input(0)?;
+
+                for (y, x) in args.output(0)?.iter_mut().zip(x) {
+                    *y += a * x;
+                }
+
+                Ok(())
+            })
+    }
+
+    fn launch_saxpy(backend: &Backend) -> Vec<f32> {
+        let ref x: SharedTensor = SharedTensor::with([4], vec![1., 2., 3., 4.]).unwrap();
+        let ref mut y: SharedTensor = SharedTensor::with([4], vec![1., 1., 1., 1.]).unwrap();
+        backend.launch("saxpy", &mut [2.0f32.into(), x.into(), y.into()]).unwrap();
+        y.as_slice().unwrap().to_vec()
+    }
+
+    #[test]
+    fn it_launches_custom_kernels_natively() {
+        let mut backend: Backend = Backend::new::<Native<()>>().unwrap();
+        backend.register_kernel(saxpy()).unwrap();
+        assert_eq!(launch_saxpy(&backend), vec![3., 5., 7., 9.]);
+    }
+
+    #[test]
+    fn it_launches_custom_kernels_on_opencl() {
+        let mut backend: Backend = Backend::new::<OpenCL<_>>().unwrap();
+        backend.register_kernel(saxpy()).unwrap();
+        assert_eq!(launch_saxpy(&backend), vec![3., 5., 7., 9.]);
+    }
+
+    #[test]
+    fn it_runs_kernels_without_an_opencl_source_on_the_host() {
+        let mut backend: Backend = Backend::new::<OpenCL<_>>().unwrap();
+        let native_only = CustomKernel::new("fill").native(|args| {
+            let value = args.scalar(0)?.to_f32();
+
+            for y in args.output(0)?.iter_mut() {
+                *y = value;
+            }
+
+            Ok(())
+        });
+
+        backend.register_kernel(native_only).unwrap();
+
+        let ref mut y: SharedTensor = SharedTensor::from([3]);
+        backend.launch("fill", &mut [7.0f32.into(), y.into()]).unwrap();
+        assert_eq!(y.as_slice().unwrap(), &[7., 7., 7.]);
+    }
+
+    #[test]
+    fn it_rejects_unknown_and_unimplemented_kernels() {
+        let mut backend: Backend = Backend::new::<Native<()>>().unwrap();
+        let ref mut y: SharedTensor = SharedTensor::from([3]);
+
+        let e = backend.launch("fill", &mut [y.into()]).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::Other);
+
+        let e = backend.register_kernel(CustomKernel::new("fill").opencl("")).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidConfiguration);
+
+        let mut mock: Backend = Backend::new::<Mock>().unwrap();
+        let e = mock.register_kernel(saxpy()).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::Unsupported {
+            package: "parenchyma/kernel",
+            operation: "register_kernel",
+        });
+    }
+}
@@ -0,0 +1,138 @@
+extern crate parenchyma;
+
+#[cfg(test)]
+mod mock_spec {
+    use parenchyma::error::ErrorKind;
+    use parenchyma::frameworks::{Mock, MockDevice, MockMemory};
+    use parenchyma::prelude::*;
+    use parenchyma::tensor;
+
+    fn tensor() -> SharedTensor {
+        SharedTensor::with([2, 2], vec![1., 2., 3., 4.]).unwrap()
+    }
+
+    #[test]
+    fn it_can_create_a_mock_backend() {
+        let backend: Backend = Backend::new::<Mock>().unwrap();
+        assert_eq!(backend.selection().len(), Mock::<()>::DEVICES);
+        assert!(backend.active_device().downcast_ref::<MockDevice>().is_some());
+    }
+
+    #[test]
+    fn it_routes_host_to_device_transfers_through_transfer_in() {
+        let device = MockDevice::new(0);
+        let x = tensor();
+
+        let memory: &MockMemory<f32> = tensor::reference(&x, &device).unwrap();
+        assert_eq!(memory.as_slice(), &[1., 2., 3., 4.]);
+        assert_eq!((device.allocations(), device.transfers_in(), device.transfers_out()), (1, 1, 0));
+    }
+
+    #[test]
+    fn it_routes_device_to_host_transfers_through_transfer_out() {
+        let device = MockDevice::new(0);
+        let mut x = tensor();
+
+        {
+            let memory: &mut MockMemory<f32> = tensor::mut_reference(&mut x, &device).unwrap();
+            assert_eq!(memory.as_slice(), &[1., 2., 3., 4.]);
+        }
+
+        x.as_slice().unwrap();
+        assert_eq!((device.transfers_in(), device.transfers_out()), (1, 1));
+    }
+
+    #[test]
+    fn it_skips_transfers_for_synchronized_copies() {
+        let device = MockDevice::new(0);
+        let x = tensor();
+
+        let _: &MockMemory<f32> = tensor::reference(&x, &device).unwrap();
+        let _: &MockMemory<f32> = tensor::reference(&x, &device).unwrap();
+        x.as_slice().unwrap();
+
+        assert_eq!((device.allocations(), device.transfers_in(), device.transfers_out()), (1, 1, 0));
+    }
+
+    #[test]
+    fn it_transfers_between_devices_directly() {
+        let (a, b) = (MockDevice::new(0), MockDevice::new(1));
+        let mut x = tensor();
+
+        let _: &mut MockMemory<f32> = tensor::mut_reference(&mut x, &a).unwrap();
+        let memory: &MockMemory<f32> = tensor::reference(&x, &b).unwrap();
+
+        assert_eq!(memory.as_slice(), &[1., 2., 3., 4.]);
+        assert_eq!(a.peer_transfers() + b.peer_transfers(), 1);
+        assert_eq!(b.transfers_in(), 0);
+    }
+
+    #[test]
+    fn it_moves_tensors_off_retired_devices_through_the_host() {
+        let (a, b) = (MockDevice::new(0), MockDevice::new(1));
+        let mut x = tensor();
+
+        let _: &mut MockMemory<f32> = tensor::mut_reference(&mut x, &a).unwrap();
+        a.retire();
+
+        let memory: &MockMemory<f32> = tensor::reference(&x, &b).unwrap();
+        assert_eq!(memory.as_slice(), &[1., 2., 3., 4.]);
+        assert_eq!((a.transfers_out(), b.transfers_in()), (1, 1));
+        assert_eq!(a.peer_transfers() + b.peer_transfers(), 0);
+
+        x.as_slice().unwrap();
+        assert_eq!(a.transfers_out(), 1);
+    }
+
+    #[test]
+    fn it_reports_injected_allocation_failures() {
+        let device = MockDevice::new(0);
+        device.fail_allocations(true);
+
+        let x = tensor();
+        let result: Result<&MockMemory<f32>, _> = tensor::reference(&x, &device);
+        assert_eq!(result.err().unwrap().kind(), ErrorKind::MemoryAllocationFailed);
+    }
+
+    #[test]
+    fn it_reports_injected_transfer_failures() {
+        let device = MockDevice::new(0);
+        device.fail_transfers(true);
+
+        let x = tensor();
+        let result: Result<&MockMemory<f32>, _> = tensor::reference(&x, &device);
+        assert_eq!(result.err().unwrap().kind(), ErrorKind::MemorySynchronizationFailed);
+    }
+
+    #[test]
+    fn it_reports_missing_routes() {
+        let device = MockDevice::new(0);
+        device.set_routes(false, true);
+
+        let x = tensor();
+        let result: Result<&MockMemory<f32>, _> = tensor::reference(&x, &device);
+        assert_eq!(result.err().unwrap().kind(), ErrorKind::NoAvailableSynchronizationRouteFound);
+    }
+
+    #[test]
+    fn it_fails_to_read_uninitialized_memory() {
+        let device = MockDevice::new(0);
+        let x: SharedTensor = unsafe { SharedTensor::uninitialized([2]) };
+
+        let result: Result<&MockMemory<f32>, _> = tensor::reference(&x, &device);
+        assert_eq!(result.err().unwrap().kind(), ErrorKind::UninitializedMemory);
+        assert_eq!(device.allocations(), 0);
+    }
+
+    #[test]
+    fn it_exceeds_the_capacity_of_the_tensor_map() {
+        let x = tensor();
+
+        for i in 0..63 {
+            let _: &MockMemory<f32> = tensor::reference(&x, &MockDevice::new(i)).unwrap();
+        }
+
+        let result: Result<&MockMemory<f32>, _> = tensor::reference(&x, &MockDevice::new(63));
+        assert_eq!(result.err().unwrap().kind(), ErrorKind::CapacityExceeded);
+    }
+}
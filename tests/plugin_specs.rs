@@ -0,0 +1,87 @@
+#[macro_use]
+extern crate lazy_static;
+extern crate parenchyma;
+
+#[cfg(test)]
+mod plugin_spec {
+    use parenchyma::error::ErrorKind;
+    use parenchyma::frameworks::Native;
+    use parenchyma::plugin::{self, Plugin};
+    use parenchyma::prelude::*;
+    use std::env;
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+
+    lazy_static! {
+        static ref FIXTURE: PathBuf = build_fixture();
+    }
+
+    /// Builds the plugin of `tests/fixtures/plugin` and returns the path of its library.
+    fn build_fixture() -> PathBuf {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let target = root.join("target").join("plugin-fixture");
+        let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+
+        let status = Command::new(cargo)
+            .arg("build")
+            .arg("--manifest-path").arg(root.join("tests/fixtures/plugin/Cargo.toml"))
+            .arg("--target-dir").arg(&target)
+            .status()
+            .expect("failed to run cargo");
+
+        assert!(status.success(), "failed to build the plugin fixture");
+
+        let library = format!(
+            "{}parenchyma_test_plugin{}", env::consts::DLL_PREFIX, env::consts::DLL_SUFFIX);
+        target.join("debug").join(library)
+    }
+
+    #[test]
+    fn it_accepts_semver_compatible_core_versions() {
+        assert!(plugin::is_compatible("0.0.4", "0.0.4"));
+        assert!(plugin::is_compatible("0.3.1", "0.3.0"));
+        assert!(plugin::is_compatible("1.2.0", "1.0.7"));
+        assert!(plugin::is_compatible("1.2.0-beta", "1.2.0"));
+    }
+
+    #[test]
+    fn it_rejects_incompatible_core_versions() {
+        assert!(!plugin::is_compatible("0.0.4", "0.0.5"));
+        assert!(!plugin::is_compatible("0.3.1", "0.4.1"));
+        assert!(!plugin::is_compatible("1.2.0", "2.2.0"));
+        assert!(!plugin::is_compatible("0.0.4", "an unknown version"));
+        assert!(!plugin::is_compatible("0.0.4", "0.0.4.1"));
+    }
+
+    #[test]
+    fn it_fails_to_load_a_missing_library() {
+        let path = env::temp_dir().join("parenchyma-missing-plugin.so");
+        let e = Plugin::load(&path).err().unwrap();
+        assert_eq!(e.kind(), ErrorKind::Plugin);
+    }
+
+    #[test]
+    fn it_loads_the_kernels_of_a_declared_plugin() {
+        let plugin = Plugin::load(&*FIXTURE).unwrap();
+        assert_eq!(plugin.name(), "test-plugin");
+
+        let names: Vec<_> = plugin.kernels().iter().map(|kernel| kernel.name()).collect();
+        assert_eq!(names, vec!["saxpy", "fail"]);
+    }
+
+    #[test]
+    fn it_launches_the_native_kernels_of_a_plugin() {
+        let plugin = Plugin::load(&*FIXTURE).unwrap();
+        let mut backend: Backend = Backend::new::<Native<()>>().unwrap();
+        backend.register_plugin(&plugin).unwrap();
+
+        let ref x: SharedTensor = SharedTensor::with([4], vec![1., 2., 3., 4.]).unwrap();
+        let ref mut y: SharedTensor = SharedTensor::with([4], vec![1., 1., 1., 1.]).unwrap();
+        backend.launch("saxpy", &mut [2.0f32.into(), x.into(), y.into()]).unwrap();
+        assert_eq!(y.as_slice().unwrap(), &[3., 5., 7., 9.]);
+
+        let e = backend.launch("fail", &mut [y.into()]).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::Other);
+        assert!(e.get_ref().unwrap().to_string().contains("(code 7)"));
+    }
+}
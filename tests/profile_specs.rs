@@ -0,0 +1,90 @@
+extern crate parenchyma;
+
+#[cfg(test)]
+mod profile_spec {
+    use parenchyma::profile::{EventCategory, Profile, ProfileEvent};
+
+    fn event(op: &str, category: EventCategory, queued: u64, start: u64, end: u64) -> ProfileEvent {
+        ProfileEvent { op: op.to_string(), category, device: 0, queued, submit: queued, start, end }
+    }
+
+    fn profile() -> Profile {
+        Profile {
+            events: vec![
+                event("write", EventCategory::Transfer, 1_000, 1_500, 4_500),
+                event("sigmoid_float", EventCategory::Kernel, 5_000, 6_000, 7_000),
+                event("sigmoid_float", EventCategory::Kernel, 8_000, 8_000, 11_000),
+                event("read", EventCategory::Transfer, 12_000, 12_000, 12_500),
+            ]
+        }
+    }
+
+    #[test]
+    fn it_summarizes_the_events_per_operation() {
+        let summary = profile().summary();
+        assert_eq!(summary.len(), 3);
+
+        assert_eq!(summary[0].op, "sigmoid_float");
+        assert_eq!(summary[0].calls, 2);
+        assert_eq!(summary[0].total, 4_000);
+        assert_eq!(summary[0].min, 1_000);
+        assert_eq!(summary[0].max, 3_000);
+        assert_eq!(summary[0].latency, 1_000);
+
+        assert_eq!(summary[1].op, "write");
+        assert_eq!(summary[2].op, "read");
+    }
+
+    #[test]
+    fn it_totals_the_categories() {
+        let profile = profile();
+        assert_eq!(profile.total(EventCategory::Kernel), 4_000);
+        assert_eq!(profile.total(EventCategory::Transfer), 3_500);
+        assert!(format!("{}", profile).contains("sigmoid_float"));
+    }
+
+    #[test]
+    fn it_exports_chrome_trace_events() {
+        let trace = profile().to_chrome_trace();
+        assert!(trace.starts_with("{\"traceEvents\":["));
+        assert!(trace.contains(
+            "{\"name\":\"write\",\"cat\":\"transfer\",\"ph\":\"X\",\"pid\":0,\"tid\":0,\
+            \"ts\":0.500,\"dur\":3.000,\"args\":{\"queued\":0.000,\"submit\":0.000}}"));
+        assert_eq!(trace.matches("\"ph\":\"X\"").count(), 4);
+    }
+
+    #[test]
+    fn it_escapes_operation_names() {
+        let profile = Profile { events: vec![event("a\"b", EventCategory::Kernel, 0, 0, 1)] };
+        assert!(profile.to_chrome_trace().contains("\"name\":\"a\\\"b\""));
+    }
+
+    mod open_cl {
+        use parenchyma::backend::Backend;
+        use parenchyma::frameworks::{OpenCL, OpenCLMemory};
+        use parenchyma::prelude::*;
+        use parenchyma::profile::EventCategory;
+        use parenchyma::tensor;
+
+        #[test]
+        fn it_records_transfers_once_enabled() {
+            let mut backend: Backend = Backend::new::<OpenCL<()>>().unwrap();
+            let mut x: SharedTensor = SharedTensor::with([4], vec![1., 2., 3., 4.]).unwrap();
+
+            backend.set_profiling(true).unwrap();
+            let _: &OpenCLMemory<f32> = tensor::reference(&x, backend.active_device()).unwrap();
+            let _: &mut OpenCLMemory<f32> =
+                tensor::mut_reference(&mut x, backend.active_device()).unwrap();
+            assert_eq!(x.as_slice().unwrap(), &[1., 2., 3., 4.]);
+
+            let profile = backend.profile().unwrap();
+            let ops: Vec<_> = profile.events.iter().map(|e| &e.op[..]).collect();
+            assert_eq!(ops, vec!["read access", "read access"]);
+            assert!(profile.events.iter().all(|e| e.category == EventCategory::Transfer));
+            assert!(profile.events.iter().all(|e| e.queued <= e.start && e.start <= e.end));
+
+            backend.reset_profile();
+            assert!(backend.profile().unwrap().events.is_empty());
+        }
+    }
+}
@@ -0,0 +1,99 @@
+extern crate parenchyma;
+
+#[cfg(test)]
+mod program_builder_spec {
+    use parenchyma::context::ContextCtor;
+    use parenchyma::error::ErrorKind;
+    use parenchyma::frameworks::{OpenCL, OpenCLAccess, OpenCLContext, OpenCLKernels, OpenCLMemory};
+    use parenchyma::prelude::*;
+    use parenchyma::tensor;
+
+    fn context(options: &str) -> OpenCLContext<()> {
+        let mut framework: OpenCL<()> = OpenCL::new().unwrap();
+        framework.set_compiler_options(options);
+        OpenCLContext::new(&framework, &framework.default_selection()).unwrap()
+    }
+
+    #[test]
+    fn it_appends_the_defines_and_options_to_the_framework_options() {
+        let context = context("-cl-mad-enable");
+        let builder = context.program_builder()
+            .define("WGS", 64)
+            .define("VW", 2)
+            .define("WGS", 32)
+            .option("-cl-fast-relaxed-math");
+
+        assert_eq!(
+            builder.compiler_options(), 
+            "-cl-mad-enable -D VW=2 -D WGS=32 -cl-fast-relaxed-math");
+    }
+
+    #[test]
+    fn it_rejects_invalid_macro_names() {
+        let e = context("").program_builder().define("2X", 1).build().unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidConfiguration);
+    }
+
+    #[test]
+    fn it_compiles_the_sources_with_the_defines() {
+        let context = context("");
+        let program = context.program_builder()
+            .src("__kernel void fill(__global float* x) { x[get_global_id(0)] = VALUE; }")
+            .define("VALUE", "4.0f")
+            .build()
+            .unwrap();
+
+        let mut x: SharedTensor = SharedTensor::from([4]);
+
+        {
+            let memory: &mut OpenCLMemory<f32> = 
+                tensor::mut_reference(&mut x, context.device()).unwrap();
+            let kernels = OpenCLKernels::new(&program, context.devices(), &["fill"]).unwrap();
+            let kernel = kernels.get(context.device(), "fill").unwrap().arg_buf(&*memory).gws([4]);
+            let accesses = [OpenCLAccess::write(memory)];
+            unsafe { context.device().enqueue("fill", &kernel, &accesses).unwrap(); }
+        }
+
+        assert_eq!(x.as_slice().unwrap(), &[4., 4., 4., 4.]);
+    }
+
+    #[test]
+    fn it_reuses_the_kernels_of_a_device() {
+        let context = context("");
+        let program = context.program_builder()
+            .src("__kernel void fill(__global float* x, float v) { x[get_global_id(0)] = v; }")
+            .build()
+            .unwrap();
+        let kernels = OpenCLKernels::new(&program, context.devices(), &["fill"]).unwrap();
+
+        let fill = |tensor: &mut SharedTensor, value: f32| {
+            let memory: &mut OpenCLMemory<f32> = 
+                tensor::mut_reference(tensor, context.device()).unwrap();
+            let kernel = kernels.get(context.device(), "fill").unwrap()
+                .arg_buf(&*memory)
+                .arg_scl(value)
+                .gws([2]);
+            let accesses = [OpenCLAccess::write(memory)];
+            unsafe { context.device().enqueue("fill", &kernel, &accesses).unwrap(); }
+        };
+
+        let mut x: SharedTensor = SharedTensor::from([2]);
+        let mut y: SharedTensor = SharedTensor::from([2]);
+        fill(&mut x, 1.);
+        fill(&mut y, 2.);
+
+        assert_eq!(x.as_slice().unwrap(), &[1., 1.]);
+        assert_eq!(y.as_slice().unwrap(), &[2., 2.]);
+        assert_eq!(kernels.created(context.device(), "fill"), 1);
+
+        // a kernel held by a call isn't handed out twice
+        let first = kernels.get(context.device(), "fill").unwrap();
+        let second = kernels.get(context.device(), "fill").unwrap();
+        assert_eq!(kernels.created(context.device(), "fill"), 2);
+        drop((first, second));
+
+        kernels.get(context.device(), "fill").unwrap();
+        assert_eq!(kernels.created(context.device(), "fill"), 2);
+        assert_eq!(kernels.get(context.device(), "drain").unwrap_err().kind(), ErrorKind::Other);
+    }
+}
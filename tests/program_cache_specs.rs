@@ -0,0 +1,86 @@
+extern crate parenchyma;
+
+#[cfg(test)]
+mod program_cache_spec {
+    use parenchyma::context::ContextCtor;
+    use parenchyma::frameworks::{OpenCL, OpenCLContext};
+    use parenchyma::prelude::*;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, SystemTime};
+    use std::{env, process, thread};
+
+    fn directory(name: &str) -> PathBuf {
+        let directory = env::temp_dir().join(format!("parenchyma-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        directory
+    }
+
+    fn entries(directory: &Path) -> Vec<PathBuf> {
+        let mut entries: Vec<_> = fs::read_dir(directory).unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        entries.sort();
+        entries
+    }
+
+    fn modified(paths: &[PathBuf]) -> Vec<SystemTime> {
+        paths.iter().map(|path| fs::metadata(path).unwrap().modified().unwrap()).collect()
+    }
+
+    fn context(framework: &OpenCL<()>) -> OpenCLContext<()> {
+        OpenCLContext::new(framework, &framework.default_selection()).unwrap()
+    }
+
+    #[test]
+    fn it_stores_a_binary_per_device_and_reuses_it() {
+        let directory = directory("cache-reuse");
+        let mut framework: OpenCL<()> = OpenCL::new().unwrap();
+        framework.set_program_cache(Some(directory.clone()));
+
+        context(&framework);
+        let stored = entries(&directory);
+        assert_eq!(stored.len(), framework.default_selection().len());
+        assert!(stored.iter().all(|path| path.extension().unwrap() == "bin"));
+
+        let stored_at = modified(&stored);
+        let contents: Vec<_> = stored.iter().map(|path| fs::read(path).unwrap()).collect();
+
+        // a rebuild would store the binaries again
+        thread::sleep(Duration::from_millis(50));
+        context(&framework);
+        assert_eq!(entries(&directory), stored);
+        assert_eq!(modified(&stored), stored_at);
+        for (path, contents) in stored.iter().zip(&contents) {
+            assert_eq!(&fs::read(path).unwrap(), contents);
+        }
+
+        // changing the compiler options misses the cache
+        framework.set_compiler_options("-cl-fast-relaxed-math");
+        context(&framework);
+        assert_eq!(entries(&directory).len(), 2 * stored.len());
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn it_rebuilds_corrupt_entries_from_source() {
+        let directory = directory("cache-corrupt");
+        let mut framework: OpenCL<()> = OpenCL::new().unwrap();
+        framework.set_program_cache(Some(directory.clone()));
+
+        context(&framework);
+
+        for path in entries(&directory) {
+            fs::write(&path, b"garbage").unwrap();
+        }
+
+        context(&framework);
+
+        for path in entries(&directory) {
+            assert!(fs::metadata(&path).unwrap().len() > 7);
+        }
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,84 @@
+extern crate parenchyma;
+
+#[cfg(test)]
+mod stats_spec {
+    use parenchyma::frameworks::{Mock, MockDevice, MockMemory, Native};
+    use parenchyma::prelude::*;
+    use parenchyma::stats::{DevicePair, Route, TransferCount};
+    use parenchyma::tensor;
+
+    fn tensor() -> SharedTensor {
+        SharedTensor::with([2, 2], vec![1., 2., 3., 4.]).unwrap()
+    }
+
+    #[test]
+    fn it_counts_transfers_by_direction() {
+        let backend: Backend = Backend::new::<Mock>().unwrap();
+        let device = backend.active_device();
+        let mut x = tensor();
+
+        let _: &mut MockMemory<f32> = tensor::mut_reference(&mut x, device).unwrap();
+        x.as_slice().unwrap();
+        x.as_slice().unwrap();
+
+        let stats = backend.transfer_stats();
+        assert_eq!(stats.route(Route::HostToDevice), TransferCount { transfers: 1, bytes: 16 });
+        assert_eq!(stats.route(Route::DeviceToHost), TransferCount { transfers: 1, bytes: 16 });
+        assert_eq!(stats.total().transfers, 2);
+    }
+
+    #[test]
+    fn it_splits_the_counters_by_device_pair() {
+        let (a, b) = (MockDevice::new(0), MockDevice::new(1));
+        let mut x = tensor();
+
+        let _: &mut MockMemory<f32> = tensor::mut_reference(&mut x, &a).unwrap();
+        let _: &MockMemory<f32> = tensor::reference(&x, &b).unwrap();
+
+        let pair = DevicePair {
+            source: "Mock Device #0".to_string(),
+            destination: "Mock Device #1".to_string(),
+            route: Route::DeviceToDevice,
+        };
+
+        assert_eq!(a.transfer_stats().pairs().len(), 1);
+        assert_eq!(b.transfer_stats().pairs()[&pair], TransferCount { transfers: 1, bytes: 16 });
+    }
+
+    #[test]
+    fn it_skips_synchronized_copies_and_failed_transfers() {
+        let device = MockDevice::new(0);
+        let x = tensor();
+
+        let _: &MockMemory<f32> = tensor::reference(&x, &device).unwrap();
+        let _: &MockMemory<f32> = tensor::reference(&x, &device).unwrap();
+        assert_eq!(device.transfer_stats().total().transfers, 1);
+
+        let other = MockDevice::new(1);
+        other.fail_transfers(true);
+        assert!(tensor::reference::<_, MockMemory<f32>>(&x, &other).is_err());
+        assert!(other.transfer_stats().is_empty());
+    }
+
+    #[test]
+    fn it_resets_the_counters() {
+        let backend: Backend = Backend::new::<Mock>().unwrap();
+        let x = tensor();
+
+        let _: &MockMemory<f32> = tensor::reference(&x, backend.active_device()).unwrap();
+        assert!(!backend.transfer_stats().is_empty());
+        assert!(format!("{}", backend.transfer_stats()).contains("host -> device"));
+
+        backend.reset_transfer_stats();
+        assert!(backend.transfer_stats().is_empty());
+    }
+
+    #[test]
+    fn it_keeps_no_stats_for_the_native_framework() {
+        let backend: Backend = Backend::new::<Native<()>>().unwrap();
+        let x = tensor();
+
+        x.as_slice().unwrap();
+        assert!(backend.transfer_stats().is_empty());
+    }
+}
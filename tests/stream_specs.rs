@@ -0,0 +1,102 @@
+extern crate parenchyma;
+
+#[cfg(test)]
+mod stream_spec {
+    use parenchyma::context::ContextCtor;
+    use parenchyma::error::ErrorKind;
+    use parenchyma::frameworks::{OpenCL, OpenCLAccess, OpenCLContext, OpenCLKernels,
+        OpenCLMemory};
+    use parenchyma::prelude::*;
+    use parenchyma::tensor;
+
+    const SOURCE: &'static str = "
+        __kernel void twice(__global const float* x, __global float* y) {
+            y[get_global_id(0)] = 2.0f * x[get_global_id(0)];
+        }
+
+        __kernel void increment(__global float* x) {
+            x[get_global_id(0)] += 1.0f;
+        }
+    ";
+
+    fn create_context() -> OpenCLContext<()> {
+        let framework: OpenCL<()> = OpenCL::new().unwrap();
+        OpenCLContext::new(&framework, &framework.default_selection()).unwrap()
+    }
+
+    #[test]
+    fn it_creates_streams_and_rejects_those_of_other_devices() {
+        let context = create_context();
+        let device = context.device();
+        assert_eq!(device.streams().len(), 1);
+        assert_eq!(device.stream().id(), 0);
+
+        let stream = device.create_stream(false).unwrap();
+        assert_eq!(stream.id(), 1);
+        device.set_stream(&stream).unwrap();
+        assert_eq!(device.stream().id(), 1);
+
+        let other = create_context();
+        let foreign = other.device().create_stream(false).unwrap();
+        let e = device.set_stream(&foreign).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidSelection);
+    }
+
+    #[test]
+    fn it_waits_for_the_uploads_of_other_streams() {
+        let context = create_context();
+        let device = context.device();
+        let program = context.program_builder().src(SOURCE).build().unwrap();
+        let kernels = OpenCLKernels::new(&program, context.devices(), &["twice"]).unwrap();
+
+        let ref x: SharedTensor = SharedTensor::with([4], vec![1., 2., 3., 4.]).unwrap();
+        let ref mut y: SharedTensor = SharedTensor::from([4]);
+
+        // upload `x` asynchronously on a second stream
+        let upload = device.create_stream(false).unwrap();
+        device.set_stream(&upload).unwrap();
+        let _: &OpenCLMemory<f32> = tensor::reference(x, device).unwrap();
+
+        // and compute on the default stream
+        device.set_stream(&device.streams()[0]).unwrap();
+
+        unsafe {
+            let x: &OpenCLMemory<f32> = tensor::reference(x, device).unwrap();
+            let y: &mut OpenCLMemory<f32> = tensor::mut_reference(y, device).unwrap();
+            let kernel = kernels.get(device, "twice").unwrap()
+                .arg_buf(x)
+                .arg_buf(&*y)
+                .gws([4]);
+            device.enqueue("twice", &kernel, &[OpenCLAccess::read(x), OpenCLAccess::write(y)])
+                .unwrap();
+        }
+
+        assert_eq!(y.as_slice().unwrap(), &[2., 4., 6., 8.]);
+    }
+
+    #[test]
+    fn it_orders_dependent_commands_on_out_of_order_streams() {
+        let context = create_context();
+        let device = context.device();
+        let program = context.program_builder().src(SOURCE).build().unwrap();
+        let kernels = OpenCLKernels::new(&program, context.devices(), &["increment"]).unwrap();
+
+        let stream = device.create_stream(true).unwrap();
+        device.set_stream(&stream).unwrap();
+
+        let ref mut x: SharedTensor = SharedTensor::from([1024]);
+
+        for _ in 0..3 {
+            unsafe {
+                let memory: &mut OpenCLMemory<f32> = tensor::mut_reference(x, device).unwrap();
+                let kernel = kernels.get(device, "increment").unwrap()
+                    .arg_buf(&*memory)
+                    .gws([1024]);
+                device.enqueue("increment", &kernel, &[OpenCLAccess::write(memory)]).unwrap();
+            }
+        }
+
+        context.device().synchronize().unwrap();
+        assert!(x.as_slice().unwrap().iter().all(|&value| value == 3.));
+    }
+}
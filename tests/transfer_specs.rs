@@ -0,0 +1,68 @@
+extern crate parenchyma;
+
+#[cfg(test)]
+mod transfer_spec {
+    use parenchyma::context::{Context, ContextCtor};
+    use parenchyma::frameworks::{OpenCL, OpenCLContext, OpenCLMemory};
+    use parenchyma::prelude::*;
+    use parenchyma::stats::{Route, TransferCount};
+    use parenchyma::tensor;
+
+    fn create_context(selection: &[usize]) -> OpenCLContext<()> {
+        let framework: OpenCL<()> = OpenCL::new().unwrap();
+        let hardware = framework.default_selection();
+        let selection: Vec<_> = selection.iter().map(|&i| hardware[i].clone()).collect();
+        OpenCLContext::new(&framework, &selection).unwrap()
+    }
+
+    /// Creates a context of two devices - the first two devices of the platform, or two 
+    /// sub-devices of its first device.
+    fn create_context_with_two_devices() -> OpenCLContext<()> {
+        let mut framework: OpenCL<()> = OpenCL::new().unwrap();
+        let mut selection = framework.default_selection();
+
+        if selection.len() < 2 {
+            let compute_units = selection[0].compute_units / 2;
+            selection = framework.partition_equally(&selection[0], compute_units)
+                .expect("requires two Open CL devices, or a device that can be partitioned");
+        }
+
+        OpenCLContext::new(&framework, &selection[..2]).unwrap()
+    }
+
+    #[test]
+    fn it_copies_between_the_devices_of_a_context() {
+        let mut context = create_context_with_two_devices();
+        let ref mut x: SharedTensor = SharedTensor::with([4], vec![1., 2., 3., 4.]).unwrap();
+
+        // make the copy of the first device the only up-to-date one
+        let _: &mut OpenCLMemory<f32> = tensor::mut_reference(x, context.device()).unwrap();
+        context.reset_transfer_stats();
+
+        context.activate(1).unwrap();
+        let _: &OpenCLMemory<f32> = tensor::reference(x, context.device()).unwrap();
+
+        let stats = context.transfer_stats();
+        assert_eq!(stats.route(Route::DeviceToDevice), TransferCount { transfers: 1, bytes: 16 });
+        assert_eq!(stats.total().transfers, 1);
+
+        assert_eq!(x.as_slice().unwrap(), &[1., 2., 3., 4.]);
+    }
+
+    #[test]
+    fn it_stages_copies_between_contexts_through_the_host() {
+        let (a, b) = (create_context(&[0]), create_context(&[0]));
+        let ref mut x: SharedTensor = SharedTensor::with([4], vec![1., 2., 3., 4.]).unwrap();
+
+        let _: &mut OpenCLMemory<f32> = tensor::mut_reference(x, a.device()).unwrap();
+        let _: &OpenCLMemory<f32> = tensor::reference(x, b.device()).unwrap();
+
+        let stats = b.transfer_stats();
+        assert_eq!(stats.route(Route::DeviceToDevice), TransferCount { transfers: 1, bytes: 16 });
+        assert_eq!(stats.route(Route::HostToDevice).transfers, 0);
+
+        // make the copy of `b` the latest one, so the data is read back from it
+        let _: &mut OpenCLMemory<f32> = tensor::mut_reference(x, b.device()).unwrap();
+        assert_eq!(x.as_slice().unwrap(), &[1., 2., 3., 4.]);
+    }
+}